@@ -0,0 +1,239 @@
+use criterion::{
+    BenchmarkId,
+    Criterion,
+    Throughput,
+    black_box,
+    criterion_group,
+    criterion_main
+};
+
+use branchy::{
+    Symbol,
+    Rule,
+    ExpanderBuilder,
+    WeightedRandomRuleSelector,
+    ProbabilisticGrammar,
+    RuleDistribution,
+    CoverageGenerator,
+    display_grammar,
+    parse_grammar
+};
+
+//
+// Constants
+//
+
+/// Number of sequences generated per benchmark iteration, so that throughput is reported in sequences per second.
+const BATCH_SIZE: usize = 100;
+
+/// Numbers of rules in flat grammars, showing how expansion scales with the size of a grammar.
+const FLAT_GRAMMAR_SIZES: [usize; 3] = [100, 1000, 10_000];
+
+/// Numbers of non-terminal symbols in the input, showing how expansion scales with the length of the output.
+const INPUT_LENGTHS: [usize; 3] = [1, 10, 100];
+
+/// Enough for virtually every expansion of the expression grammar, whose expected number of iterations is finite.
+const EXPRESSION_MAX_ITERATIONS: usize = 1_000_000;
+
+const NAMES_GRAMMAR: &str = r#"
+    name     -> <first> " " <last> | <first> " " <initial> " " <last> [0.5] | <title> " " <last> [0.25]
+    first    -> "Alice" | "Bob" | "Carol" | "Dave" | "Erin" | "Frank" | "Grace" | "Heidi"
+    last     -> <root> | <root> <suffix> [2]
+    root     -> "Smith" | "Stone" | "Wood" | "Brook" | "Field" | "Hill"
+    suffix   -> "son" | "er" | "ley" | "ford"
+    initial  -> "A." | "J." | "M." | "R."
+    title    -> "Dr." | "Prof." | "Sir"
+"#;
+
+const EXPRESSION_GRAMMAR: &str = r#"
+    expr     -> <term> [3] | <term> "+" <expr> | <term> "-" <expr>
+    term     -> <factor> [3] | <factor> "*" <term> | <factor> "/" <term>
+    factor   -> <number> [3] | <variable> [3] | "(" <expr> ")" [2] | "-" <factor>
+    number   -> <digit> [2] | <digit> <number>
+    digit    -> "0" | "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9"
+    variable -> "x" | "y" | "z"
+"#;
+
+//
+// Benchmarks
+//
+
+fn expansion(c: &mut Criterion)
+{
+    let mut group = c.benchmark_group("expansion");
+
+    group.throughput(Throughput::Elements(BATCH_SIZE as u64));
+
+    let names_input = [Symbol::Nonterminal("name".to_string())];
+
+    let mut names_expander = ExpanderBuilder::from(grammar(NAMES_GRAMMAR))
+        .with_rule_selector(WeightedRandomRuleSelector::new())
+        .build();
+
+    group.bench_function("names/expand", |b| b.iter(|| {
+        for _ in 0..BATCH_SIZE {
+            black_box(names_expander.expand(names_input.to_vec()).unwrap());
+        }
+    }));
+
+    let mut output = Vec::new();
+
+    group.bench_function("names/expand_into", |b| b.iter(|| {
+        for _ in 0..BATCH_SIZE {
+            output.clear();
+
+            names_expander.expand_into(&names_input, &mut output).unwrap();
+
+            black_box(&output);
+        }
+    }));
+
+    let expression_input = [Symbol::Nonterminal("expr".to_string())];
+
+    let mut expression_expander = ExpanderBuilder::from(grammar(EXPRESSION_GRAMMAR))
+        .with_rule_selector(WeightedRandomRuleSelector::new())
+        .with_max_iterations(EXPRESSION_MAX_ITERATIONS)
+        .build();
+
+    assert!(expression_expander.probabilistic_grammar().divergent_nonterms().is_empty());
+
+    group.bench_function("expression/expand", |b| b.iter(|| {
+        for _ in 0..BATCH_SIZE {
+            black_box(expression_expander.expand(expression_input.to_vec()).unwrap());
+        }
+    }));
+
+    group.bench_function("expression/expand_into", |b| b.iter(|| {
+        for _ in 0..BATCH_SIZE {
+            output.clear();
+
+            expression_expander.expand_into(&expression_input, &mut output).unwrap();
+
+            black_box(&output);
+        }
+    }));
+
+    for &rule_count in &FLAT_GRAMMAR_SIZES {
+        let mut flat_expander = ExpanderBuilder::from(flat_grammar(rule_count)).build();
+
+        group.bench_with_input(BenchmarkId::new("flat/expand", rule_count), &rule_count, |b, _| b.iter(|| {
+            for _ in 0..BATCH_SIZE {
+                black_box(flat_expander.expand(vec![Symbol::Nonterminal("sentence")]).unwrap());
+            }
+        }));
+    }
+
+    group.finish();
+
+    let mut group = c.benchmark_group("expansion_by_input_length");
+
+    for &input_length in &INPUT_LENGTHS {
+        let input = vec![Symbol::Nonterminal("name".to_string()); input_length];
+
+        group.throughput(Throughput::Elements(input_length as u64));
+
+        group.bench_with_input(BenchmarkId::new("names/expand_into", input_length), &input, |b, input| b.iter(|| {
+            output.clear();
+
+            names_expander.expand_into(input, &mut output).unwrap();
+
+            black_box(&output);
+        }));
+    }
+
+    group.finish();
+}
+
+fn enumeration(c: &mut Criterion)
+{
+    let mut group = c.benchmark_group("enumeration");
+
+    // Covering the largest flat grammar takes a considerable fraction of a second.
+    group.sample_size(20);
+
+    let names_rules      = grammar(NAMES_GRAMMAR);
+    let expression_rules = grammar(EXPRESSION_GRAMMAR);
+
+    group.bench_function("names/coverage", |b| b.iter(|| {
+        black_box(CoverageGenerator::new(&names_rules).generate(&[Symbol::Nonterminal("name".to_string())]))
+    }));
+
+    group.bench_function("expression/coverage", |b| b.iter(|| {
+        black_box(CoverageGenerator::new(&expression_rules).generate(&[Symbol::Nonterminal("expr".to_string())]))
+    }));
+
+    for &rule_count in &FLAT_GRAMMAR_SIZES {
+        let flat_rules = flat_grammar(rule_count);
+
+        group.throughput(Throughput::Elements(rule_count as u64));
+
+        group.bench_with_input(BenchmarkId::new("flat/coverage", rule_count), &flat_rules, |b, flat_rules| b.iter(|| {
+            black_box(CoverageGenerator::new(flat_rules).generate(&[Symbol::Nonterminal("sentence")]))
+        }));
+    }
+
+    group.finish();
+}
+
+fn parsing(c: &mut Criterion)
+{
+    let mut group = c.benchmark_group("parsing");
+
+    group.bench_function("names/parse_grammar", |b| b.iter(|| {
+        black_box(parse_grammar(black_box(NAMES_GRAMMAR)).unwrap())
+    }));
+
+    group.bench_function("expression/parse_grammar", |b| b.iter(|| {
+        black_box(parse_grammar(black_box(EXPRESSION_GRAMMAR)).unwrap())
+    }));
+
+    let expression_rules = grammar(EXPRESSION_GRAMMAR);
+
+    let probabilistic_grammar = ProbabilisticGrammar::new(&expression_rules, RuleDistribution::Weighted);
+
+    let expression_input = [Symbol::Nonterminal("expr".to_string())];
+
+    let sentence: Vec<String> = "( x + 1 2 ) * - y / 3 - z".split(' ').map(str::to_string).collect();
+
+    assert!(probabilistic_grammar.sentence_probability(&expression_input, &sentence) > 0.0);
+
+    group.bench_function("expression/sentence_probability", |b| b.iter(|| {
+        black_box(probabilistic_grammar.sentence_probability(&expression_input, &sentence))
+    }));
+
+    for &rule_count in &FLAT_GRAMMAR_SIZES {
+        let text = display_grammar(&flat_grammar(rule_count)).to_string();
+
+        group.throughput(Throughput::Elements(rule_count as u64));
+
+        group.bench_with_input(BenchmarkId::new("flat/parse_grammar", rule_count), &text, |b, text| b.iter(|| {
+            black_box(parse_grammar(text).unwrap())
+        }));
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, expansion, enumeration, parsing);
+criterion_main!(benches);
+
+//
+// Service
+//
+
+fn grammar(text: &str) -> Vec<Rule<String, String>>
+{
+    parse_grammar(text).expect("benchmark grammar must be valid")
+}
+
+/// Returns a grammar of `rule_count` rules, all but one of which are alternatives for the same non-terminal symbol,
+/// so that every rule selection has to consider the whole grammar.
+fn flat_grammar(rule_count: usize) -> Vec<Rule<&'static str, String>>
+{
+    let word_rules = (1..rule_count)
+        .map(|word_idx| Rule::new("word", vec![Symbol::Terminal(format!("word{}", word_idx))]));
+
+    std::iter::once(Rule::new("sentence", vec![Symbol::Nonterminal("word"); 4]))
+        .chain(word_rules)
+        .collect()
+}
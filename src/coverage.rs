@@ -0,0 +1,422 @@
+use std::collections::{
+    HashSet,
+    VecDeque
+};
+
+use crate::grammar::{
+    NonterminalValue,
+    TerminalValue,
+    Symbol,
+    Rule,
+    indexed::{
+        IndexedGrammar,
+        MinimalDerivations
+    }
+};
+use crate::derivation::{
+    Derivation,
+    DerivationTree,
+    build_tree,
+    build_minimal_tree
+};
+
+//
+// Interface types
+//
+
+//
+// CoverageCriterion: Debug + Clone + Copy + PartialEq + Eq
+//
+
+/// Determines what a [`CoverageSuite`](struct.CoverageSuite.html) generated by
+/// [`CoverageGenerator`](struct.CoverageGenerator.html) must cover.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoverageCriterion {
+    /// Every rule must be applied at least once.
+    Rules,
+    /// Every rule must be applied at least once and for every rule, every rule which can expand
+    /// a non-terminal symbol from its replacement, must be applied to such a symbol at least once.
+    RulePairs
+}
+
+//
+// CoverageGenerator<'r, Nt, T>
+//
+
+/// Generates a small set of derivations, which together use every rule of a grammar
+/// (or every parent-child pair of rules, depending on [`CoverageCriterion`](enum.CoverageCriterion.html)).
+///
+/// Unlike repeated random expansion, which may take arbitrarily long to hit rarely selected rules,
+/// the generator directs each derivation towards a rule not covered yet, completing the rest of the
+/// derivation as briefly as possible while opportunistically covering other rules on the way.
+///
+/// ```
+/// use branchy::{
+///     Symbol,
+///     Rule,
+///     CoverageGenerator
+/// };
+///
+/// let rules = vec![
+///     Rule::new("greeting", vec![Symbol::Terminal("Hello"), Symbol::Nonterminal("name")]),
+///     Rule::new("greeting", vec![Symbol::Terminal("Hi"), Symbol::Nonterminal("name")]),
+///     Rule::new("name", vec![Symbol::Terminal("Alice")]),
+///     Rule::new("name", vec![Symbol::Terminal("Bob")])
+/// ];
+///
+/// let coverage_suite = CoverageGenerator::new(&rules).generate(&[Symbol::Nonterminal("greeting")]);
+///
+/// assert_eq!(coverage_suite.derivations.len(), 2);
+/// assert!(coverage_suite.uncovered_rule_idxs.is_empty());
+/// ```
+pub struct CoverageGenerator<'r, Nt, T> {
+    rules:     &'r [Rule<Nt, T>],
+    criterion: CoverageCriterion
+}
+
+impl<'r, Nt, T> CoverageGenerator<'r, Nt, T>
+    where Nt: NonterminalValue,
+          T:  TerminalValue
+{
+    //
+    // Interface
+    //
+
+    /// Creates a generator for the given `rules` with [`CoverageCriterion::Rules`](enum.CoverageCriterion.html#variant.Rules).
+    #[must_use]
+    pub fn new(rules: &'r [Rule<Nt, T>]) -> Self {
+        Self{
+            rules,
+            criterion: CoverageCriterion::Rules
+        }
+    }
+
+    #[must_use]
+    pub fn with_criterion(self, criterion: CoverageCriterion) -> Self {
+        Self{
+            criterion,
+            ..self
+        }
+    }
+
+    /// Generates derivations of the `input` sequence, which together satisfy the coverage criterion.
+    ///
+    /// Rules, which cannot be used in any complete derivation of `input` (since they are unreachable from it
+    /// or lead to non-terminal symbols which cannot be fully expanded), are listed in the
+    /// [`uncovered_rule_idxs`](struct.CoverageSuite.html#structfield.uncovered_rule_idxs) of the result.
+    /// If `input` itself cannot be fully expanded, no derivations are generated.
+    #[must_use]
+    pub fn generate(&self, input: &[Symbol<Nt, T>]) -> CoverageSuite<Nt, T> {
+        let grammar             = IndexedGrammar::new(self.rules);
+        let minimal_derivations = grammar.minimal_derivations();
+
+        let maybe_input_nonterm_idxs: Option<Vec<_>> = input.iter()
+            .map(|symbol| match symbol {
+                Symbol::Nonterminal(value) => grammar.nonterm_idx(value)
+                    .filter(|nonterm_idx| minimal_derivations.costs[*nonterm_idx].is_some())
+                    .map(Some),
+                Symbol::Terminal(_) => Some(None)
+            })
+            .collect();
+
+        let input_nonterm_idxs = if let Some(input_nonterm_idxs) = maybe_input_nonterm_idxs {
+            input_nonterm_idxs
+        } else {
+            return CoverageSuite{
+                derivations:         Vec::new(),
+                uncovered_rule_idxs: (0..self.rules.len()).collect()
+            };
+        };
+
+        let mut builder = DerivationBuilder::new(&grammar, &minimal_derivations, self.criterion, &input_nonterm_idxs);
+
+        let mut derivations = Vec::new();
+
+        while let Some(target) = builder.next_target() {
+            let trees = builder.build_derivation(input, &input_nonterm_idxs, target);
+
+            derivations.push(Derivation::new(trees));
+        }
+
+        CoverageSuite{
+            derivations,
+            uncovered_rule_idxs: (0..self.rules.len())
+                .filter(|rule_idx| !builder.covered_rules[*rule_idx])
+                .collect()
+        }
+    }
+}
+
+//
+// CoverageSuite<Nt, T>: Debug + Clone
+//
+
+/// Result of [`CoverageGenerator::generate()`](struct.CoverageGenerator.html#method.generate).
+#[derive(Debug, Clone)]
+pub struct CoverageSuite<Nt, T> {
+    /// Generated derivations, rule indices in which refer to the rules given to the generator.
+    pub derivations:         Vec<Derivation<Nt, T>>,
+    /// Indices of the rules, which could not be used in any complete derivation.
+    pub uncovered_rule_idxs: Vec<usize>
+}
+
+impl<Nt, T> CoverageSuite<Nt, T>
+    where Nt: NonterminalValue,
+          T:  TerminalValue
+{
+    /// Returns the fully expanded sequences of terminal values, one for each derivation.
+    #[must_use]
+    pub fn outputs(&self) -> Vec<Vec<T>> {
+        self.derivations.iter()
+            .map(Derivation::terminals)
+            .collect()
+    }
+}
+
+//
+// Service types
+//
+
+#[derive(Clone, Copy)]
+enum Target {
+    Rule(usize),
+    RulePair(usize, usize)
+}
+
+#[derive(Clone, Copy)]
+enum Origin {
+    Input(usize),
+    Rule(usize, usize)
+}
+
+struct DerivationBuilder<'g, 'r, Nt, T> {
+    grammar:             &'g IndexedGrammar<'r, Nt, T>,
+    minimal_derivations: &'g MinimalDerivations,
+    criterion:           CoverageCriterion,
+    productive_rules:    Vec<bool>,
+    origins:             Vec<Option<Origin>>,
+    covered_rules:       Vec<bool>,
+    covered_rule_pairs:  HashSet<(usize, usize)>
+}
+
+impl<'g, 'r, Nt, T> DerivationBuilder<'g, 'r, Nt, T>
+    where Nt: NonterminalValue,
+          T:  TerminalValue
+{
+    fn new(
+        grammar:             &'g IndexedGrammar<'r, Nt, T>,
+        minimal_derivations: &'g MinimalDerivations,
+        criterion:           CoverageCriterion,
+        input_nonterm_idxs:  &[Option<usize>]
+    ) -> Self {
+        let productive_rules = grammar.productive_rules(minimal_derivations);
+
+        // Breadth-first search for the shortest way to reach every non-terminal from the input.
+        let mut origins = vec![None; grammar.nonterm_count()];
+        let mut queue   = VecDeque::new();
+
+        for (position, maybe_nonterm_idx) in input_nonterm_idxs.iter().enumerate() {
+            if let Some(nonterm_idx) = *maybe_nonterm_idx {
+                if origins[nonterm_idx].is_none() {
+                    origins[nonterm_idx] = Some(Origin::Input(position));
+
+                    queue.push_back(nonterm_idx);
+                }
+            }
+        }
+
+        while let Some(nonterm_idx) = queue.pop_front() {
+            for &rule_idx in &grammar.rules_by_nonterm[nonterm_idx] {
+                if !productive_rules[rule_idx] {
+                    continue;
+                }
+
+                for (position, maybe_child_idx) in grammar.rule_symbols[rule_idx].iter().enumerate() {
+                    if let Some(child_idx) = *maybe_child_idx {
+                        if origins[child_idx].is_none() {
+                            origins[child_idx] = Some(Origin::Rule(rule_idx, position));
+
+                            queue.push_back(child_idx);
+                        }
+                    }
+                }
+            }
+        }
+
+        Self{
+            grammar,
+            minimal_derivations,
+            criterion,
+            productive_rules,
+            origins,
+            covered_rules:      vec![false; grammar.rules.len()],
+            covered_rule_pairs: HashSet::new()
+        }
+    }
+
+    fn next_target(&self) -> Option<Target> {
+        let mut coverable_rule_idxs = (0..self.grammar.rules.len())
+            .filter(|rule_idx| self.is_coverable(*rule_idx));
+
+        match self.criterion {
+            CoverageCriterion::Rules => coverable_rule_idxs
+                .find(|rule_idx| !self.covered_rules[*rule_idx])
+                .map(Target::Rule),
+            CoverageCriterion::RulePairs => coverable_rule_idxs
+                .find_map(|rule_idx| {
+                    if !self.covered_rules[rule_idx] {
+                        return Some(Target::Rule(rule_idx));
+                    }
+
+                    self.grammar.rule_symbols[rule_idx].iter()
+                        .flatten()
+                        .flat_map(|child_idx| self.grammar.rules_by_nonterm[*child_idx].iter())
+                        .find(|child_rule_idx| {
+                            self.productive_rules[**child_rule_idx]
+                                && !self.covered_rule_pairs.contains(&(rule_idx, **child_rule_idx))
+                        })
+                        .map(|child_rule_idx| Target::RulePair(rule_idx, *child_rule_idx))
+                })
+        }
+    }
+
+    fn build_derivation(
+        &mut self,
+        input:              &[Symbol<Nt, T>],
+        input_nonterm_idxs: &[Option<usize>],
+        target:             Target
+    ) -> Vec<DerivationTree<Nt, T>> {
+        let target_rule_idx = match target {
+            Target::Rule(rule_idx) | Target::RulePair(rule_idx, _) => rule_idx
+        };
+
+        // Rules (along with positions in their replacements) leading from the input to the target rule's pattern.
+        let mut path            = Vec::new();
+        let mut current_nonterm = self.grammar.rule_patterns[target_rule_idx];
+
+        let input_position = loop {
+            match self.origins[current_nonterm].expect("target rules must be reachable") {
+                Origin::Input(position) => break position,
+                Origin::Rule(rule_idx, position) => {
+                    path.push((rule_idx, position));
+
+                    current_nonterm = self.grammar.rule_patterns[rule_idx];
+                }
+            }
+        };
+
+        path.reverse();
+
+        input.iter()
+            .zip(input_nonterm_idxs)
+            .enumerate()
+            .map(|(position, (symbol, maybe_nonterm_idx))| match (symbol, maybe_nonterm_idx) {
+                (Symbol::Terminal(value), _) => DerivationTree::Terminal(value.clone()),
+                (Symbol::Nonterminal(_), Some(nonterm_idx)) => if position == input_position {
+                    self.build_path(&path, target, None)
+                } else {
+                    self.complete(*nonterm_idx, None)
+                },
+                (Symbol::Nonterminal(_), None) => unreachable!("input non-terminals must be productive")
+            })
+            .collect()
+    }
+
+    fn build_path(
+        &mut self,
+        path:             &[(usize, usize)],
+        target:           Target,
+        maybe_parent_idx: Option<usize>
+    ) -> DerivationTree<Nt, T> {
+        let grammar = self.grammar;
+
+        if let Some(((rule_idx, path_position), remaining_path)) = path.split_first() {
+            self.mark_covered(*rule_idx, maybe_parent_idx);
+
+            build_tree(grammar, *rule_idx, |position, child_idx| {
+                if position == *path_position {
+                    self.build_path(remaining_path, target, Some(*rule_idx))
+                } else {
+                    self.complete(child_idx, Some(*rule_idx))
+                }
+            })
+        } else {
+            match target {
+                Target::Rule(rule_idx) => self.build_node(rule_idx, maybe_parent_idx),
+                Target::RulePair(rule_idx, child_rule_idx) => {
+                    self.mark_covered(rule_idx, maybe_parent_idx);
+
+                    let child_pattern        = grammar.rule_patterns[child_rule_idx];
+                    let mut is_child_pending = true;
+
+                    build_tree(grammar, rule_idx, |_, child_idx| {
+                        if is_child_pending && child_idx == child_pattern {
+                            is_child_pending = false;
+
+                            self.build_node(child_rule_idx, Some(rule_idx))
+                        } else {
+                            self.complete(child_idx, Some(rule_idx))
+                        }
+                    })
+                }
+            }
+        }
+    }
+
+    fn complete(&mut self, nonterm_idx: usize, maybe_parent_idx: Option<usize>) -> DerivationTree<Nt, T> {
+        let productive_rule_idxs = self.grammar.rules_by_nonterm[nonterm_idx].iter()
+            .copied()
+            .filter(|rule_idx| self.productive_rules[*rule_idx]);
+
+        // Prefer rules and rule pairs not covered yet. Every such choice covers something new,
+        // so there can only be a finite number of them, which guarantees termination.
+        let maybe_uncovered_rule_idx = match (self.criterion, maybe_parent_idx) {
+            (CoverageCriterion::RulePairs, Some(parent_idx)) => productive_rule_idxs
+                .filter(|rule_idx| !self.covered_rule_pairs.contains(&(parent_idx, *rule_idx)))
+                .min_by_key(|rule_idx| self.covered_rules[*rule_idx]),
+            _ => productive_rule_idxs
+                .filter(|rule_idx| !self.covered_rules[*rule_idx])
+                .min()
+        };
+
+        if let Some(rule_idx) = maybe_uncovered_rule_idx {
+            self.build_node(rule_idx, maybe_parent_idx)
+        } else {
+            let minimal_tree = build_minimal_tree(self.grammar, self.minimal_derivations, nonterm_idx);
+
+            self.mark_tree_covered(&minimal_tree, maybe_parent_idx);
+
+            minimal_tree
+        }
+    }
+
+    fn build_node(&mut self, rule_idx: usize, maybe_parent_idx: Option<usize>) -> DerivationTree<Nt, T> {
+        let grammar = self.grammar;
+
+        self.mark_covered(rule_idx, maybe_parent_idx);
+
+        build_tree(grammar, rule_idx, |_, child_idx| self.complete(child_idx, Some(rule_idx)))
+    }
+
+    fn mark_covered(&mut self, rule_idx: usize, maybe_parent_idx: Option<usize>) {
+        self.covered_rules[rule_idx] = true;
+
+        if let Some(parent_idx) = maybe_parent_idx {
+            self.covered_rule_pairs.insert((parent_idx, rule_idx));
+        }
+    }
+
+    fn mark_tree_covered(&mut self, tree: &DerivationTree<Nt, T>, maybe_parent_idx: Option<usize>) {
+        if let Some(rule_idx) = tree.rule_idx() {
+            self.mark_covered(rule_idx, maybe_parent_idx);
+
+            for child in tree.children() {
+                self.mark_tree_covered(child, Some(rule_idx));
+            }
+        }
+    }
+
+    fn is_coverable(&self, rule_idx: usize) -> bool {
+        self.productive_rules[rule_idx] && self.origins[self.grammar.rule_patterns[rule_idx]].is_some()
+    }
+}
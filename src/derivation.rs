@@ -0,0 +1,354 @@
+pub(crate) mod random;
+#[cfg(any(feature = "proptest", feature = "quickcheck"))]
+pub(crate) mod shrink;
+
+use crate::grammar::{
+    NonterminalValue,
+    TerminalValue,
+    Symbol,
+    Rule,
+    indexed::{
+        IndexedGrammar,
+        MinimalDerivations
+    }
+};
+
+//
+// Interface types
+//
+
+//
+// DerivationTree<Nt, T>: Debug + Clone + PartialEq
+//
+
+/// Describes how a single symbol was expanded.
+///
+/// Non-terminal nodes refer to the applied rules by their indices in the set of rules,
+/// from which the tree was derived.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DerivationTree<Nt, T> {
+    /// A terminal symbol, which is a leaf of the tree.
+    Terminal(T),
+    /// A non-terminal symbol with `value`, expanded using the rule with index `rule_idx`
+    /// into `children` - one for every symbol of that rule's replacement.
+    Nonterminal{
+        value:    Nt,
+        rule_idx: usize,
+        children: Vec<DerivationTree<Nt, T>>
+    }
+}
+
+impl<Nt, T> DerivationTree<Nt, T> {
+    /// Returns the symbol at the root of this tree.
+    pub fn symbol(&self) -> Symbol<&Nt, &T> {
+        match self {
+            Self::Terminal(value)        => Symbol::Terminal(value),
+            Self::Nonterminal{value, ..} => Symbol::Nonterminal(value)
+        }
+    }
+
+    /// Returns the index of the rule applied at the root of this tree,
+    /// or `None` if this tree is a single terminal.
+    pub fn rule_idx(&self) -> Option<usize> {
+        match self {
+            Self::Terminal(_)               => None,
+            Self::Nonterminal{rule_idx, ..} => Some(*rule_idx)
+        }
+    }
+
+    /// Returns the subtrees of the root, which are empty for a terminal.
+    pub fn children(&self) -> &[Self] {
+        match self {
+            Self::Terminal(_)               => &[],
+            Self::Nonterminal{children, ..} => children
+        }
+    }
+
+    /// Number of nodes in this tree.
+    pub fn node_count(&self) -> usize {
+        1 + self.children().iter()
+            .map(Self::node_count)
+            .sum::<usize>()
+    }
+
+    /// Number of nodes on the longest path from the root to a leaf.
+    pub fn depth(&self) -> usize {
+        1 + self.children().iter()
+            .map(Self::depth)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the subtree at `path`, which lists child indices starting from the root.
+    ///
+    /// # Panics
+    /// Panics if `path` does not lead to a node of this tree.
+    #[must_use]
+    pub fn subtree(&self, path: &[usize]) -> &Self {
+        path.iter().fold(self, |tree, child_idx| &tree.children()[*child_idx])
+    }
+
+    /// Returns a mutable reference to the subtree at `path`, which lists child indices starting from the root.
+    ///
+    /// # Panics
+    /// Panics if `path` does not lead to a node of this tree.
+    pub fn subtree_mut(&mut self, path: &[usize]) -> &mut Self {
+        path.iter().fold(self, |tree, child_idx| match tree {
+            Self::Nonterminal{children, ..} => &mut children[*child_idx],
+            Self::Terminal(_)               => panic!("path must only lead through non-terminal nodes")
+        })
+    }
+
+    /// Calls `f` for every applied rule's index in the order of a leftmost derivation,
+    /// i.e. in the order in which [`Expander`](struct.Expander.html) would apply them.
+    pub fn for_each_rule_idx<F>(&self, f: &mut F)
+        where F: FnMut(usize)
+    {
+        if let Self::Nonterminal{rule_idx, children, ..} = self {
+            f(*rule_idx);
+
+            for child in children {
+                child.for_each_rule_idx(f);
+            }
+        }
+    }
+}
+
+impl<Nt, T> DerivationTree<Nt, T>
+    where T: TerminalValue
+{
+    /// Returns the fully expanded sequence of terminal values at the leaves of this tree.
+    pub fn terminals(&self) -> Vec<T> {
+        let mut terminals = Vec::new();
+
+        self.collect_terminals(&mut terminals);
+
+        terminals
+    }
+
+    /// Appends the terminal values at the leaves of this tree to `terminals`.
+    pub fn collect_terminals(&self, terminals: &mut Vec<T>) {
+        match self {
+            Self::Terminal(value)           => terminals.push(value.clone()),
+            Self::Nonterminal{children, ..} => {
+                for child in children {
+                    child.collect_terminals(terminals);
+                }
+            }
+        }
+    }
+}
+
+//
+// Derivation<Nt, T>: Debug + Clone + PartialEq
+//
+
+/// Describes a complete expansion of an input sequence: one [`DerivationTree`](enum.DerivationTree.html)
+/// for every symbol of the input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Derivation<Nt, T> {
+    pub trees: Vec<DerivationTree<Nt, T>>
+}
+
+impl<Nt, T> Derivation<Nt, T> {
+    #[must_use]
+    pub fn new(trees: Vec<DerivationTree<Nt, T>>) -> Self {
+        Self{trees}
+    }
+
+    /// Total number of nodes in all trees of this derivation.
+    #[must_use]
+    pub fn node_count(&self) -> usize {
+        self.trees.iter()
+            .map(DerivationTree::node_count)
+            .sum()
+    }
+
+    /// Returns indices of the applied rules in the order of a leftmost derivation,
+    /// i.e. in the order in which [`Expander`](struct.Expander.html) would apply them.
+    #[must_use]
+    pub fn rule_idxs(&self) -> Vec<usize> {
+        let mut rule_idxs = Vec::new();
+
+        for tree in &self.trees {
+            tree.for_each_rule_idx(&mut |rule_idx| rule_idxs.push(rule_idx));
+        }
+
+        rule_idxs
+    }
+}
+
+impl<Nt, T> Derivation<Nt, T>
+    where Nt: NonterminalValue,
+          T:  TerminalValue
+{
+    /// Returns the input sequence, from which this derivation starts.
+    #[must_use]
+    pub fn input(&self) -> Vec<Symbol<Nt, T>> {
+        self.trees.iter()
+            .map(|tree| match tree.symbol() {
+                Symbol::Nonterminal(value) => Symbol::Nonterminal(value.clone()),
+                Symbol::Terminal(value)    => Symbol::Terminal(value.clone())
+            })
+            .collect()
+    }
+
+    /// Reconstructs a derivation of `input` from indices of `rules`, applied in the order of a leftmost derivation,
+    /// as returned by [`rule_idxs()`](#method.rule_idxs).
+    ///
+    /// Returns `None` if the indices do not describe a complete derivation of `input`, i.e. if there are
+    /// too few or too many of them, or if some rule cannot be applied to the symbol it is expected to expand.
+    #[must_use]
+    pub fn from_rule_idxs(rules: &[Rule<Nt, T>], input: &[Symbol<Nt, T>], rule_idxs: &[usize]) -> Option<Self> {
+        let mut rule_idxs = rule_idxs.iter().copied();
+
+        let trees = input.iter()
+            .map(|symbol| build_tree_from_rule_idxs(rules, symbol, &mut rule_idxs))
+            .collect::<Option<Vec<_>>>()?;
+
+        if rule_idxs.next().is_some() {
+            return None;
+        }
+
+        Some(Self::new(trees))
+    }
+
+    /// Returns the fully expanded sequence of terminal values, i.e. the result of this derivation.
+    #[must_use]
+    pub fn terminals(&self) -> Vec<T> {
+        let mut terminals = Vec::new();
+
+        for tree in &self.trees {
+            tree.collect_terminals(&mut terminals);
+        }
+
+        terminals
+    }
+}
+
+//
+// Friend interface
+//
+
+/// Builds a tree by applying `rule_idx` to its pattern and letting `expand_child` build the subtree
+/// for every non-terminal symbol of the replacement, given the symbol's position and non-terminal index.
+pub(crate) fn build_tree<Nt, T, F>(
+    grammar:          &IndexedGrammar<Nt, T>,
+    rule_idx:         usize,
+    mut expand_child: F
+) -> DerivationTree<Nt, T>
+    where Nt: NonterminalValue,
+          T:  TerminalValue,
+          F:  FnMut(usize, usize) -> DerivationTree<Nt, T>
+{
+    let rule = &grammar.rules[rule_idx];
+
+    let children = rule.replacement.iter()
+        .zip(grammar.rule_symbols[rule_idx].iter())
+        .enumerate()
+        .map(|(position, (symbol, maybe_nonterm_idx))| match (symbol, maybe_nonterm_idx) {
+            (Symbol::Terminal(value), _)                => DerivationTree::Terminal(value.clone()),
+            (Symbol::Nonterminal(_), Some(nonterm_idx)) => expand_child(position, *nonterm_idx),
+            (Symbol::Nonterminal(_), None)              => unreachable!("non-terminal symbols must be indexed")
+        })
+        .collect();
+
+    DerivationTree::Nonterminal{
+        value: rule.pattern.clone(),
+        rule_idx,
+        children
+    }
+}
+
+/// Builds the tree with the minimal number of nodes for a productive non-terminal.
+pub(crate) fn build_minimal_tree<Nt, T>(
+    grammar:             &IndexedGrammar<Nt, T>,
+    minimal_derivations: &MinimalDerivations,
+    nonterm_idx:         usize
+) -> DerivationTree<Nt, T>
+    where Nt: NonterminalValue,
+          T:  TerminalValue
+{
+    let rule_idx = minimal_derivations.best_rules[nonterm_idx]
+        .expect("minimal trees must only be built for productive non-terminals");
+
+    build_tree(grammar, rule_idx, |_, child_nonterm_idx| {
+        build_minimal_tree(grammar, minimal_derivations, child_nonterm_idx)
+    })
+}
+
+/// Returns the subtree at `path`, which starts with the index of a tree in `derivation`, followed by child indices.
+pub(crate) fn subtree_at<'d, Nt, T>(derivation: &'d Derivation<Nt, T>, path: &[usize]) -> &'d DerivationTree<Nt, T> {
+    derivation.trees[path[0]].subtree(&path[1..])
+}
+
+pub(crate) fn subtree_at_mut<'d, Nt, T>(
+    derivation: &'d mut Derivation<Nt, T>,
+    path:       &[usize]
+) -> &'d mut DerivationTree<Nt, T> {
+    derivation.trees[path[0]].subtree_mut(&path[1..])
+}
+
+/// Returns paths (as accepted by `subtree_at()`) of all non-terminal nodes
+/// of `derivation` in pre-order.
+pub(crate) fn nonterm_paths<Nt, T>(derivation: &Derivation<Nt, T>) -> Vec<Vec<usize>> {
+    let mut paths = Vec::new();
+
+    for (tree_idx, tree) in derivation.trees.iter().enumerate() {
+        collect_nonterm_paths(tree, &mut vec![tree_idx], &mut paths);
+    }
+
+    paths
+}
+
+/// Appends paths of all non-terminal nodes of `tree` in pre-order to `paths`,
+/// each prefixed with `path` of the tree itself.
+fn collect_nonterm_paths<Nt, T>(
+    tree:  &DerivationTree<Nt, T>,
+    path:  &mut Vec<usize>,
+    paths: &mut Vec<Vec<usize>>
+) {
+    if let DerivationTree::Nonterminal{children, ..} = tree {
+        paths.push(path.clone());
+
+        for (child_idx, child) in children.iter().enumerate() {
+            path.push(child_idx);
+
+            collect_nonterm_paths(child, path, paths);
+
+            path.pop();
+        }
+    }
+}
+
+//
+// Service
+//
+
+fn build_tree_from_rule_idxs<Nt, T, I>(
+    rules:     &[Rule<Nt, T>],
+    symbol:    &Symbol<Nt, T>,
+    rule_idxs: &mut I
+) -> Option<DerivationTree<Nt, T>>
+    where Nt: NonterminalValue,
+          T:  TerminalValue,
+          I:  Iterator<Item = usize>
+{
+    match symbol {
+        Symbol::Terminal(value)    => Some(DerivationTree::Terminal(value.clone())),
+        Symbol::Nonterminal(value) => {
+            let rule_idx = rule_idxs.next()?;
+            let rule     = rules.get(rule_idx).filter(|rule| rule.pattern == *value)?;
+
+            let children = rule.replacement.iter()
+                .map(|child_symbol| build_tree_from_rule_idxs(rules, child_symbol, rule_idxs))
+                .collect::<Option<Vec<_>>>()?;
+
+            Some(DerivationTree::Nonterminal{
+                value: value.clone(),
+                rule_idx,
+                children
+            })
+        }
+    }
+}
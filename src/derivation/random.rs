@@ -0,0 +1,167 @@
+use std::sync::Arc;
+
+use crate::grammar::{
+    NonterminalValue,
+    TerminalValue,
+    Symbol,
+    indexed::{
+        IndexedGrammar,
+        MinimalDerivations
+    }
+};
+#[cfg(any(feature = "proptest", feature = "quickcheck"))]
+use crate::grammar::{
+    Rule,
+    indexed::GrammarIndex
+};
+use super::{
+    Derivation,
+    DerivationTree,
+    build_tree,
+    build_minimal_tree
+};
+#[cfg(any(feature = "proptest", feature = "quickcheck"))]
+use super::shrink::DerivationShrinker;
+
+//
+// Friend types
+//
+
+//
+// RandomDerivationGenerator<'r, Nt, T>
+//
+
+/// Generates random derivations, independent of [`Expander`](struct.Expander.html) and its rule selector,
+/// using a caller-provided source of randomness.
+///
+/// Every derivation is guaranteed to terminate: once the given budget of nodes is used up,
+/// the remaining non-terminal symbols are expanded using minimal derivations.
+pub(crate) struct RandomDerivationGenerator<'r, Nt, T> {
+    grammar:             IndexedGrammar<'r, Nt, T>,
+    minimal_derivations: Arc<MinimalDerivations>,
+    productive_rules:    Arc<Vec<bool>>
+}
+
+impl<'r, Nt, T> RandomDerivationGenerator<'r, Nt, T>
+    where Nt: NonterminalValue,
+          T:  TerminalValue
+{
+    pub fn new(grammar: IndexedGrammar<'r, Nt, T>) -> Self {
+        let minimal_derivations = grammar.minimal_derivations();
+        let productive_rules    = grammar.productive_rules(&minimal_derivations);
+
+        Self{
+            grammar,
+            minimal_derivations: Arc::new(minimal_derivations),
+            productive_rules:    Arc::new(productive_rules)
+        }
+    }
+
+    pub fn grammar(&self) -> &IndexedGrammar<'r, Nt, T> {
+        &self.grammar
+    }
+
+    pub fn is_productive(&self, nonterm_idx: usize) -> bool {
+        self.minimal_derivations.costs[nonterm_idx].is_some()
+    }
+
+    /// Generates a derivation of `input`, or returns `None` if some of its non-terminal symbols
+    /// cannot be fully expanded. `choose` is called with a number of options `n` and must return a value in `0..n`.
+    pub fn generate<F>(&self, input: &[Symbol<Nt, T>], budget: usize, choose: &mut F) -> Option<Derivation<Nt, T>>
+        where F: FnMut(usize) -> usize
+    {
+        let mut budget = budget;
+
+        input.iter()
+            .map(|symbol| match symbol {
+                Symbol::Terminal(value)    => Some(DerivationTree::Terminal(value.clone())),
+                Symbol::Nonterminal(value) => self.grammar.nonterm_idx(value)
+                    .filter(|nonterm_idx| self.is_productive(*nonterm_idx))
+                    .map(|nonterm_idx| self.generate_tree(nonterm_idx, &mut budget, choose))
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(Derivation::new)
+    }
+
+    /// Generates a random tree for a productive non-terminal.
+    pub fn generate_tree<F>(&self, nonterm_idx: usize, budget: &mut usize, choose: &mut F) -> DerivationTree<Nt, T>
+        where F: FnMut(usize) -> usize
+    {
+        if *budget == 0 {
+            return build_minimal_tree(&self.grammar, &self.minimal_derivations, nonterm_idx);
+        }
+
+        let productive_rule_idxs: Vec<_> = self.grammar.rules_by_nonterm[nonterm_idx].iter()
+            .copied()
+            .filter(|rule_idx| self.productive_rules[*rule_idx])
+            .collect();
+
+        let rule_idx = productive_rule_idxs[choose(productive_rule_idxs.len())];
+
+        *budget = budget.saturating_sub(1 + self.grammar.rules[rule_idx].replacement.len());
+
+        build_tree(&self.grammar, rule_idx, |_, child_idx| self.generate_tree(child_idx, budget, choose))
+    }
+}
+
+//
+// PreparedGrammar<Nt, T>
+//
+
+/// Owned rules along with everything needed to generate and shrink their random derivations,
+/// computed once and shared by all cases of a property-based test.
+#[cfg(any(feature = "proptest", feature = "quickcheck"))]
+pub(crate) struct PreparedGrammar<Nt, T> {
+    rules:               Vec<Rule<Nt, T>>,
+    index:               Arc<GrammarIndex>,
+    minimal_derivations: Arc<MinimalDerivations>,
+    productive_rules:    Arc<Vec<bool>>,
+    shrinker:            Arc<DerivationShrinker<Nt, T>>
+}
+
+#[cfg(any(feature = "proptest", feature = "quickcheck"))]
+impl<Nt, T> PreparedGrammar<Nt, T>
+    where Nt: NonterminalValue,
+          T:  TerminalValue
+{
+    pub fn new(rules: Vec<Rule<Nt, T>>) -> Self {
+        let (index, minimal_derivations, productive_rules, shrinker) = {
+            let generator = RandomDerivationGenerator::new(IndexedGrammar::new(&rules));
+
+            (
+                Arc::clone(generator.grammar.index()),
+                Arc::clone(&generator.minimal_derivations),
+                Arc::clone(&generator.productive_rules),
+                Arc::new(DerivationShrinker::new(&generator.grammar, &generator.minimal_derivations))
+            )
+        };
+
+        Self{
+            rules,
+            index,
+            minimal_derivations,
+            productive_rules,
+            shrinker
+        }
+    }
+
+    /// Returns a generator for the rules, which reuses the prepared analyses instead of repeating them.
+    pub fn generator(&self) -> RandomDerivationGenerator<'_, Nt, T> {
+        RandomDerivationGenerator{
+            grammar:             IndexedGrammar::with_index(&self.rules, &self.index),
+            minimal_derivations: Arc::clone(&self.minimal_derivations),
+            productive_rules:    Arc::clone(&self.productive_rules)
+        }
+    }
+
+    pub fn shrinker(&self) -> &Arc<DerivationShrinker<Nt, T>> {
+        &self.shrinker
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<Nt, T> PreparedGrammar<Nt, T> {
+    pub fn rules(&self) -> &[Rule<Nt, T>] {
+        &self.rules
+    }
+}
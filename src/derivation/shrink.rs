@@ -0,0 +1,181 @@
+use crate::grammar::{
+    NonterminalValue,
+    TerminalValue,
+    indexed::{
+        IndexedGrammar,
+        MinimalDerivations
+    }
+};
+use super::{
+    Derivation,
+    DerivationTree,
+    build_minimal_tree,
+    subtree_at,
+    subtree_at_mut
+};
+
+//
+// Friend types
+//
+
+//
+// DerivationShrinker<Nt, T>
+//
+
+/// Produces smaller derivations of the same input from a given derivation by replacing
+/// its subtrees with shorter derivations of the same non-terminal symbols, so that the results
+/// remain valid derivations of the grammar.
+///
+/// Owns all the data it needs, so it can be shared by values outliving the rules it was created from.
+pub(crate) struct DerivationShrinker<Nt, T> {
+    rule_patterns: Vec<usize>,
+    minimal_trees: Vec<Option<DerivationTree<Nt, T>>>
+}
+
+impl<Nt, T> DerivationShrinker<Nt, T>
+    where Nt: NonterminalValue,
+          T:  TerminalValue
+{
+    pub fn new(grammar: &IndexedGrammar<Nt, T>, minimal_derivations: &MinimalDerivations) -> Self {
+        Self{
+            rule_patterns: grammar.rule_patterns.clone(),
+            minimal_trees: (0..grammar.nonterm_count())
+                .map(|nonterm_idx| {
+                    minimal_derivations.costs[nonterm_idx]
+                        .map(|_| build_minimal_tree(grammar, minimal_derivations, nonterm_idx))
+                })
+                .collect()
+        }
+    }
+
+    /// Lists possible ways to shrink `derivation`, starting with the ones closest to the roots.
+    pub fn candidates(&self, derivation: &Derivation<Nt, T>) -> Vec<ShrinkCandidate> {
+        let mut candidates = Vec::new();
+        let mut path       = Vec::new();
+
+        for (tree_idx, tree) in derivation.trees.iter().enumerate() {
+            path.push(tree_idx);
+
+            self.collect_candidates(tree, &mut path, &mut candidates);
+
+            path.pop();
+        }
+
+        candidates
+    }
+
+    /// Returns `derivation` shrunk as described by `candidate`, which must have been produced
+    /// by [`candidates()`](#method.candidates) for the same derivation.
+    pub fn apply(&self, derivation: &Derivation<Nt, T>, candidate: &ShrinkCandidate) -> Derivation<Nt, T> {
+        let mut shrunk_derivation = derivation.clone();
+
+        let (path, replacement) = match candidate {
+            ShrinkCandidate::Minimal{path, nonterm_idx} => (
+                path,
+                self.minimal_trees[*nonterm_idx].clone()
+                    .expect("minimal tree candidates must only be produced for productive non-terminals")
+            ),
+            ShrinkCandidate::Descendant{path, descendant_path} => (
+                path,
+                subtree_at(derivation, path).subtree(descendant_path).clone()
+            )
+        };
+
+        *subtree_at_mut(&mut shrunk_derivation, path) = replacement;
+
+        shrunk_derivation
+    }
+
+    //
+    // Service
+    //
+
+    fn collect_candidates(
+        &self,
+        tree:       &DerivationTree<Nt, T>,
+        path:       &mut Vec<usize>,
+        candidates: &mut Vec<ShrinkCandidate>
+    ) {
+        let rule_idx = match tree.rule_idx() {
+            Some(rule_idx) if rule_idx < self.rule_patterns.len() => rule_idx,
+            _                                                      => return
+        };
+
+        let nonterm_idx = self.rule_patterns[rule_idx];
+
+        if let Some(minimal_tree) = &self.minimal_trees[nonterm_idx] {
+            if minimal_tree.node_count() < tree.node_count() {
+                candidates.push(ShrinkCandidate::Minimal{
+                    path: path.clone(),
+                    nonterm_idx
+                });
+            }
+        }
+
+        let mut descendant_path = Vec::new();
+
+        for (child_idx, child) in tree.children().iter().enumerate() {
+            descendant_path.push(child_idx);
+
+            self.collect_descendants(child, nonterm_idx, path, &mut descendant_path, candidates);
+
+            descendant_path.pop();
+        }
+
+        for (child_idx, child) in tree.children().iter().enumerate() {
+            path.push(child_idx);
+
+            self.collect_candidates(child, path, candidates);
+
+            path.pop();
+        }
+    }
+
+    fn collect_descendants(
+        &self,
+        tree:            &DerivationTree<Nt, T>,
+        nonterm_idx:     usize,
+        path:            &[usize],
+        descendant_path: &mut Vec<usize>,
+        candidates:      &mut Vec<ShrinkCandidate>
+    ) {
+        let rule_idx = match tree.rule_idx() {
+            Some(rule_idx) if rule_idx < self.rule_patterns.len() => rule_idx,
+            _                                                      => return
+        };
+
+        if self.rule_patterns[rule_idx] == nonterm_idx {
+            candidates.push(ShrinkCandidate::Descendant{
+                path:            path.to_vec(),
+                descendant_path: descendant_path.clone()
+            });
+        }
+
+        for (child_idx, child) in tree.children().iter().enumerate() {
+            descendant_path.push(child_idx);
+
+            self.collect_descendants(child, nonterm_idx, path, descendant_path, candidates);
+
+            descendant_path.pop();
+        }
+    }
+}
+
+//
+// ShrinkCandidate
+//
+
+/// Describes a replacement of the subtree at `path` (tree index followed by child indices)
+/// either with the minimal tree for its non-terminal or with one of its own subtrees
+/// for the same non-terminal.
+#[derive(Debug, Clone)]
+pub(crate) enum ShrinkCandidate {
+    Minimal{
+        path:        Vec<usize>,
+        nonterm_idx: usize
+    },
+    Descendant{
+        path:            Vec<usize>,
+        descendant_path: Vec<usize>
+    }
+}
@@ -1,15 +1,27 @@
 mod error;
 mod helpers;
+mod context;
+mod recovery;
+mod modifiers;
 
 #[cfg(test)]
+#[allow(clippy::useless_vec, clippy::map_flatten, clippy::type_complexity)]
 mod tests;
 
+use std::sync::Arc;
+
 use crate::grammar::{
     NonterminalValue,
     TerminalValue,
     Symbol,
-    Rule
+    Rule,
+    indexed::{
+        IndexedGrammar,
+        GrammarIndex
+    }
 };
+use crate::derivation::Derivation;
+use crate::probability::ProbabilisticGrammar;
 
 //
 // Constants
@@ -23,14 +35,40 @@ const DEFAULT_MAX_ITERATIONS: usize = 1024;
 
 pub use helpers::RuleSelector;
 pub use helpers::ExpansionLogger;
+pub use helpers::ExpansionLoggerExt;
 
 //
 // Interface types
 //
 
-pub use error::{Error, ErrorKind};
+pub use error::{Error, ErrorKind, ErrorDisplay};
+pub use context::ExpansionContext;
+pub use recovery::{
+    RecoveryPolicy,
+    FallbackFn,
+    ExpansionWarning,
+    Recovered
+};
+pub use modifiers::{
+    ModifierRegistry,
+    ModifierFn
+};
 pub use helpers::builtin::UniformRandomRuleSelector;
+pub use helpers::builtin::WeightedRandomRuleSelector;
+pub use helpers::builtin::ShuffleBagRuleSelector;
+pub use helpers::builtin::NoImmediateRepeatRuleSelector;
 pub use helpers::builtin::NullExpansionLogger;
+pub use helpers::builtin::StatsExpansionLogger;
+pub use helpers::builtin::CoverageReport;
+pub use helpers::builtin::TeeExpansionLogger;
+pub use helpers::builtin::NontermFilterExpansionLogger;
+pub use helpers::builtin::FailuresOnlyExpansionLogger;
+
+#[cfg(feature = "tracing")]
+pub use helpers::builtin::TracingExpansionLogger;
+
+#[cfg(feature = "log")]
+pub use helpers::builtin::LogExpansionLogger;
 
 /// On success - a vector containing only terminal values, on failure - an [`Error`](struct.Error.html).
 pub type Result<Nt, T> = std::result::Result<Vec<T>, Error<Nt, T>>;
@@ -47,7 +85,8 @@ pub struct Expander<Nt, T, RS, EL>
     where RS: RuleSelector<Nt, T>,
           EL: ExpansionLogger<Nt, T>
 {
-    config: ExpanderConfig<Nt, T, RS, EL>
+    config: ExpanderConfig<Nt, T, RS, EL>,
+    stack:  ExpansionStack<Nt, T>
 }
 
 impl<Nt, T, RS, EL> Expander<Nt, T, RS, EL>
@@ -70,22 +109,336 @@ impl<Nt, T, RS, EL> Expander<Nt, T, RS, EL>
     /// # Errors
     /// Will result in an [`Error`](struct.Error.html) if there is no matching rule to expand a non-terminal symbol or
     /// if the maximum number of iterations has been reached while there are still non-terminal symbols left.
+    ///
+    /// Non-terminal symbols, for which there is no matching rule, are handled according to the
+    /// [`RecoveryPolicy`](enum.RecoveryPolicy.html), see [`expand_with_warnings()`](#method.expand_with_warnings).
     pub fn expand(&mut self, input: Vec<Symbol<Nt, T>>) -> Result<Nt, T> {
-        expand_input(
+        self.expand_with_warnings(input)
+            .map(|recovered| recovered.output)
+    }
+
+    /// Same as [`expand()`](struct.Expander.html#method.expand), but borrows `input` and appends the resulting
+    /// terminal values to `output` instead of returning a new vector. On failure, `output` is left unchanged.
+    ///
+    /// The `Expander` keeps its work stack between expansions and an index of the rules for every non-terminal symbol,
+    /// so reusing `output` (e.g. clearing it after consuming the values) allows generating any number of sequences
+    /// without allocating, once the buffers have grown large enough. This holds as long as the values of symbols
+    /// are cloned without allocating, the applied rules have no [`Modifier`](struct.Modifier.html)s and the
+    /// [`RuleSelector`](trait.RuleSelector.html) does not allocate either, like
+    /// [`UniformRandomRuleSelector`](struct.UniformRandomRuleSelector.html) and
+    /// [`WeightedRandomRuleSelector`](struct.WeightedRandomRuleSelector.html). Terminal values are cloned into `output`
+    /// exactly once, so cheaply clonable ones, such as `Rc<str>` or `Arc<str>`, avoid allocating for the values as well.
+    ///
+    /// ```
+    /// use branchy::{
+    ///     Symbol,
+    ///     ExpanderBuilder
+    /// };
+    ///
+    /// let mut expander = ExpanderBuilder::new()
+    ///     .with_new_rule("name", vec![Symbol::Terminal("Al"), Symbol::Nonterminal("suffix")])
+    ///     .with_new_rule("suffix", vec![Symbol::Terminal("ice")])
+    ///     .with_new_rule("suffix", vec![Symbol::Terminal("an")])
+    ///     .build();
+    ///
+    /// let input = [Symbol::Nonterminal("name")];
+    ///
+    /// let mut output = Vec::new();
+    ///
+    /// for _ in 0..100 {
+    ///     output.clear();
+    ///
+    ///     expander.expand_into(&input, &mut output).unwrap();
+    ///
+    ///     assert!(output == ["Al", "ice"] || output == ["Al", "an"]);
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// Same as [`expand()`](struct.Expander.html#method.expand).
+    pub fn expand_into(&mut self, input: &[Symbol<Nt, T>], output: &mut Vec<T>) -> std::result::Result<(), Error<Nt, T>> {
+        expand_input_recovering(
             input,
             &self.config.rules,
-            &self.config.rule_selector,
+            &self.config.rule_index,
+            &mut self.config.rule_selector,
+            &mut self.config.logger,
+            self.config.max_iterations,
+            &self.config.recovery_policy,
+            Some(&self.config.modifier_registry),
+            &mut self.stack,
+            output,
+            &mut Vec::new()
+        )
+    }
+
+    /// Same as [`expand()`](struct.Expander.html#method.expand), but also returns an
+    /// [`ExpansionWarning`](struct.ExpansionWarning.html) for every non-terminal symbol, for which there was
+    /// no matching rule and which was replaced according to the [`RecoveryPolicy`](enum.RecoveryPolicy.html)
+    /// specified via [`ExpanderBuilder::with_recovery_policy()`](struct.ExpanderBuilder.html#method.with_recovery_policy).
+    ///
+    /// # Errors
+    /// Will result in an [`Error`](struct.Error.html) if there is no matching rule to expand a non-terminal symbol
+    /// and the recovery policy is [`RecoveryPolicy::Fail`](enum.RecoveryPolicy.html#variant.Fail), or
+    /// if the maximum number of iterations has been reached while there are still non-terminal symbols left.
+    pub fn expand_with_warnings(
+        &mut self,
+        input: Vec<Symbol<Nt, T>>
+    ) -> std::result::Result<Recovered<Nt, T>, Error<Nt, T>> {
+        let mut output   = Vec::new();
+        let mut warnings = Vec::new();
+
+        expand_input_recovering(
+            &input,
+            &self.config.rules,
+            &self.config.rule_index,
+            &mut self.config.rule_selector,
             &mut self.config.logger,
-            self.config.max_iterations
+            self.config.max_iterations,
+            &self.config.recovery_policy,
+            Some(&self.config.modifier_registry),
+            &mut self.stack,
+            &mut output,
+            &mut warnings
+        )?;
+
+        Ok(Recovered{
+            output,
+            warnings
+        })
+    }
+
+    /// Same as [`expand()`](struct.Expander.html#method.expand), but returns the complete
+    /// [`Derivation`](struct.Derivation.html) of `input` instead of just the resulting sequence of terminal values,
+    /// e.g. in order to mutate it later.
+    ///
+    /// Since derivations only consist of applied rules, the [`RecoveryPolicy`](enum.RecoveryPolicy.html) is ignored:
+    /// a non-terminal symbol without a matching rule always results in an error, as if the policy was
+    /// [`RecoveryPolicy::Fail`](enum.RecoveryPolicy.html#variant.Fail). The [`ModifierRegistry`](struct.ModifierRegistry.html)
+    /// is ignored as well, since [`Modifier`](struct.Modifier.html)s only change terminal values, but not the derivation,
+    /// so rules with modifiers, which are not registered, do not result in an error either.
+    ///
+    /// # Errors
+    /// Will result in an [`Error`](struct.Error.html) if there is no matching rule to expand a non-terminal symbol or
+    /// if the maximum number of iterations has been reached while there are still non-terminal symbols left.
+    ///
+    /// # Panics
+    /// Panics if the [`RuleSelector`](trait.RuleSelector.html) selects a rule which is not among this `Expander`'s rules,
+    /// e.g. a clone of one of them, since a derivation can only refer to the `Expander`'s rules.
+    pub fn expand_derivation(
+        &mut self,
+        input: Vec<Symbol<Nt, T>>
+    ) -> std::result::Result<Derivation<Nt, T>, Error<Nt, T>> {
+        let mut rule_idx_recorder = RuleIdxRecorder::new();
+
+        expand_input_recovering(
+            &input,
+            &self.config.rules,
+            &self.config.rule_index,
+            &mut self.config.rule_selector,
+            &mut TeeExpansionLogger::new(&mut self.config.logger, &mut rule_idx_recorder),
+            self.config.max_iterations,
+            &RecoveryPolicy::Fail,
+            None,
+            &mut self.stack,
+            &mut Vec::new(),
+            &mut Vec::new()
+        )?;
+
+        Ok(
+            Derivation::from_rule_idxs(&self.config.rules, &input, &rule_idx_recorder.rule_idxs)
+                .expect("rule selector must only select rules of the expander")
         )
     }
 
+    /// Returns the [`Rule`](struct.Rule.html)s used by this `Expander`.
+    #[must_use]
+    pub fn rules(&self) -> &[Rule<Nt, T>] {
+        &self.config.rules
+    }
+
+    /// Returns the [`ExpansionLogger`](trait.ExpansionLogger.html) used by this `Expander`,
+    /// e.g. in order to retrieve data it has accumulated.
+    #[must_use]
+    pub fn logger(&self) -> &EL {
+        &self.config.logger
+    }
+
+    /// Returns a mutable reference to the [`ExpansionLogger`](trait.ExpansionLogger.html) used by this `Expander`.
+    pub fn logger_mut(&mut self) -> &mut EL {
+        &mut self.config.logger
+    }
+
+    /// Replaces the [`ExpansionLogger`](trait.ExpansionLogger.html) used by this `Expander`,
+    /// returning the previous one.
+    pub fn set_logger(&mut self, logger: EL) -> EL {
+        std::mem::replace(&mut self.config.logger, logger)
+    }
+
+    /// Returns the [`RuleSelector`](trait.RuleSelector.html) used by this `Expander`.
+    #[must_use]
+    pub fn rule_selector(&self) -> &RS {
+        &self.config.rule_selector
+    }
+
+    /// Returns a mutable reference to the [`RuleSelector`](trait.RuleSelector.html) used by this `Expander`.
+    pub fn rule_selector_mut(&mut self) -> &mut RS {
+        &mut self.config.rule_selector
+    }
+
+    /// Replaces the [`RuleSelector`](trait.RuleSelector.html) used by this `Expander`,
+    /// returning the previous one.
+    pub fn set_rule_selector(&mut self, rule_selector: RS) -> RS {
+        std::mem::replace(&mut self.config.rule_selector, rule_selector)
+    }
+
+    /// Returns a [`ProbabilisticGrammar`](struct.ProbabilisticGrammar.html) for the rules of this `Expander`,
+    /// which uses the [`RuleDistribution`](enum.RuleDistribution.html) of its [`RuleSelector`](trait.RuleSelector.html).
+    #[must_use]
+    pub fn probabilistic_grammar(&self) -> ProbabilisticGrammar<'_, Nt, T> {
+        ProbabilisticGrammar::new(&self.config.rules, self.config.rule_selector.rule_distribution())
+    }
+
+    /// Returns the [`ModifierRegistry`](struct.ModifierRegistry.html) used by this `Expander`.
+    #[must_use]
+    pub fn modifier_registry(&self) -> &ModifierRegistry<T> {
+        &self.config.modifier_registry
+    }
+
+    /// Returns a mutable reference to the [`ModifierRegistry`](struct.ModifierRegistry.html) used by this `Expander`,
+    /// e.g. in order to register more modifiers.
+    pub fn modifier_registry_mut(&mut self) -> &mut ModifierRegistry<T> {
+        &mut self.config.modifier_registry
+    }
+
+    /// Replaces the [`RecoveryPolicy`](enum.RecoveryPolicy.html) used by this `Expander`,
+    /// returning the previous one.
+    pub fn set_recovery_policy(&mut self, recovery_policy: RecoveryPolicy<Nt, T>) -> RecoveryPolicy<Nt, T> {
+        std::mem::replace(&mut self.config.recovery_policy, recovery_policy)
+    }
+
+    /// Adds a new [`Rule`](struct.Rule.html) after the existing ones.
+    pub fn add_new_rule<Ss>(&mut self, pattern: Nt, replacement: Ss)
+        where Ss: IntoIterator<Item = Symbol<Nt, T>>
+    {
+        self.add_rule(Rule::new(pattern, replacement.into_iter().collect()));
+    }
+
+    /// Adds `rule` after the existing [`Rule`](struct.Rule.html)s.
+    pub fn add_rule(&mut self, rule: Rule<Nt, T>) {
+        self.add_rules(std::iter::once(rule));
+    }
+
+    /// Adds `rules` after the existing [`Rule`](struct.Rule.html)s.
+    pub fn add_rules<Rs>(&mut self, rules: Rs)
+        where Rs: IntoIterator<Item = Rule<Nt, T>>
+    {
+        self.config.rules.extend(rules);
+
+        // Existing rules keep their indices, so only the added ones need to be indexed.
+        Arc::make_mut(&mut self.config.rule_index).extend(&self.config.rules);
+
+        self.config.rule_selector.on_rules_changed(&self.config.rules);
+    }
+
+    /// Removes the [`Rule`](struct.Rule.html) at `rule_idx`, shifting all rules after it,
+    /// and returns it.
+    ///
+    /// # Panics
+    /// Panics if `rule_idx` is out of bounds.
+    pub fn remove_rule(&mut self, rule_idx: usize) -> Rule<Nt, T> {
+        self.update_rules(|rules| rules.remove(rule_idx))
+    }
+
+    /// Removes all [`Rule`](struct.Rule.html)s, which do not satisfy the `predicate`.
+    pub fn retain_rules<F>(&mut self, predicate: F)
+        where F: FnMut(&Rule<Nt, T>) -> bool
+    {
+        self.update_rules(|rules| rules.retain(predicate));
+    }
+
+    /// Replaces the [`Rule`](struct.Rule.html) at `rule_idx` with `rule`, returning the previous one.
+    ///
+    /// # Panics
+    /// Panics if `rule_idx` is out of bounds.
+    pub fn replace_rule(&mut self, rule_idx: usize, rule: Rule<Nt, T>) -> Rule<Nt, T> {
+        self.update_rules(|rules| std::mem::replace(&mut rules[rule_idx], rule))
+    }
+
+    /// Sets the [`weight`](struct.Rule.html#structfield.weight) of the [`Rule`](struct.Rule.html) at `rule_idx`.
+    ///
+    /// # Panics
+    /// Panics if `rule_idx` is out of bounds.
+    pub fn set_rule_weight(&mut self, rule_idx: usize, weight: f64) {
+        // Weights are not indexed, so the index stays valid.
+        self.config.rules[rule_idx].weight = weight;
+
+        self.config.rule_selector.on_rules_changed(&self.config.rules);
+    }
+
+    /// Allows arbitrary changes to the [`Rule`](struct.Rule.html)s used by this `Expander`
+    /// and returns the result of `update`.
+    ///
+    /// Once `update` returns, the [`RuleSelector`](trait.RuleSelector.html) is notified via
+    /// [`on_rules_changed()`](trait.RuleSelector.html#method.on_rules_changed), as it is by all other methods
+    /// changing rules. Since `update` may change rules arbitrarily, the rules are reindexed, which takes time
+    /// proportional to their number. Methods adding rules or changing their weights avoid that.
+    /// Rule indices, e.g. ones stored in previously obtained [`Derivation`](struct.Derivation.html)s,
+    /// may no longer be valid afterwards.
+    pub fn update_rules<F, R>(&mut self, update: F) -> R
+        where F: FnOnce(&mut Vec<Rule<Nt, T>>) -> R
+    {
+        let result = update(&mut self.config.rules);
+
+        self.config.reindex_rules();
+        self.config.rule_selector.on_rules_changed(&self.config.rules);
+
+        result
+    }
+
+    /// Consumes this `Expander`, returning a new one with the same rules and logger, but a different
+    /// [`RuleSelector`](trait.RuleSelector.html).
+    #[allow(clippy::use_self)]
+    pub fn with_rule_selector<NewRS>(self, rule_selector: NewRS) -> Expander<Nt, T, NewRS, EL>
+        where NewRS: RuleSelector<Nt, T>
+    {
+        Expander::new(ExpanderConfig{
+            rules:             self.config.rules,
+            rule_index:        self.config.rule_index,
+            rule_selector,
+            logger:            self.config.logger,
+            max_iterations:    self.config.max_iterations,
+            recovery_policy:   self.config.recovery_policy,
+            modifier_registry: self.config.modifier_registry
+        })
+    }
+
+    /// Consumes this `Expander`, returning a new one with the same rules and rule selector, but a different
+    /// [`ExpansionLogger`](trait.ExpansionLogger.html).
+    #[allow(clippy::use_self)]
+    pub fn with_logger<NewEL>(self, logger: NewEL) -> Expander<Nt, T, RS, NewEL>
+        where NewEL: ExpansionLogger<Nt, T>
+    {
+        Expander::new(ExpanderConfig{
+            rules:             self.config.rules,
+            rule_index:        self.config.rule_index,
+            rule_selector:     self.config.rule_selector,
+            logger,
+            max_iterations:    self.config.max_iterations,
+            recovery_policy:   self.config.recovery_policy,
+            modifier_registry: self.config.modifier_registry
+        })
+    }
+
     //
     // Friend interface
     //
 
     fn new(config: ExpanderConfig<Nt, T, RS, EL>) -> Self {
-        Self{config}
+        Self{
+            config,
+            stack: ExpansionStack::new()
+        }
     }
 }
 
@@ -124,10 +477,13 @@ impl<Nt, T> ExpanderBuilder<Nt, T, UniformRandomRuleSelector, NullExpansionLogge
     {
         Self{
             config: ExpanderConfig{
-                rules:          rules.into_iter().collect(),
-                rule_selector:  UniformRandomRuleSelector,
-                logger:         NullExpansionLogger,
-                max_iterations: DEFAULT_MAX_ITERATIONS
+                rules:             rules.into_iter().collect(),
+                rule_index:        Arc::default(),
+                rule_selector:     UniformRandomRuleSelector,
+                logger:            NullExpansionLogger,
+                max_iterations:    DEFAULT_MAX_ITERATIONS,
+                recovery_policy:   RecoveryPolicy::Fail,
+                modifier_registry: ModifierRegistry::new()
             }
         }
     }
@@ -140,7 +496,9 @@ impl<Nt, T, RS, EL> ExpanderBuilder<Nt, T, RS, EL>
           RS: RuleSelector<Nt, T>,
           EL: ExpansionLogger<Nt, T>
 {
-    pub fn build(self) -> Expander<Nt, T, RS, EL> {
+    pub fn build(mut self) -> Expander<Nt, T, RS, EL> {
+        self.config.reindex_rules();
+
         Expander::new(self.config)
     }
 
@@ -167,7 +525,7 @@ impl<Nt, T, RS, EL> ExpanderBuilder<Nt, T, RS, EL>
 
         my_rules.splice(
             my_rules.len()..my_rules.len(),
-            rules.into_iter()
+            rules
         );
 
         self
@@ -178,10 +536,13 @@ impl<Nt, T, RS, EL> ExpanderBuilder<Nt, T, RS, EL>
     {
         ExpanderBuilder{
             config: ExpanderConfig{
-                rules:          self.config.rules,
+                rules:             self.config.rules,
+                rule_index:        self.config.rule_index,
                 rule_selector,
-                logger:         self.config.logger,
-                max_iterations: self.config.max_iterations
+                logger:            self.config.logger,
+                max_iterations:    self.config.max_iterations,
+                recovery_policy:   self.config.recovery_policy,
+                modifier_registry: self.config.modifier_registry
             }
         }
     }
@@ -191,17 +552,41 @@ impl<Nt, T, RS, EL> ExpanderBuilder<Nt, T, RS, EL>
     {
         ExpanderBuilder{
             config: ExpanderConfig{
-                rules:          self.config.rules,
-                rule_selector:  self.config.rule_selector,
+                rules:             self.config.rules,
+                rule_index:        self.config.rule_index,
+                rule_selector:     self.config.rule_selector,
                 logger,
-                max_iterations: self.config.max_iterations
+                max_iterations:    self.config.max_iterations,
+                recovery_policy:   self.config.recovery_policy,
+                modifier_registry: self.config.modifier_registry
+            }
+        }
+    }
+
+    /// Sets the [`RecoveryPolicy`](enum.RecoveryPolicy.html) for non-terminal symbols, for which there is no matching rule.
+    pub fn with_recovery_policy(self, recovery_policy: RecoveryPolicy<Nt, T>) -> Self {
+        Self{
+            config: ExpanderConfig{
+                recovery_policy,
+                ..self.config
+            }
+        }
+    }
+
+    /// Sets the [`ModifierRegistry`](struct.ModifierRegistry.html), in which [`Modifier`](struct.Modifier.html)s
+    /// attached to symbols in rules are looked up.
+    pub fn with_modifier_registry(self, modifier_registry: ModifierRegistry<T>) -> Self {
+        Self{
+            config: ExpanderConfig{
+                modifier_registry,
+                ..self.config
             }
         }
     }
 
     pub fn with_max_iterations(self, max_iterations: usize) -> Self {
         Self{
-            config: ExpanderConfig {
+            config: ExpanderConfig{
                 max_iterations,
                 ..self.config
             }
@@ -221,60 +606,335 @@ pub struct ExpanderConfig<Nt, T, RS, EL>
     where RS: RuleSelector<Nt, T>,
           EL: ExpansionLogger<Nt, T>
 {
-    rules:          Vec<Rule<Nt, T>>,
-    rule_selector:  RS,
-    logger:         EL,
-    max_iterations: usize
+    rules:             Vec<Rule<Nt, T>>,
+    /// Rules for every non-terminal symbol, built by [`ExpanderBuilder::build()`](struct.ExpanderBuilder.html#method.build),
+    /// rebuilt by [`Expander::update_rules()`](struct.Expander.html#method.update_rules) and extended as rules are added.
+    rule_index:        Arc<GrammarIndex>,
+    rule_selector:     RS,
+    logger:            EL,
+    max_iterations:    usize,
+    recovery_policy:   RecoveryPolicy<Nt, T>,
+    modifier_registry: ModifierRegistry<T>
+}
+
+impl<Nt, T, RS, EL> ExpanderConfig<Nt, T, RS, EL>
+    where Nt: NonterminalValue,
+          RS: RuleSelector<Nt, T>,
+          EL: ExpansionLogger<Nt, T>
+{
+    fn reindex_rules(&mut self) {
+        self.rule_index = IndexedGrammar::new(&self.rules).into_index();
+    }
+}
+
+//
+// ExpansionStack<Nt, T>
+//
+
+/// Work stack of an expansion, which an [`Expander`](struct.Expander.html) keeps between expansions,
+/// so that its buffers are reused.
+struct ExpansionStack<Nt, T> {
+    /// Symbols, which are yet to be processed, the next one being the last, along with the ends of the ancestors' expansions.
+    pending:   Vec<PendingSymbol<Nt, T>>,
+    /// Values of the non-terminal symbols, whose expansions are being processed, starting with the outermost one.
+    ancestors: Vec<Nt>
+}
+
+impl<Nt, T> ExpansionStack<Nt, T> {
+    fn new() -> Self {
+        Self{
+            pending:   Vec::new(),
+            ancestors: Vec::new()
+        }
+    }
+
+    fn clear(&mut self) {
+        self.pending.clear();
+        self.ancestors.clear();
+    }
+}
+
+//
+// PendingSymbol<Nt, T>
+//
+
+enum PendingSymbol<Nt, T> {
+    /// Symbol of the input sequence or of a replacement provided by the recovery policy.
+    Owned(Symbol<Nt, T>),
+    /// Symbol at `symbol_idx` in the replacement of the rule at `rule_idx`, which is only cloned once it is processed.
+    Replacement{
+        rule_idx:   usize,
+        symbol_idx: usize
+    },
+    /// End of the expansion of the innermost ancestor, which produced the output values starting at `output_start`.
+    /// `modified_symbol` is the position of the ancestor in the replacement of a rule, if it has modifiers
+    /// to be applied to these values.
+    ExpansionEnd{
+        output_start:    usize,
+        modified_symbol: Option<(usize, usize)>
+    }
+}
+
+//
+// RuleIdxRecorder: ExpansionLogger<Nt, T>
+//
+
+/// Records indices of the applied rules in the order of application.
+struct RuleIdxRecorder {
+    rule_idxs: Vec<usize>
+}
+
+impl RuleIdxRecorder {
+    fn new() -> Self {
+        Self{
+            rule_idxs: Vec::new()
+        }
+    }
+}
+
+impl<Nt, T> ExpansionLogger<Nt, T> for RuleIdxRecorder {
+    fn on_rule_applied(&mut self, _expanded_nonterm_value: &Nt, rule_idx: usize) {
+        self.rule_idxs.push(rule_idx);
+    }
 }
 
 //
 // Service
 //
 
-fn expand_input<Nt, T, RS, EL>(
-    mut input:      Vec<Symbol<Nt, T>>,
-    rules:          &[Rule<Nt, T>],
-    rule_selector:  &RS,
-    logger:         &mut EL,
-    max_iterations: usize
-) -> Result<Nt, T>
+/// Expands `input` using `rules`, replacing non-terminal symbols, for which there is no matching rule,
+/// according to `recovery_policy`, adding a warning for each of them, and applying modifiers of the applied rules,
+/// unless `modifier_registry` is `None`.
+///
+/// Appends the resulting terminal values to `output`, leaving it unchanged on failure.
+/// Symbols are processed from left to right using `stack`, so that every terminal value is cloned into `output` once,
+/// and symbols of the applied rules' replacements are not cloned at all. Rules matching every non-terminal symbol
+/// are looked up in `rule_index`, which must have been built for `rules`.
+///
+/// Rules selected by `rule_selector`, which are not among `rules`, are expanded by cloning their replacements,
+/// without notifying `logger` via `on_rule_applied()` and without applying their modifiers.
+#[allow(clippy::too_many_arguments)]
+fn expand_input_recovering<Nt, T, RS, EL>(
+    input:             &[Symbol<Nt, T>],
+    rules:             &[Rule<Nt, T>],
+    rule_index:        &GrammarIndex,
+    rule_selector:     &mut RS,
+    logger:            &mut EL,
+    max_iterations:    usize,
+    recovery_policy:   &RecoveryPolicy<Nt, T>,
+    modifier_registry: Option<&ModifierRegistry<T>>,
+    stack:             &mut ExpansionStack<Nt, T>,
+    output:            &mut Vec<T>,
+    warnings:          &mut Vec<ExpansionWarning<Nt>>
+) -> std::result::Result<(), Error<Nt, T>>
     where Nt: NonterminalValue,
           T:  TerminalValue,
           RS: RuleSelector<Nt, T>,
           EL: ExpansionLogger<Nt, T>
 {
-    #[allow(clippy::manual_find_map)]
-    for _ in 0..max_iterations {
-        let maybe_first_nonterm = input.iter()
-            .enumerate()
-            .find(|(_, symbol)| symbol.is_nonterminal())
-            .map(|(idx, symbol)| (idx, symbol.clone().unwrap_nonterm()));
-
-        if let Some((first_nonterm_idx, first_nonterm_value)) = maybe_first_nonterm {
-            let maybe_selected_rule = rule_selector.select_rule(rules, &first_nonterm_value);
-
-            if let Some(selected_rule) = maybe_selected_rule {
-                input.splice(
-                    first_nonterm_idx..=first_nonterm_idx,
-                    selected_rule.replacement.iter().cloned()
-                );
+    logger.on_expansion_started(input);
+
+    let output_start = output.len();
+
+    stack.clear();
+    stack.pending.extend(input.iter().rev().cloned().map(PendingSymbol::Owned));
+
+    let mut iteration = 0;
+
+    while let Some(pending_symbol) = stack.pending.pop() {
+        let (nonterm_value, modified_symbol, maybe_nonterm_idx) = match pending_symbol {
+            PendingSymbol::Owned(Symbol::Terminal(value))    => {
+                output.push(value);
+
+                continue;
+            },
+            PendingSymbol::Owned(Symbol::Nonterminal(value)) => {
+                let maybe_nonterm_idx = rule_index.find_nonterm(rules, &value);
+
+                (value, None, maybe_nonterm_idx)
+            },
+            PendingSymbol::Replacement{rule_idx, symbol_idx} => {
+                let rule = &rules[rule_idx];
+
+                let is_modified = modifier_registry.is_some()
+                    && rule.modifiers.iter().any(|modifier| modifier.symbol_idx == symbol_idx);
+
+                match (&rule.replacement[symbol_idx], modifier_registry) {
+                    (Symbol::Terminal(value), Some(modifier_registry)) if is_modified => {
+                        output.extend(apply_modifiers(rule.symbol_modifiers(symbol_idx), vec![value.clone()], modifier_registry));
+
+                        continue;
+                    },
+                    (Symbol::Terminal(value), _) => {
+                        output.push(value.clone());
+
+                        continue;
+                    },
+                    (Symbol::Nonterminal(value), _) => (
+                        value.clone(),
+                        Some((rule_idx, symbol_idx)).filter(|_| is_modified),
+                        rule_index.rule_symbols[rule_idx][symbol_idx]
+                    )
+                }
+            },
+            PendingSymbol::ExpansionEnd{output_start: span_start, modified_symbol} => {
+                stack.ancestors.pop();
+
+                if let (Some((rule_idx, symbol_idx)), Some(modifier_registry)) = (modified_symbol, modifier_registry) {
+                    let values = output.drain(span_start..).collect();
+
+                    output.extend(apply_modifiers(rules[rule_idx].symbol_modifiers(symbol_idx), values, modifier_registry));
+                }
+
+                continue;
+            }
+        };
+
+        if iteration == max_iterations {
+            let state = current_state(rules, &output[output_start..], Some(Symbol::Nonterminal(nonterm_value)), &stack.pending);
+
+            logger.on_max_iterations_reached(&state, max_iterations);
+
+            output.truncate(output_start);
+
+            return Err(Error::max_iterations_reached(state, max_iterations).with_ancestors(stack.ancestors.clone()));
+        }
+
+        let matching_rule_idxs = maybe_nonterm_idx.map_or(&[][..], |nonterm_idx| &rule_index.rules_by_nonterm[nonterm_idx]);
 
-                logger.on_nonterm_expanded(&first_nonterm_value, &selected_rule);
-            } else {
-                logger.on_nonterm_expansion_failed(&first_nonterm_value);
+        let maybe_selected_rule = rule_selector.select_rule(
+            rules,
+            &ExpansionContext::new(&nonterm_value, &stack.ancestors, &output[output_start..], iteration, matching_rule_idxs)
+        );
+
+        let maybe_selected_rule_idx = maybe_selected_rule
+            .and_then(|selected_rule| find_rule_idx(rules, matching_rule_idxs, selected_rule));
+
+        if let (Some(selected_rule), Some(rule_idx)) = (maybe_selected_rule, maybe_selected_rule_idx) {
+            let maybe_unknown_modifier = modifier_registry.and_then(|modifier_registry| {
+                selected_rule.modifiers.iter().find(|modifier| !modifier_registry.contains(&modifier.name))
+            });
+
+            if let Some(unknown_modifier) = maybe_unknown_modifier {
+                logger.on_nonterm_expansion_failed(&nonterm_value);
 
-                return Err(Error::nonterm_expansion_failed(input, first_nonterm_value));
+                let state = current_state(rules, &output[output_start..], Some(Symbol::Nonterminal(nonterm_value)), &stack.pending);
+
+                output.truncate(output_start);
+
+                return Err(
+                    Error::unknown_modifier(state, unknown_modifier.name.clone()).with_ancestors(stack.ancestors.clone())
+                );
             }
+
+            logger.on_nonterm_expanded(&nonterm_value, selected_rule);
+            logger.on_rule_applied(&nonterm_value, rule_idx);
+
+            stack.pending.push(PendingSymbol::ExpansionEnd{
+                output_start: output.len(),
+                modified_symbol
+            });
+            stack.pending.extend(
+                (0..selected_rule.replacement.len()).rev()
+                    .map(|symbol_idx| PendingSymbol::Replacement{rule_idx, symbol_idx})
+            );
+        } else if let Some(selected_rule) = maybe_selected_rule {
+            // The selected rule is not one of `rules`, e.g. a clone of one, so its replacement is cloned,
+            // as for the recovery policy, and its modifiers cannot be referred to.
+            logger.on_nonterm_expanded(&nonterm_value, selected_rule);
+
+            stack.pending.push(PendingSymbol::ExpansionEnd{
+                output_start: output.len(),
+                modified_symbol
+            });
+            stack.pending.extend(selected_rule.replacement.iter().rev().cloned().map(PendingSymbol::Owned));
+        } else if let Some(replacement) = recovery_policy.recover(&nonterm_value) {
+            logger.on_nonterm_expansion_recovered(&nonterm_value, &replacement);
+
+            warnings.push(ExpansionWarning{
+                nonterm_value: nonterm_value.clone(),
+                position:      output.len() - output_start,
+                ancestors:     stack.ancestors.clone()
+            });
+
+            stack.pending.push(PendingSymbol::ExpansionEnd{
+                output_start: output.len(),
+                modified_symbol
+            });
+            stack.pending.extend(replacement.into_iter().rev().map(PendingSymbol::Owned));
         } else {
-            let expansion_result: Vec<_> = input.into_iter().map(Symbol::unwrap_term).collect();
+            logger.on_nonterm_expansion_failed(&nonterm_value);
+
+            let state = current_state(rules, &output[output_start..], Some(Symbol::Nonterminal(nonterm_value.clone())), &stack.pending);
 
-            logger.on_input_fully_expanded(&expansion_result);
+            output.truncate(output_start);
 
-            return Ok(expansion_result);
+            return Err(Error::nonterm_expansion_failed(state, nonterm_value).with_ancestors(stack.ancestors.clone()));
         }
+
+        stack.ancestors.push(nonterm_value);
+
+        iteration += 1;
+    }
+
+    if iteration == max_iterations {
+        let state = current_state(rules, &output[output_start..], None, &[]);
+
+        logger.on_max_iterations_reached(&state, max_iterations);
+
+        output.truncate(output_start);
+
+        return Err(Error::max_iterations_reached(state, max_iterations));
     }
 
-    logger.on_max_iterations_reached(&input, max_iterations);
+    logger.on_input_fully_expanded(&output[output_start..]);
+
+    Ok(())
+}
+
+/// Returns the index of `rule` among `rules`, looking among the rules at `matching_rule_idxs` first,
+/// or `None` if `rule` is not one of `rules`. Rules are compared by identity, since rules equal to one another
+/// are still different rules, and terminal values need not be comparable.
+fn find_rule_idx<Nt, T>(rules: &[Rule<Nt, T>], matching_rule_idxs: &[usize], rule: &Rule<Nt, T>) -> Option<usize> {
+    matching_rule_idxs.iter()
+        .copied()
+        .chain(0..rules.len())
+        .find(|&rule_idx| std::ptr::eq(&rules[rule_idx], rule))
+}
 
-    Err(Error::max_iterations_reached(input, max_iterations))
+/// Returns the result of applying the modifiers named `modifier_names` to `values` in order.
+fn apply_modifiers<'m, T, Ms>(modifier_names: Ms, values: Vec<T>, modifier_registry: &ModifierRegistry<T>) -> Vec<T>
+    where Ms: IntoIterator<Item = &'m str>
+{
+    modifier_names.into_iter().fold(values, |values, modifier_name| {
+        modifier_registry.apply(modifier_name, values)
+            .expect("modifier must have been checked to be registered")
+    })
+}
+
+/// Returns the sequence of symbols reached by an expansion, which has produced `output` values
+/// and is about to process `next_symbol` followed by `pending` symbols.
+fn current_state<Nt, T>(
+    rules:       &[Rule<Nt, T>],
+    output:      &[T],
+    next_symbol: Option<Symbol<Nt, T>>,
+    pending:     &[PendingSymbol<Nt, T>]
+) -> Vec<Symbol<Nt, T>>
+    where Nt: NonterminalValue,
+          T:  TerminalValue
+{
+    let pending_symbols = pending.iter()
+        .rev()
+        .filter_map(|pending_symbol| match pending_symbol {
+            PendingSymbol::Owned(symbol)                     => Some(symbol.clone()),
+            PendingSymbol::Replacement{rule_idx, symbol_idx} => Some(rules[*rule_idx].replacement[*symbol_idx].clone()),
+            PendingSymbol::ExpansionEnd{..}                  => None
+        });
+
+    output.iter()
+        .cloned()
+        .map(Symbol::Terminal)
+        .chain(next_symbol)
+        .chain(pending_symbols)
+        .collect()
 }
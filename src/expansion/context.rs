@@ -0,0 +1,104 @@
+//
+// Interface types
+//
+
+//
+// ExpansionContext<'c, Nt, T>
+//
+
+/// Describes the point of the expansion, at which a [`RuleSelector`](trait.RuleSelector.html)
+/// is asked to select a rule.
+///
+/// Passed to [`RuleSelector::select_rule()`](trait.RuleSelector.html#method.select_rule) and
+/// [`RuleSelector::select_matching_rule()`](trait.RuleSelector.html#method.select_matching_rule),
+/// allowing for context-sensitive and adaptive rule selection strategies.
+#[allow(clippy::module_name_repetitions)]
+pub struct ExpansionContext<'c, Nt, T> {
+    nonterm_value:      &'c Nt,
+    ancestors:          &'c [Nt],
+    partial_output:     &'c [T],
+    iteration:          usize,
+    matching_rule_idxs: &'c [usize]
+}
+
+impl<'c, Nt, T> ExpansionContext<'c, Nt, T> {
+    //
+    // Interface
+    //
+
+    /// Value of the non-terminal symbol, which is about to be expanded.
+    #[must_use]
+    pub fn nonterm_value(&self) -> &'c Nt {
+        self.nonterm_value
+    }
+
+    /// Values of the non-terminal symbols, from whose expansion the current non-terminal symbol
+    /// was derived, starting with the outermost one (which was present in the input sequence).
+    ///
+    /// Empty if the current non-terminal symbol comes directly from the input sequence.
+    #[must_use]
+    pub fn ancestors(&self) -> &'c [Nt] {
+        self.ancestors
+    }
+
+    /// Value of the non-terminal symbol, whose expansion produced the current non-terminal symbol,
+    /// or `None` if the current non-terminal symbol comes directly from the input sequence.
+    #[must_use]
+    pub fn parent(&self) -> Option<&'c Nt> {
+        self.ancestors.last()
+    }
+
+    /// Depth of the current non-terminal symbol in the derivation tree,
+    /// `0` meaning it comes directly from the input sequence.
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.ancestors.len()
+    }
+
+    /// Terminal values, which have already been produced to the left of the current non-terminal symbol.
+    pub fn partial_output(&self) -> impl Iterator<Item = &'c T> {
+        self.partial_output.iter()
+    }
+
+    /// Number of terminal values, which have already been produced to the left of the current non-terminal symbol.
+    #[must_use]
+    pub fn partial_output_len(&self) -> usize {
+        self.partial_output.len()
+    }
+
+    /// Zero-based number of the current expansion step.
+    #[must_use]
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    /// Indices of the rules matching the current non-terminal symbol among the rules of the
+    /// [`Expander`](struct.Expander.html), in their original order.
+    ///
+    /// The `Expander` keeps an index of its rules for every non-terminal symbol, so rule selectors
+    /// can use these indices instead of collecting matching rules on every expansion step.
+    #[must_use]
+    pub fn matching_rule_idxs(&self) -> &'c [usize] {
+        self.matching_rule_idxs
+    }
+
+    //
+    // Friend interface
+    //
+
+    pub(super) fn new(
+        nonterm_value:      &'c Nt,
+        ancestors:          &'c [Nt],
+        partial_output:     &'c [T],
+        iteration:          usize,
+        matching_rule_idxs: &'c [usize]
+    ) -> Self {
+        Self{
+            nonterm_value,
+            ancestors,
+            partial_output,
+            iteration,
+            matching_rule_idxs
+        }
+    }
+}
@@ -10,22 +10,33 @@ use crate::grammar::{
     Symbol
 };
 
+//
+// Constants
+//
+
+/// Maximal number of ancestors listed by `Error`'s `Display` implementation,
+/// so that messages about runaway recursion remain readable.
+const MAX_DISPLAYED_ANCESTORS: usize = 8;
+
 //
 // Interface
 //
 
 //
-// ErrorKind<Nt, T>: Debug + Copy
+// ErrorKind<Nt>: Debug + Clone
 //
 
 /// Enumerates kinds of possible errors during expansion.
 #[allow(clippy::module_name_repetitions)]
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub enum ErrorKind<Nt>
     where Nt: NonterminalValue
 {
     NontermExpansionFailed(Nt),
-    MaxIterationsReached(usize)
+    MaxIterationsReached(usize),
+    /// A rule refers to a [`Modifier`](struct.Modifier.html) with the given name,
+    /// which is not registered in the [`ModifierRegistry`](struct.ModifierRegistry.html).
+    UnknownModifier(String)
 }
 
 impl<Nt> Debug for ErrorKind<Nt>
@@ -33,29 +44,49 @@ impl<Nt> Debug for ErrorKind<Nt>
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::NontermExpansionFailed(_) =>  write!(
+            Self::NontermExpansionFailed(_) => write!(
                 f, "NontermExpansionFailed(_)"
             ),
             Self::MaxIterationsReached(iterations) => write!(
                 f, "MaxIterationsReached({})", iterations
+            ),
+            Self::UnknownModifier(modifier_name) => write!(
+                f, "UnknownModifier({:?})", modifier_name
             )
         }
     }
 }
 
 //
-// Error<Nt, T>: Error
+// Error<Nt, T>: Debug + Display + Error
 //
 
 /// Used as error variant for [`Result`](type.Result.html).
 ///
 /// The reason for the error can be determined via the [`kind`](struct.Error.html#structfield.kind) field.
+/// The non-terminal symbol, at which the expansion stopped, is the first one in [`state`](struct.Error.html#structfield.state)
+/// (see [`position()`](struct.Error.html#method.position)), and [`ancestors`](struct.Error.html#structfield.ancestors)
+/// describe how it was derived.
+///
+/// The `Display` implementation reports the position of that non-terminal symbol and the number of its ancestors.
+/// If non-terminal symbol values implement `Debug`, [`display_nonterms()`](struct.Error.html#method.display_nonterms)
+/// names them as well, e.g.
+///
+/// ```text
+/// no rule to expand nonterminal symbol "adjective" at position 2, derived from "sentence" -> "noun_phrase"
+/// ```
 pub struct Error<Nt, T>
     where Nt: NonterminalValue,
           T:  TerminalValue
 {
-    pub state: Vec<Symbol<Nt, T>>,
-    pub kind:  ErrorKind<Nt>
+    /// Sequence of symbols at the moment the expansion stopped.
+    pub state:     Vec<Symbol<Nt, T>>,
+    pub kind:      ErrorKind<Nt>,
+    /// Values of the non-terminal symbols, from whose expansion the first non-terminal symbol in `state`
+    /// was derived, starting with the outermost one (which was present in the input sequence).
+    /// Same as [`ExpansionContext::ancestors()`](struct.ExpansionContext.html#method.ancestors) would be
+    /// when expanding that non-terminal symbol.
+    pub ancestors: Vec<Nt>
 }
 
 impl<Nt, T> Debug for Error<Nt, T>
@@ -63,7 +94,11 @@ impl<Nt, T> Debug for Error<Nt, T>
           T:  TerminalValue
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Error {{ state, kind: {:?} }}", self.kind)
+        f.debug_struct("Error")
+            .field("kind", &self.kind)
+            .field("position", &self.position())
+            .field("ancestor_count", &self.ancestors.len())
+            .finish_non_exhaustive()
     }
 }
 
@@ -72,19 +107,7 @@ impl<Nt, T> Display for Error<Nt, T>
           T:  TerminalValue
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.kind {
-            ErrorKind::NontermExpansionFailed(_) => write!(
-                f, "no rule to expand nonterminal symbol"
-            ),
-            ErrorKind::MaxIterationsReached(iterations) => write!(
-                f,
-                "reached the maximum {} iterations with {} nonterminal symbols still unexpanded",
-                iterations,
-                self.state.iter()
-                    .filter(|symbol| symbol.is_nonterminal())
-                    .count()
-            )
-        }
+        self.fmt_message(f, None)
     }
 }
 
@@ -113,11 +136,151 @@ impl<Nt, T> Error<Nt, T>
         Self::new(state, ErrorKind::MaxIterationsReached(iterations))
     }
 
+    #[must_use]
+    pub fn unknown_modifier(state: Vec<Symbol<Nt, T>>, modifier_name: String) -> Self {
+        Self::new(state, ErrorKind::UnknownModifier(modifier_name))
+    }
+
+    #[must_use]
+    pub fn with_ancestors(self, ancestors: Vec<Nt>) -> Self {
+        Self{
+            ancestors,
+            ..self
+        }
+    }
+
+    /// Returns a wrapper, whose `Display` implementation names the non-terminal symbols involved
+    /// in addition to what `Error`'s own `Display` implementation reports.
+    #[must_use]
+    pub fn display_nonterms(&self) -> ErrorDisplay<'_, Nt, T>
+        where Nt: Debug
+    {
+        ErrorDisplay{error: self}
+    }
+
+    /// Index of the first non-terminal symbol in [`state`](struct.Error.html#structfield.state),
+    /// at which the expansion stopped, or `None` if there are no non-terminal symbols in `state`.
+    #[must_use]
+    pub fn position(&self) -> Option<usize> {
+        self.state.iter().position(Symbol::is_nonterminal)
+    }
+
     //
     // Service
     //
 
     fn new(state: Vec<Symbol<Nt, T>>, kind: ErrorKind<Nt>) -> Self {
-        Self{state, kind}
+        Self{
+            state,
+            kind,
+            ancestors: Vec::new()
+        }
+    }
+
+    /// Writes the message, naming non-terminal symbols with `fmt_nonterm`, if given.
+    fn fmt_message(&self, f: &mut fmt::Formatter<'_>, fmt_nonterm: Option<NontermFormatter<Nt>>) -> fmt::Result {
+        match &self.kind {
+            ErrorKind::NontermExpansionFailed(nonterm_value) => {
+                write!(f, "no rule to expand nonterminal symbol")?;
+
+                if let Some(fmt_nonterm) = fmt_nonterm {
+                    write!(f, " ")?;
+                    fmt_nonterm(nonterm_value, f)?;
+                }
+            },
+            ErrorKind::MaxIterationsReached(iterations) => {
+                write!(
+                    f,
+                    "reached the maximum {} iterations with {} nonterminal symbols still unexpanded",
+                    iterations,
+                    self.state.iter()
+                        .filter(|symbol| symbol.is_nonterminal())
+                        .count()
+                )?;
+
+                let maybe_first_symbol = self.position().map(|position| &self.state[position]);
+
+                if let (Some(Symbol::Nonterminal(nonterm_value)), Some(fmt_nonterm)) = (maybe_first_symbol, fmt_nonterm) {
+                    write!(f, ", the first one being ")?;
+                    fmt_nonterm(nonterm_value, f)?;
+                }
+            },
+            ErrorKind::UnknownModifier(modifier_name) => {
+                // The position of the first non-terminal symbol is unrelated to the modifier, so it is not reported.
+                write!(f, "unknown modifier {:?}", modifier_name)?;
+
+                return self.fmt_ancestors(f, fmt_nonterm);
+            }
+        }
+
+        if let Some(position) = self.position() {
+            write!(f, " at position {}", position)?;
+        }
+
+        self.fmt_ancestors(f, fmt_nonterm)
+    }
+
+    fn fmt_ancestors(&self, f: &mut fmt::Formatter<'_>, fmt_nonterm: Option<NontermFormatter<Nt>>) -> fmt::Result {
+        if self.ancestors.is_empty() {
+            return Ok(());
+        }
+
+        let fmt_nonterm = match fmt_nonterm {
+            Some(fmt_nonterm) => fmt_nonterm,
+            None              => return write!(f, ", derived from {} nonterminal symbols", self.ancestors.len())
+        };
+
+        write!(f, ", derived from ")?;
+
+        let omitted_count = self.ancestors.len().saturating_sub(MAX_DISPLAYED_ANCESTORS);
+
+        // Keep the outermost ancestor and the innermost ones, which are usually the most relevant.
+        for (ancestor_idx, ancestor) in self.ancestors.iter().enumerate() {
+            if omitted_count > 0 && ancestor_idx > 0 && ancestor_idx <= omitted_count {
+                if ancestor_idx == 1 {
+                    write!(f, " -> ... ({} more)", omitted_count)?;
+                }
+
+                continue;
+            }
+
+            if ancestor_idx > 0 {
+                write!(f, " -> ")?;
+            }
+
+            fmt_nonterm(ancestor, f)?;
+        }
+
+        Ok(())
+    }
+}
+
+//
+// ErrorDisplay<'e, Nt, T>: Display
+//
+
+/// Formats an [`Error`](struct.Error.html) like its `Display` implementation,
+/// but names the non-terminal symbols involved using their `Debug` implementation.
+///
+/// Returned by [`Error::display_nonterms()`](struct.Error.html#method.display_nonterms).
+pub struct ErrorDisplay<'e, Nt, T>
+    where Nt: NonterminalValue,
+          T:  TerminalValue
+{
+    error: &'e Error<Nt, T>
+}
+
+impl<'e, Nt, T> Display for ErrorDisplay<'e, Nt, T>
+    where Nt: NonterminalValue + Debug,
+          T:  TerminalValue
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt_message(f, Some(|nonterm_value, f| write!(f, "{:?}", nonterm_value)))
     }
 }
+
+//
+// Service
+//
+
+type NontermFormatter<Nt> = fn(&Nt, &mut fmt::Formatter<'_>) -> fmt::Result;
@@ -3,24 +3,47 @@ use crate::grammar::{
     Symbol,
     Rule
 };
+use crate::probability::RuleDistribution;
+use super::ExpansionContext;
 
 pub mod builtin;
 
+use builtin::{
+    TeeExpansionLogger,
+    NontermFilterExpansionLogger,
+    FailuresOnlyExpansionLogger
+};
+
 //
 // Interface traits
 //
 
 /// Implement this trait if you want to provide custom rule selection logic.
 ///
+/// Rule selectors receive an [`ExpansionContext`](struct.ExpansionContext.html) describing
+/// the non-terminal symbol being expanded and its place in the derivation, and may mutate
+/// their own state in order to adapt to previous selections.
+///
 /// See [crate docs](index.html#using-a-custom-rule-selector) for an example.
 pub trait RuleSelector<Nt, T>
 {
-    fn select_rule<'a>(&self, all_rules: &'a [Rule<Nt, T>], nonterm_value: &Nt) -> Option<&'a Rule<Nt,T>>
+    /// Selects one of `all_rules` to expand the non-terminal symbol described by the `context`.
+    ///
+    /// The default implementation passes the rules found by [`find_matching_rules()`](#method.find_matching_rules)
+    /// to [`select_matching_rule()`](#method.select_matching_rule). Selectors can avoid collecting them on every
+    /// expansion step by overriding this method and using
+    /// [`ExpansionContext::matching_rule_idxs()`](struct.ExpansionContext.html#method.matching_rule_idxs) instead,
+    /// as the built-in ones do.
+    ///
+    /// A selected rule, which is not one of `all_rules`, e.g. a clone of one, is still expanded, but its
+    /// [`Modifier`](struct.Modifier.html)s are not applied and loggers are not notified via
+    /// [`ExpansionLogger::on_rule_applied()`](trait.ExpansionLogger.html#method.on_rule_applied).
+    fn select_rule<'a>(&mut self, all_rules: &'a [Rule<Nt, T>], context: &ExpansionContext<Nt, T>) -> Option<&'a Rule<Nt,T>>
         where Nt: NonterminalValue
     {
-        self.select_matching_rule(
-            self.find_matching_rules(all_rules, nonterm_value).as_slice()
-        )
+        let matching_rules = self.find_matching_rules(all_rules, context.nonterm_value());
+
+        self.select_matching_rule(matching_rules.as_slice(), context)
     }
 
     fn find_matching_rules<'a>(&self, all_rules: &'a [Rule<Nt, T>], nonterm_value: &Nt) -> Vec<&'a Rule<Nt, T>>
@@ -31,21 +54,59 @@ pub trait RuleSelector<Nt, T>
             .collect()
     }
 
-    fn select_matching_rule<'a>(&self, matching_rules: &[&'a Rule<Nt, T>]) -> Option<&'a Rule<Nt, T>>;
+    fn select_matching_rule<'a>(
+        &mut self,
+        matching_rules: &[&'a Rule<Nt, T>],
+        context:        &ExpansionContext<Nt, T>
+    ) -> Option<&'a Rule<Nt, T>>;
+
+    /// Called whenever the rules of the [`Expander`](struct.Expander.html) using this selector are changed
+    /// (see [`Expander::update_rules()`](struct.Expander.html#method.update_rules)), so that stateful selectors
+    /// can discard state, which refers to the previous rules.
+    fn on_rules_changed(&mut self, _all_rules: &[Rule<Nt, T>]) {
+        // Empty
+    }
+
+    /// Returns the distribution, with which this selector selects among matching rules, for the purposes
+    /// of probabilistic analysis (see [`Expander::probabilistic_grammar()`](struct.Expander.html#method.probabilistic_grammar)).
+    /// Selectors, whose selections depend on their state, should return the distribution they approximate in the long run.
+    fn rule_distribution(&self) -> RuleDistribution {
+        RuleDistribution::Uniform
+    }
 }
 
 /// Implement this trait if you want to log or otherwise handle individual steps during expansion.
 ///
 /// See [crate docs](index.html#logging) for an example.
 pub trait ExpansionLogger<Nt, T> {
+    fn on_expansion_started(&mut self, _input: &[Symbol<Nt, T>]) {
+        // Empty
+    }
+
     fn on_nonterm_expanded(&mut self, _expanded_nonterm_value: &Nt, _rule: &Rule<Nt, T>) {
         // Empty
     }
 
+    /// Called right after [`on_nonterm_expanded()`](#method.on_nonterm_expanded) with the index of the applied rule
+    /// among the rules of the [`Expander`](struct.Expander.html), so that loggers can tell rules apart
+    /// without comparing them.
+    fn on_rule_applied(&mut self, _expanded_nonterm_value: &Nt, _rule_idx: usize) {
+        // Empty
+    }
+
+    /// Called when there is no rule to expand a non-terminal symbol or when the selected rule refers to a modifier,
+    /// which is not registered in the [`ModifierRegistry`](struct.ModifierRegistry.html).
     fn on_nonterm_expansion_failed(&mut self, _expanded_nonterm_value: &Nt) {
         // Empty
     }
 
+    /// Called instead of [`on_nonterm_expansion_failed()`](#method.on_nonterm_expansion_failed),
+    /// when there is no rule to expand a non-terminal symbol, but it is replaced with `replacement`
+    /// according to the [`RecoveryPolicy`](enum.RecoveryPolicy.html).
+    fn on_nonterm_expansion_recovered(&mut self, _recovered_nonterm_value: &Nt, _replacement: &[Symbol<Nt, T>]) {
+        // Empty
+    }
+
     fn on_input_fully_expanded(&mut self, _expansion_result: &[T]) {
         // Empty
     }
@@ -54,3 +115,100 @@ pub trait ExpansionLogger<Nt, T> {
         // Empty
     }
 }
+
+/// Provides combinators for [`ExpansionLogger`](trait.ExpansionLogger.html)s.
+///
+/// Automatically implemented for all types implementing [`ExpansionLogger`](trait.ExpansionLogger.html).
+/// Since `ExpansionLogger` is also implemented for `&mut L` and `Box<L>`, combined loggers
+/// may borrow their parts instead of taking ownership of them.
+pub trait ExpansionLoggerExt<Nt, T>: ExpansionLogger<Nt, T> + Sized {
+    /// Combines this logger with `other`, so that both are notified of every step.
+    fn and<L>(self, other: L) -> TeeExpansionLogger<Nt, T, Self, L>
+        where L: ExpansionLogger<Nt, T>
+    {
+        TeeExpansionLogger::new(self, other)
+    }
+
+    /// Makes this logger ignore notifications about individual non-terminal symbols,
+    /// whose values do not satisfy the `predicate`.
+    fn filter_nonterm<F>(self, predicate: F) -> NontermFilterExpansionLogger<Nt, T, Self, F>
+        where F: FnMut(&Nt) -> bool
+    {
+        NontermFilterExpansionLogger::new(self, predicate)
+    }
+
+    /// Makes this logger ignore all notifications except for the ones about failures.
+    fn only_failures(self) -> FailuresOnlyExpansionLogger<Nt, T, Self> {
+        FailuresOnlyExpansionLogger::new(self)
+    }
+}
+
+impl<Nt, T, L> ExpansionLoggerExt<Nt, T> for L
+    where L: ExpansionLogger<Nt, T>
+{
+    // Default
+}
+
+impl<Nt, T, L> ExpansionLogger<Nt, T> for &mut L
+    where L: ExpansionLogger<Nt, T> + ?Sized
+{
+    fn on_expansion_started(&mut self, input: &[Symbol<Nt, T>]) {
+        (**self).on_expansion_started(input);
+    }
+
+    fn on_nonterm_expanded(&mut self, expanded_nonterm_value: &Nt, rule: &Rule<Nt, T>) {
+        (**self).on_nonterm_expanded(expanded_nonterm_value, rule);
+    }
+
+    fn on_rule_applied(&mut self, expanded_nonterm_value: &Nt, rule_idx: usize) {
+        (**self).on_rule_applied(expanded_nonterm_value, rule_idx);
+    }
+
+    fn on_nonterm_expansion_failed(&mut self, expanded_nonterm_value: &Nt) {
+        (**self).on_nonterm_expansion_failed(expanded_nonterm_value);
+    }
+
+    fn on_nonterm_expansion_recovered(&mut self, recovered_nonterm_value: &Nt, replacement: &[Symbol<Nt, T>]) {
+        (**self).on_nonterm_expansion_recovered(recovered_nonterm_value, replacement);
+    }
+
+    fn on_input_fully_expanded(&mut self, expansion_result: &[T]) {
+        (**self).on_input_fully_expanded(expansion_result);
+    }
+
+    fn on_max_iterations_reached(&mut self, current_state: &[Symbol<Nt, T>], iterations: usize) {
+        (**self).on_max_iterations_reached(current_state, iterations);
+    }
+}
+
+impl<Nt, T, L> ExpansionLogger<Nt, T> for Box<L>
+    where L: ExpansionLogger<Nt, T> + ?Sized
+{
+    fn on_expansion_started(&mut self, input: &[Symbol<Nt, T>]) {
+        (**self).on_expansion_started(input);
+    }
+
+    fn on_nonterm_expanded(&mut self, expanded_nonterm_value: &Nt, rule: &Rule<Nt, T>) {
+        (**self).on_nonterm_expanded(expanded_nonterm_value, rule);
+    }
+
+    fn on_rule_applied(&mut self, expanded_nonterm_value: &Nt, rule_idx: usize) {
+        (**self).on_rule_applied(expanded_nonterm_value, rule_idx);
+    }
+
+    fn on_nonterm_expansion_failed(&mut self, expanded_nonterm_value: &Nt) {
+        (**self).on_nonterm_expansion_failed(expanded_nonterm_value);
+    }
+
+    fn on_nonterm_expansion_recovered(&mut self, recovered_nonterm_value: &Nt, replacement: &[Symbol<Nt, T>]) {
+        (**self).on_nonterm_expansion_recovered(recovered_nonterm_value, replacement);
+    }
+
+    fn on_input_fully_expanded(&mut self, expansion_result: &[T]) {
+        (**self).on_input_fully_expanded(expansion_result);
+    }
+
+    fn on_max_iterations_reached(&mut self, current_state: &[Symbol<Nt, T>], iterations: usize) {
+        (**self).on_max_iterations_reached(current_state, iterations);
+    }
+}
@@ -1,11 +1,45 @@
-use rand::Rng;
+use rand::{
+    Rng,
+    seq::SliceRandom
+};
 
-use crate::grammar::Rule;
+use crate::grammar::{
+    NonterminalValue,
+    Rule
+};
+use crate::probability::RuleDistribution;
 use super::{
     RuleSelector,
-    ExpansionLogger
+    ExpansionLogger,
+    ExpansionContext
+};
+
+mod stats_logger;
+mod combinators;
+
+#[cfg(feature = "tracing")]
+mod tracing_logger;
+
+#[cfg(feature = "log")]
+mod log_logger;
+
+pub use combinators::{
+    TeeExpansionLogger,
+    NontermFilterExpansionLogger,
+    FailuresOnlyExpansionLogger
 };
 
+pub use stats_logger::{
+    StatsExpansionLogger,
+    CoverageReport
+};
+
+#[cfg(feature = "tracing")]
+pub use tracing_logger::TracingExpansionLogger;
+
+#[cfg(feature = "log")]
+pub use log_logger::LogExpansionLogger;
+
 //
 // Interface types
 //
@@ -25,14 +59,22 @@ impl Default for UniformRandomRuleSelector {
 }
 
 impl<Nt, T> RuleSelector<Nt, T> for UniformRandomRuleSelector {
-    fn select_matching_rule<'a>(&self, matching_rules: &[&'a Rule<Nt, T>]) -> Option<&'a Rule<Nt, T>> {
-        if matching_rules.is_empty() {
-            None
-        } else {
-            let selected_rule_idx = rand::thread_rng().gen_range(0, matching_rules.len());
+    fn select_rule<'a>(&mut self, all_rules: &'a [Rule<Nt, T>], context: &ExpansionContext<Nt, T>) -> Option<&'a Rule<Nt,T>>
+        where Nt: NonterminalValue
+    {
+        let matching_rule_idxs = context.matching_rule_idxs();
 
-            Some(matching_rules[selected_rule_idx])
-        }
+        random_idx(matching_rule_idxs.len())
+            .map(|selected_idx| &all_rules[matching_rule_idxs[selected_idx]])
+    }
+
+    fn select_matching_rule<'a>(
+        &mut self,
+        matching_rules: &[&'a Rule<Nt, T>],
+        _context:       &ExpansionContext<Nt, T>
+    ) -> Option<&'a Rule<Nt, T>> {
+        random_idx(matching_rules.len())
+            .map(|selected_idx| matching_rules[selected_idx])
     }
 }
 
@@ -43,6 +85,210 @@ impl UniformRandomRuleSelector {
     }
 }
 
+//
+// WeightedRandomRuleSelector: RuleSelector<Nt, T> + Default
+//
+
+/// Randomly selects one of the matching rules for every encountered non-terminal symbol
+/// with probability proportional to its [`weight`](struct.Rule.html#structfield.weight).
+///
+/// Rules with zero, negative or NaN weights are never selected. If none of the matching rules
+/// has a positive weight, no rule is selected.
+pub struct WeightedRandomRuleSelector;
+
+impl Default for WeightedRandomRuleSelector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Nt, T> RuleSelector<Nt, T> for WeightedRandomRuleSelector {
+    fn select_rule<'a>(&mut self, all_rules: &'a [Rule<Nt, T>], context: &ExpansionContext<Nt, T>) -> Option<&'a Rule<Nt,T>>
+        where Nt: NonterminalValue
+    {
+        select_weighted(context.matching_rule_idxs().iter().map(|&rule_idx| &all_rules[rule_idx]))
+    }
+
+    fn select_matching_rule<'a>(
+        &mut self,
+        matching_rules: &[&'a Rule<Nt, T>],
+        _context:       &ExpansionContext<Nt, T>
+    ) -> Option<&'a Rule<Nt, T>> {
+        select_weighted(matching_rules.iter().copied())
+    }
+
+    fn rule_distribution(&self) -> RuleDistribution {
+        RuleDistribution::Weighted
+    }
+}
+
+impl WeightedRandomRuleSelector {
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+//
+// ShuffleBagRuleSelector<Nt>: RuleSelector<Nt, T> + Default
+//
+
+/// Stateful rule selector, which goes through all matching rules for a non-terminal symbol
+/// in random order before selecting any of them again.
+///
+/// A separate "bag" of rules is kept for every encountered non-terminal symbol value.
+/// Once a bag is emptied, it is refilled and shuffled anew, making sure that the rule,
+/// which was selected last, does not immediately come up again.
+pub struct ShuffleBagRuleSelector<Nt> {
+    bags: Vec<(Nt, ShuffleBag)>
+}
+
+impl<Nt> Default for ShuffleBagRuleSelector<Nt> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Nt, T> RuleSelector<Nt, T> for ShuffleBagRuleSelector<Nt>
+    where Nt: NonterminalValue
+{
+    fn select_rule<'a>(&mut self, all_rules: &'a [Rule<Nt, T>], context: &ExpansionContext<Nt, T>) -> Option<&'a Rule<Nt,T>> {
+        let matching_rule_idxs = context.matching_rule_idxs();
+
+        self.select_idx(matching_rule_idxs.len(), context.nonterm_value())
+            .map(|selected_idx| &all_rules[matching_rule_idxs[selected_idx]])
+    }
+
+    fn select_matching_rule<'a>(
+        &mut self,
+        matching_rules: &[&'a Rule<Nt, T>],
+        context:        &ExpansionContext<Nt, T>
+    ) -> Option<&'a Rule<Nt, T>> {
+        self.select_idx(matching_rules.len(), context.nonterm_value())
+            .map(|selected_idx| matching_rules[selected_idx])
+    }
+
+    fn on_rules_changed(&mut self, _all_rules: &[Rule<Nt, T>]) {
+        self.bags.clear();
+    }
+}
+
+impl<Nt> ShuffleBagRuleSelector<Nt> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self{
+            bags: Vec::new()
+        }
+    }
+}
+
+impl<Nt> ShuffleBagRuleSelector<Nt>
+    where Nt: NonterminalValue
+{
+    /// Draws the index of one of `rule_count` matching rules for `nonterm_value` from its bag.
+    fn select_idx(&mut self, rule_count: usize, nonterm_value: &Nt) -> Option<usize> {
+        if rule_count == 0 {
+            return None;
+        }
+
+        let bag_idx = if let Some(bag_idx) = self.bags.iter().position(|(value, _)| value == nonterm_value) {
+            bag_idx
+        } else {
+            self.bags.push((nonterm_value.clone(), ShuffleBag::new()));
+
+            self.bags.len() - 1
+        };
+
+        Some(self.bags[bag_idx].1.draw(rule_count))
+    }
+}
+
+//
+// NoImmediateRepeatRuleSelector<Nt>: RuleSelector<Nt, T> + Default
+//
+
+/// Stateful rule selector, which randomly selects one of the matching rules
+/// for every encountered non-terminal symbol, except for the one selected for the same
+/// non-terminal symbol value the last time.
+///
+/// If there is only one matching rule, it is always selected.
+pub struct NoImmediateRepeatRuleSelector<Nt> {
+    last_selected: Vec<(Nt, usize)>
+}
+
+impl<Nt> Default for NoImmediateRepeatRuleSelector<Nt> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Nt, T> RuleSelector<Nt, T> for NoImmediateRepeatRuleSelector<Nt>
+    where Nt: NonterminalValue
+{
+    fn select_rule<'a>(&mut self, all_rules: &'a [Rule<Nt, T>], context: &ExpansionContext<Nt, T>) -> Option<&'a Rule<Nt,T>> {
+        let matching_rule_idxs = context.matching_rule_idxs();
+
+        self.select_idx(matching_rule_idxs.len(), context.nonterm_value())
+            .map(|selected_idx| &all_rules[matching_rule_idxs[selected_idx]])
+    }
+
+    fn select_matching_rule<'a>(
+        &mut self,
+        matching_rules: &[&'a Rule<Nt, T>],
+        context:        &ExpansionContext<Nt, T>
+    ) -> Option<&'a Rule<Nt, T>> {
+        self.select_idx(matching_rules.len(), context.nonterm_value())
+            .map(|selected_idx| matching_rules[selected_idx])
+    }
+
+    fn on_rules_changed(&mut self, _all_rules: &[Rule<Nt, T>]) {
+        self.last_selected.clear();
+    }
+}
+
+impl<Nt> NoImmediateRepeatRuleSelector<Nt> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self{
+            last_selected: Vec::new()
+        }
+    }
+}
+
+impl<Nt> NoImmediateRepeatRuleSelector<Nt>
+    where Nt: NonterminalValue
+{
+    /// Selects the index of one of `rule_count` matching rules for `nonterm_value`,
+    /// other than the one selected for it the last time.
+    fn select_idx(&mut self, rule_count: usize, nonterm_value: &Nt) -> Option<usize> {
+        if rule_count == 0 {
+            return None;
+        }
+
+        let maybe_last_selected = self.last_selected.iter_mut()
+            .find(|(value, _)| value == nonterm_value);
+
+        let selected_idx = match maybe_last_selected {
+            Some((_, last_selected_idx)) => {
+                let selected_idx = select_other_idx(rule_count, *last_selected_idx);
+
+                *last_selected_idx = selected_idx;
+
+                selected_idx
+            },
+            None => {
+                let selected_idx = rand::thread_rng().gen_range(0, rule_count);
+
+                self.last_selected.push((nonterm_value.clone(), selected_idx));
+
+                selected_idx
+            }
+        };
+
+        Some(selected_idx)
+    }
+}
+
 //
 // NullExpansionLogger: ExpansionLogger<Nt, T>
 //
@@ -53,3 +299,127 @@ pub struct NullExpansionLogger;
 impl<Nt, T> ExpansionLogger<Nt, T> for NullExpansionLogger {
     // Default
 }
+
+//
+// Service types
+//
+
+//
+// ShuffleBag
+//
+
+struct ShuffleBag {
+    remaining_idxs: Vec<usize>,
+    size:           usize,
+    last_drawn_idx: Option<usize>
+}
+
+impl ShuffleBag {
+    fn new() -> Self {
+        Self{
+            remaining_idxs: Vec::new(),
+            size:           0,
+            last_drawn_idx: None
+        }
+    }
+
+    fn draw(&mut self, size: usize) -> usize {
+        if size != self.size {
+            // The set of matching rules has changed, so the bag contents are no longer valid.
+            self.size = size;
+            self.remaining_idxs.clear();
+            self.last_drawn_idx = None;
+        }
+
+        if self.remaining_idxs.is_empty() {
+            self.refill();
+        }
+
+        let drawn_idx = self.remaining_idxs.pop()
+            .expect("bag must not be empty after refill");
+
+        self.last_drawn_idx = Some(drawn_idx);
+
+        drawn_idx
+    }
+
+    fn refill(&mut self) {
+        self.remaining_idxs.extend(0..self.size);
+        self.remaining_idxs.shuffle(&mut rand::thread_rng());
+
+        // Indices are drawn from the back, so make sure that the one drawn last
+        // from the previous bag won't be the first one drawn from the new bag.
+        if self.size > 1 && self.remaining_idxs.last() == self.last_drawn_idx.as_ref() {
+            let swap_idx = rand::thread_rng().gen_range(0, self.size - 1);
+
+            self.remaining_idxs.swap(swap_idx, self.size - 1);
+        }
+    }
+}
+
+//
+// Service
+//
+
+/// Returns a random index below `len`, or `None` if `len` is zero.
+fn random_idx(len: usize) -> Option<usize> {
+    if len == 0 {
+        None
+    } else {
+        Some(rand::thread_rng().gen_range(0, len))
+    }
+}
+
+/// Selects one of `matching_rules` with probability proportional to its weight, see
+/// [`WeightedRandomRuleSelector`](struct.WeightedRandomRuleSelector.html).
+fn select_weighted<'a, Nt, T, Rs>(matching_rules: Rs) -> Option<&'a Rule<Nt, T>>
+    where Rs: DoubleEndedIterator<Item = &'a Rule<Nt, T>> + Clone
+{
+    let total_weight: f64 = matching_rules.clone()
+        .map(selection_weight)
+        .sum();
+
+    if !(total_weight > 0.0 && total_weight.is_finite()) {
+        return None;
+    }
+
+    let mut remaining_weight = rand::thread_rng().gen_range(0.0, total_weight);
+
+    for rule in matching_rules.clone() {
+        let weight = selection_weight(rule);
+
+        if remaining_weight < weight {
+            return Some(rule);
+        }
+
+        remaining_weight -= weight;
+    }
+
+    // Rounding errors may leave a tiny remainder, in which case the last selectable rule is selected.
+    matching_rules.rev()
+        .find(|rule| selection_weight(rule) > 0.0)
+}
+
+fn selection_weight<Nt, T>(rule: &Rule<Nt, T>) -> f64 {
+    if rule.weight > 0.0 {
+        rule.weight
+    } else {
+        0.0
+    }
+}
+
+fn select_other_idx(len: usize, excluded_idx: usize) -> usize {
+    if len < 2 {
+        0
+    } else if excluded_idx >= len {
+        rand::thread_rng().gen_range(0, len)
+    } else {
+        let selected_idx = rand::thread_rng().gen_range(0, len - 1);
+
+        if selected_idx >= excluded_idx {
+            selected_idx + 1
+        } else {
+            selected_idx
+        }
+    }
+}
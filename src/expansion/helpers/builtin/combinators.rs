@@ -0,0 +1,213 @@
+use std::marker::PhantomData;
+
+use crate::grammar::{
+    Symbol,
+    Rule
+};
+use super::ExpansionLogger;
+
+//
+// Interface types
+//
+
+//
+// TeeExpansionLogger<Nt, T, A, B>: ExpansionLogger<Nt, T>
+//
+
+/// Forwards every notification to both of the contained loggers, first to `A`, then to `B`.
+///
+/// Constructed via [`ExpansionLoggerExt::and()`](trait.ExpansionLoggerExt.html#method.and).
+pub struct TeeExpansionLogger<Nt, T, A, B> {
+    first:    A,
+    second:   B,
+    _phantom: PhantomData<fn(&Nt, &T)>
+}
+
+impl<Nt, T, A, B> ExpansionLogger<Nt, T> for TeeExpansionLogger<Nt, T, A, B>
+    where A: ExpansionLogger<Nt, T>,
+          B: ExpansionLogger<Nt, T>
+{
+    fn on_expansion_started(&mut self, input: &[Symbol<Nt, T>]) {
+        self.first.on_expansion_started(input);
+        self.second.on_expansion_started(input);
+    }
+
+    fn on_nonterm_expanded(&mut self, expanded_nonterm_value: &Nt, rule: &Rule<Nt, T>) {
+        self.first.on_nonterm_expanded(expanded_nonterm_value, rule);
+        self.second.on_nonterm_expanded(expanded_nonterm_value, rule);
+    }
+
+    fn on_rule_applied(&mut self, expanded_nonterm_value: &Nt, rule_idx: usize) {
+        self.first.on_rule_applied(expanded_nonterm_value, rule_idx);
+        self.second.on_rule_applied(expanded_nonterm_value, rule_idx);
+    }
+
+    fn on_nonterm_expansion_failed(&mut self, expanded_nonterm_value: &Nt) {
+        self.first.on_nonterm_expansion_failed(expanded_nonterm_value);
+        self.second.on_nonterm_expansion_failed(expanded_nonterm_value);
+    }
+
+    fn on_nonterm_expansion_recovered(&mut self, recovered_nonterm_value: &Nt, replacement: &[Symbol<Nt, T>]) {
+        self.first.on_nonterm_expansion_recovered(recovered_nonterm_value, replacement);
+        self.second.on_nonterm_expansion_recovered(recovered_nonterm_value, replacement);
+    }
+
+    fn on_input_fully_expanded(&mut self, expansion_result: &[T]) {
+        self.first.on_input_fully_expanded(expansion_result);
+        self.second.on_input_fully_expanded(expansion_result);
+    }
+
+    fn on_max_iterations_reached(&mut self, current_state: &[Symbol<Nt, T>], iterations: usize) {
+        self.first.on_max_iterations_reached(current_state, iterations);
+        self.second.on_max_iterations_reached(current_state, iterations);
+    }
+}
+
+impl<Nt, T, A, B> TeeExpansionLogger<Nt, T, A, B> {
+    #[must_use]
+    pub fn new(first: A, second: B) -> Self {
+        Self{
+            first,
+            second,
+            _phantom: PhantomData
+        }
+    }
+
+    /// Returns the contained loggers.
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+
+    /// Returns references to the contained loggers.
+    pub fn get_ref(&self) -> (&A, &B) {
+        (&self.first, &self.second)
+    }
+}
+
+//
+// NontermFilterExpansionLogger<Nt, T, L, F>: ExpansionLogger<Nt, T>
+//
+
+/// Forwards notifications about individual non-terminal symbols only if their values satisfy the predicate `F`.
+///
+/// Notifications, which concern the expansion as a whole, are always forwarded.
+///
+/// Constructed via [`ExpansionLoggerExt::filter_nonterm()`](trait.ExpansionLoggerExt.html#method.filter_nonterm).
+pub struct NontermFilterExpansionLogger<Nt, T, L, F> {
+    inner:     L,
+    predicate: F,
+    _phantom:  PhantomData<fn(&Nt, &T)>
+}
+
+impl<Nt, T, L, F> ExpansionLogger<Nt, T> for NontermFilterExpansionLogger<Nt, T, L, F>
+    where L: ExpansionLogger<Nt, T>,
+          F: FnMut(&Nt) -> bool
+{
+    fn on_expansion_started(&mut self, input: &[Symbol<Nt, T>]) {
+        self.inner.on_expansion_started(input);
+    }
+
+    fn on_nonterm_expanded(&mut self, expanded_nonterm_value: &Nt, rule: &Rule<Nt, T>) {
+        if (self.predicate)(expanded_nonterm_value) {
+            self.inner.on_nonterm_expanded(expanded_nonterm_value, rule);
+        }
+    }
+
+    fn on_rule_applied(&mut self, expanded_nonterm_value: &Nt, rule_idx: usize) {
+        if (self.predicate)(expanded_nonterm_value) {
+            self.inner.on_rule_applied(expanded_nonterm_value, rule_idx);
+        }
+    }
+
+    fn on_nonterm_expansion_failed(&mut self, expanded_nonterm_value: &Nt) {
+        if (self.predicate)(expanded_nonterm_value) {
+            self.inner.on_nonterm_expansion_failed(expanded_nonterm_value);
+        }
+    }
+
+    fn on_nonterm_expansion_recovered(&mut self, recovered_nonterm_value: &Nt, replacement: &[Symbol<Nt, T>]) {
+        if (self.predicate)(recovered_nonterm_value) {
+            self.inner.on_nonterm_expansion_recovered(recovered_nonterm_value, replacement);
+        }
+    }
+
+    fn on_input_fully_expanded(&mut self, expansion_result: &[T]) {
+        self.inner.on_input_fully_expanded(expansion_result);
+    }
+
+    fn on_max_iterations_reached(&mut self, current_state: &[Symbol<Nt, T>], iterations: usize) {
+        self.inner.on_max_iterations_reached(current_state, iterations);
+    }
+}
+
+impl<Nt, T, L, F> NontermFilterExpansionLogger<Nt, T, L, F> {
+    #[must_use]
+    pub fn new(inner: L, predicate: F) -> Self {
+        Self{
+            inner,
+            predicate,
+            _phantom: PhantomData
+        }
+    }
+
+    /// Returns the contained logger.
+    pub fn into_inner(self) -> L {
+        self.inner
+    }
+
+    /// Returns a reference to the contained logger.
+    pub fn get_ref(&self) -> &L {
+        &self.inner
+    }
+}
+
+//
+// FailuresOnlyExpansionLogger<Nt, T, L>: ExpansionLogger<Nt, T>
+//
+
+/// Forwards only the notifications about failures, i.e.
+/// [`on_nonterm_expansion_failed()`](trait.ExpansionLogger.html#method.on_nonterm_expansion_failed),
+/// [`on_nonterm_expansion_recovered()`](trait.ExpansionLogger.html#method.on_nonterm_expansion_recovered)
+/// and [`on_max_iterations_reached()`](trait.ExpansionLogger.html#method.on_max_iterations_reached).
+///
+/// Constructed via [`ExpansionLoggerExt::only_failures()`](trait.ExpansionLoggerExt.html#method.only_failures).
+pub struct FailuresOnlyExpansionLogger<Nt, T, L> {
+    inner:    L,
+    _phantom: PhantomData<fn(&Nt, &T)>
+}
+
+impl<Nt, T, L> ExpansionLogger<Nt, T> for FailuresOnlyExpansionLogger<Nt, T, L>
+    where L: ExpansionLogger<Nt, T>
+{
+    fn on_nonterm_expansion_failed(&mut self, expanded_nonterm_value: &Nt) {
+        self.inner.on_nonterm_expansion_failed(expanded_nonterm_value);
+    }
+
+    fn on_nonterm_expansion_recovered(&mut self, recovered_nonterm_value: &Nt, replacement: &[Symbol<Nt, T>]) {
+        self.inner.on_nonterm_expansion_recovered(recovered_nonterm_value, replacement);
+    }
+
+    fn on_max_iterations_reached(&mut self, current_state: &[Symbol<Nt, T>], iterations: usize) {
+        self.inner.on_max_iterations_reached(current_state, iterations);
+    }
+}
+
+impl<Nt, T, L> FailuresOnlyExpansionLogger<Nt, T, L> {
+    #[must_use]
+    pub fn new(inner: L) -> Self {
+        Self{
+            inner,
+            _phantom: PhantomData
+        }
+    }
+
+    /// Returns the contained logger.
+    pub fn into_inner(self) -> L {
+        self.inner
+    }
+
+    /// Returns a reference to the contained logger.
+    pub fn get_ref(&self) -> &L {
+        &self.inner
+    }
+}
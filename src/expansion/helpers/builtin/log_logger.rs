@@ -0,0 +1,87 @@
+use std::fmt::Debug;
+
+use crate::grammar::{
+    Symbol,
+    Rule
+};
+use super::ExpansionLogger;
+
+//
+// Interface types
+//
+
+//
+// LogExpansionLogger: ExpansionLogger<Nt, T> + Default
+//
+
+/// Writes a [`log`](https://docs.rs/log) record for every step of the expansion.
+///
+/// Individual non-terminal expansions are logged at `Trace` level, start and successful completion
+/// of an [`Expander::expand()`](struct.Expander.html#method.expand) call at `Debug` level
+/// and failures at `Warn` level. All records use `branchy` as their target and are prefixed
+/// with the sequential number of the `expand()` call, so that records of different calls can be told apart.
+///
+/// Available with the `log` cargo feature.
+#[derive(Default)]
+pub struct LogExpansionLogger {
+    expansion_count: usize
+}
+
+impl<Nt, T> ExpansionLogger<Nt, T> for LogExpansionLogger
+    where Nt: Debug,
+          T:  Debug
+{
+    fn on_expansion_started(&mut self, input: &[Symbol<Nt, T>]) {
+        self.expansion_count += 1;
+
+        log::debug!(target: "branchy", "expand#{}: started with input {:?}", self.expansion_count, input);
+    }
+
+    fn on_nonterm_expanded(&mut self, expanded_nonterm_value: &Nt, rule: &Rule<Nt, T>) {
+        log::trace!(
+            target: "branchy",
+            "expand#{}: expanded {:?} to {:?}", self.expansion_count, expanded_nonterm_value, rule.replacement
+        );
+    }
+
+    fn on_nonterm_expansion_failed(&mut self, expanded_nonterm_value: &Nt) {
+        log::warn!(
+            target: "branchy",
+            "expand#{}: failed to expand {:?}", self.expansion_count, expanded_nonterm_value
+        );
+    }
+
+    fn on_nonterm_expansion_recovered(&mut self, recovered_nonterm_value: &Nt, replacement: &[Symbol<Nt, T>]) {
+        log::warn!(
+            target: "branchy",
+            "expand#{}: no rule to expand {:?}, replaced with {:?}",
+            self.expansion_count, recovered_nonterm_value, replacement
+        );
+    }
+
+    fn on_input_fully_expanded(&mut self, expansion_result: &[T]) {
+        log::debug!(
+            target: "branchy",
+            "expand#{}: fully expanded to {:?}", self.expansion_count, expansion_result
+        );
+    }
+
+    fn on_max_iterations_reached(&mut self, current_state: &[Symbol<Nt, T>], iterations: usize) {
+        log::warn!(
+            target: "branchy",
+            "expand#{}: reached the maximum {} iterations with {} nonterminal symbols still unexpanded",
+            self.expansion_count,
+            iterations,
+            current_state.iter().filter(|symbol| symbol.is_nonterminal()).count()
+        );
+    }
+}
+
+impl LogExpansionLogger {
+    #[must_use]
+    pub fn new() -> Self {
+        Self{
+            expansion_count: 0
+        }
+    }
+}
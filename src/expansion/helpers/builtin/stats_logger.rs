@@ -0,0 +1,281 @@
+use std::{
+    collections::BTreeMap,
+    marker::PhantomData
+};
+
+use crate::grammar::{
+    NonterminalValue,
+    TerminalValue,
+    Symbol,
+    Rule
+};
+use super::ExpansionLogger;
+
+//
+// Interface types
+//
+
+//
+// StatsExpansionLogger<Nt, T>: ExpansionLogger<Nt, T> + Default
+//
+
+/// Accumulates statistics across any number of [`Expander::expand()`](struct.Expander.html#method.expand) calls.
+///
+/// Counts how many times each rule was selected and each non-terminal symbol value was expanded,
+/// how many expansions failed and why, which non-terminal symbols were recovered according to the
+/// [`RecoveryPolicy`](enum.RecoveryPolicy.html), and how long the successfully expanded outputs were.
+/// Use [`coverage_report()`](struct.StatsExpansionLogger.html#method.coverage_report) to find out,
+/// which rules of a grammar have never been used.
+///
+/// Rules are identified by their indices among the rules of the [`Expander`](struct.Expander.html),
+/// so counts of rules, which were added, removed or reordered after some expansions,
+/// are only meaningful after a [`reset()`](struct.StatsExpansionLogger.html#method.reset).
+/// Non-terminal symbol values are compared with `==`, so the cost of recording a step
+/// grows with the number of distinct non-terminal symbol values expanded so far.
+pub struct StatsExpansionLogger<Nt, T> {
+    expansion_count:              usize,
+    success_count:                usize,
+    nonterm_failure_count:        usize,
+    max_iterations_reached_count: usize,
+    rule_selection_counts:        Vec<usize>,
+    nonterm_expansion_counts:     Vec<(Nt, usize)>,
+    nonterm_failure_counts:       Vec<(Nt, usize)>,
+    nonterm_recovery_counts:      Vec<(Nt, usize)>,
+    output_length_histogram:      BTreeMap<usize, usize>,
+    _phantom:                     PhantomData<fn(&T)>
+}
+
+impl<Nt, T> Default for StatsExpansionLogger<Nt, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Nt, T> ExpansionLogger<Nt, T> for StatsExpansionLogger<Nt, T>
+    where Nt: NonterminalValue,
+          T:  TerminalValue
+{
+    fn on_expansion_started(&mut self, _input: &[Symbol<Nt, T>]) {
+        self.expansion_count += 1;
+    }
+
+    fn on_nonterm_expanded(&mut self, expanded_nonterm_value: &Nt, _rule: &Rule<Nt, T>) {
+        increment_count(&mut self.nonterm_expansion_counts, expanded_nonterm_value);
+    }
+
+    fn on_rule_applied(&mut self, _expanded_nonterm_value: &Nt, rule_idx: usize) {
+        if rule_idx >= self.rule_selection_counts.len() {
+            self.rule_selection_counts.resize(rule_idx + 1, 0);
+        }
+
+        self.rule_selection_counts[rule_idx] += 1;
+    }
+
+    fn on_nonterm_expansion_failed(&mut self, expanded_nonterm_value: &Nt) {
+        self.nonterm_failure_count += 1;
+
+        increment_count(&mut self.nonterm_failure_counts, expanded_nonterm_value);
+    }
+
+    fn on_nonterm_expansion_recovered(&mut self, recovered_nonterm_value: &Nt, _replacement: &[Symbol<Nt, T>]) {
+        increment_count(&mut self.nonterm_recovery_counts, recovered_nonterm_value);
+    }
+
+    fn on_input_fully_expanded(&mut self, expansion_result: &[T]) {
+        self.success_count += 1;
+
+        *self.output_length_histogram.entry(expansion_result.len()).or_insert(0) += 1;
+    }
+
+    fn on_max_iterations_reached(&mut self, _current_state: &[Symbol<Nt, T>], _iterations: usize) {
+        self.max_iterations_reached_count += 1;
+    }
+}
+
+impl<Nt, T> StatsExpansionLogger<Nt, T> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self{
+            expansion_count:              0,
+            success_count:                0,
+            nonterm_failure_count:        0,
+            max_iterations_reached_count: 0,
+            rule_selection_counts:        Vec::new(),
+            nonterm_expansion_counts:     Vec::new(),
+            nonterm_failure_counts:       Vec::new(),
+            nonterm_recovery_counts:      Vec::new(),
+            output_length_histogram:      BTreeMap::new(),
+            _phantom:                     PhantomData
+        }
+    }
+
+    /// Discards all statistics accumulated so far.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Number of expansions started.
+    #[must_use]
+    pub fn expansion_count(&self) -> usize {
+        self.expansion_count
+    }
+
+    /// Number of expansions which resulted in a fully expanded sequence.
+    #[must_use]
+    pub fn success_count(&self) -> usize {
+        self.success_count
+    }
+
+    /// Number of expansions which failed, because some non-terminal symbol could not be expanded,
+    /// i.e. there was no rule for it and it was not recovered, or the selected rule referred to an unknown modifier.
+    #[must_use]
+    pub fn nonterm_expansion_failure_count(&self) -> usize {
+        self.nonterm_failure_count
+    }
+
+    /// Number of expansions which failed due to reaching the maximum number of iterations.
+    #[must_use]
+    pub fn max_iterations_reached_count(&self) -> usize {
+        self.max_iterations_reached_count
+    }
+
+    /// Indices of the rules selected at least once, along with the number of times each of them was selected.
+    pub fn rule_selection_counts(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.rule_selection_counts.iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(rule_idx, count)| (rule_idx, *count))
+    }
+
+    /// Number of times the rule at `rule_idx` among the rules of the [`Expander`](struct.Expander.html) was selected.
+    #[must_use]
+    pub fn rule_selection_count(&self, rule_idx: usize) -> usize {
+        self.rule_selection_counts.get(rule_idx)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Non-terminal symbol values expanded at least once, along with the number of their expansions.
+    pub fn nonterm_expansion_counts(&self) -> impl Iterator<Item = (&Nt, usize)> {
+        self.nonterm_expansion_counts.iter()
+            .map(|(value, count)| (value, *count))
+    }
+
+    /// Non-terminal symbol values, at which expansions failed, along with the number of such failures.
+    pub fn nonterm_failure_counts(&self) -> impl Iterator<Item = (&Nt, usize)> {
+        self.nonterm_failure_counts.iter()
+            .map(|(value, count)| (value, *count))
+    }
+
+    /// Non-terminal symbol values, which were recovered according to the [`RecoveryPolicy`](enum.RecoveryPolicy.html),
+    /// along with the number of their recoveries.
+    pub fn nonterm_recovery_counts(&self) -> impl Iterator<Item = (&Nt, usize)> {
+        self.nonterm_recovery_counts.iter()
+            .map(|(value, count)| (value, *count))
+    }
+
+    /// Maps lengths of successfully expanded outputs to the number of outputs of that length.
+    #[must_use]
+    pub fn output_length_histogram(&self) -> &BTreeMap<usize, usize> {
+        &self.output_length_histogram
+    }
+
+    /// Checks which of the given `rules` have been selected during expansions so far.
+    ///
+    /// Usually called with [`Expander::rules()`](struct.Expander.html#method.rules)
+    /// after a number of expansions, in order to find rules which are never used.
+    #[must_use]
+    pub fn coverage_report<'r>(&self, rules: &'r [Rule<Nt, T>]) -> CoverageReport<'r, Nt, T> {
+        let (used_rules, unused_rules) = rules.iter()
+            .enumerate()
+            .map(|(rule_idx, rule)| (rule, self.rule_selection_count(rule_idx)))
+            .partition::<Vec<_>, _>(|(_, count)| *count > 0);
+
+        CoverageReport{
+            used_rules,
+            unused_rules: unused_rules.into_iter()
+                .map(|(rule, _)| rule)
+                .collect()
+        }
+    }
+}
+
+impl<Nt, T> StatsExpansionLogger<Nt, T>
+    where Nt: NonterminalValue
+{
+    /// Number of times non-terminal symbols with `nonterm_value` were expanded.
+    #[must_use]
+    pub fn nonterm_expansion_count(&self, nonterm_value: &Nt) -> usize {
+        find_count(&self.nonterm_expansion_counts, nonterm_value)
+    }
+
+    /// Number of expansions, which failed at non-terminal symbols with `nonterm_value`.
+    #[must_use]
+    pub fn nonterm_failure_count(&self, nonterm_value: &Nt) -> usize {
+        find_count(&self.nonterm_failure_counts, nonterm_value)
+    }
+
+    /// Number of times non-terminal symbols with `nonterm_value` were recovered
+    /// according to the [`RecoveryPolicy`](enum.RecoveryPolicy.html), since there was no rule to expand them.
+    #[must_use]
+    pub fn nonterm_recovery_count(&self, nonterm_value: &Nt) -> usize {
+        find_count(&self.nonterm_recovery_counts, nonterm_value)
+    }
+}
+
+//
+// CoverageReport<'r, Nt, T>
+//
+
+/// Lists used and unused rules of a grammar, as produced by
+/// [`StatsExpansionLogger::coverage_report()`](struct.StatsExpansionLogger.html#method.coverage_report).
+#[derive(Debug, Clone)]
+pub struct CoverageReport<'r, Nt, T> {
+    /// Rules selected at least once, along with the number of times each of them was selected.
+    pub used_rules:   Vec<(&'r Rule<Nt, T>, usize)>,
+    /// Rules never selected.
+    pub unused_rules: Vec<&'r Rule<Nt, T>>
+}
+
+impl<'r, Nt, T> CoverageReport<'r, Nt, T> {
+    /// Fraction of rules selected at least once, `1.0` for an empty set of rules.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn coverage(&self) -> f64 {
+        let rule_count = self.used_rules.len() + self.unused_rules.len();
+
+        if rule_count == 0 {
+            1.0
+        } else {
+            self.used_rules.len() as f64 / rule_count as f64
+        }
+    }
+
+    /// Returns `true` if every rule was selected at least once.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.unused_rules.is_empty()
+    }
+}
+
+//
+// Service
+//
+
+fn increment_count<K>(counts: &mut Vec<(K, usize)>, key: &K)
+    where K: Clone + PartialEq
+{
+    if let Some((_, count)) = counts.iter_mut().find(|(counted_key, _)| counted_key == key) {
+        *count += 1;
+    } else {
+        counts.push((key.clone(), 1));
+    }
+}
+
+fn find_count<K>(counts: &[(K, usize)], key: &K) -> usize
+    where K: PartialEq
+{
+    counts.iter()
+        .find(|(counted_key, _)| counted_key == key)
+        .map_or(0, |(_, count)| *count)
+}
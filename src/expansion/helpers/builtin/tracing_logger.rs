@@ -0,0 +1,108 @@
+use std::fmt::Debug;
+
+use tracing::{
+    Level,
+    Span
+};
+
+use crate::grammar::{
+    Symbol,
+    Rule
+};
+use super::ExpansionLogger;
+
+//
+// Interface types
+//
+
+//
+// TracingExpansionLogger: ExpansionLogger<Nt, T> + Default
+//
+
+/// Emits structured [`tracing`](https://docs.rs/tracing) events for every step of the expansion.
+///
+/// Each [`Expander::expand()`](struct.Expander.html#method.expand) call is wrapped in an `expand` span
+/// at `DEBUG` level. Individual non-terminal expansions are reported at `TRACE` level,
+/// successful completion at `DEBUG` level and failures at `WARN` level. All spans and events
+/// use `branchy` as their target.
+///
+/// Available with the `tracing` cargo feature.
+#[derive(Default)]
+pub struct TracingExpansionLogger {
+    span: Option<Span>
+}
+
+impl<Nt, T> ExpansionLogger<Nt, T> for TracingExpansionLogger
+    where Nt: Debug,
+          T:  Debug
+{
+    fn on_expansion_started(&mut self, input: &[Symbol<Nt, T>]) {
+        let span = tracing::span!(target: "branchy", Level::DEBUG, "expand", input_len = input.len());
+
+        tracing::event!(
+            target: "branchy", parent: &span, Level::TRACE,
+            ?input, "expansion started"
+        );
+
+        self.span = Some(span);
+    }
+
+    fn on_nonterm_expanded(&mut self, expanded_nonterm_value: &Nt, rule: &Rule<Nt, T>) {
+        let _entered = self.span.as_ref().map(Span::enter);
+
+        tracing::event!(
+            target: "branchy", Level::TRACE,
+            nonterm = ?expanded_nonterm_value, replacement = ?rule.replacement, "nonterminal expanded"
+        );
+    }
+
+    fn on_nonterm_expansion_failed(&mut self, expanded_nonterm_value: &Nt) {
+        let span = self.span.take();
+        let _entered = span.as_ref().map(Span::enter);
+
+        tracing::event!(
+            target: "branchy", Level::WARN,
+            nonterm = ?expanded_nonterm_value, "failed to expand nonterminal"
+        );
+    }
+
+    fn on_nonterm_expansion_recovered(&mut self, recovered_nonterm_value: &Nt, replacement: &[Symbol<Nt, T>]) {
+        let _entered = self.span.as_ref().map(Span::enter);
+
+        tracing::event!(
+            target: "branchy", Level::WARN,
+            nonterm = ?recovered_nonterm_value, ?replacement, "no rule to expand nonterminal, replaced"
+        );
+    }
+
+    fn on_input_fully_expanded(&mut self, expansion_result: &[T]) {
+        let span = self.span.take();
+        let _entered = span.as_ref().map(Span::enter);
+
+        tracing::event!(
+            target: "branchy", Level::DEBUG,
+            output_len = expansion_result.len(), output = ?expansion_result, "input fully expanded"
+        );
+    }
+
+    fn on_max_iterations_reached(&mut self, current_state: &[Symbol<Nt, T>], iterations: usize) {
+        let span = self.span.take();
+        let _entered = span.as_ref().map(Span::enter);
+
+        tracing::event!(
+            target: "branchy", Level::WARN,
+            iterations,
+            unexpanded = current_state.iter().filter(|symbol| symbol.is_nonterminal()).count(),
+            "maximum iterations reached"
+        );
+    }
+}
+
+impl TracingExpansionLogger {
+    #[must_use]
+    pub fn new() -> Self {
+        Self{
+            span: None
+        }
+    }
+}
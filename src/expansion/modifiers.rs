@@ -0,0 +1,267 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        Mutex
+    }
+};
+
+use crate::text::{
+    Language,
+    English,
+    capitalize
+};
+use crate::interning::{
+    SymbolId,
+    SymbolTable
+};
+
+//
+// Interface types
+//
+
+/// Function transforming the terminal values produced by a modified symbol,
+/// registered in a [`ModifierRegistry`](struct.ModifierRegistry.html).
+pub type ModifierFn<T> = dyn Fn(Vec<T>) -> Vec<T> + Send + Sync;
+
+//
+// ModifierRegistry<T>: Default
+//
+
+/// Maps names of [`Modifier`](struct.Modifier.html)s attached to symbols in rules to functions,
+/// which [`Expander`](struct.Expander.html) applies to the terminal values produced by these symbols.
+///
+/// Set via [`ExpanderBuilder::with_modifier_registry()`](struct.ExpanderBuilder.html#method.with_modifier_registry).
+/// Expansion fails with [`ErrorKind::UnknownModifier`](enum.ErrorKind.html#variant.UnknownModifier)
+/// if a rule refers to a modifier, which is not registered.
+///
+/// ```
+/// use branchy::{
+///     Symbol,
+///     Rule,
+///     ExpanderBuilder,
+///     ModifierRegistry
+/// };
+///
+/// let mut expander = ExpanderBuilder::new()
+///     .with_rule(
+///         Rule::new("greeting".to_string(), vec![Symbol::Nonterminal("animal".to_string())])
+///             .with_modifier(0, "a")
+///             .with_modifier(0, "capitalize")
+///     )
+///     .with_new_rule("animal".to_string(), vec![Symbol::Terminal("owl".to_string())])
+///     .with_modifier_registry(ModifierRegistry::with_builtin_modifiers())
+///     .build();
+///
+/// assert_eq!(expander.expand(vec![Symbol::Nonterminal("greeting".to_string())]).unwrap(), ["An", "owl"]);
+/// ```
+pub struct ModifierRegistry<T> {
+    modifiers: HashMap<String, Box<ModifierFn<T>>>
+}
+
+impl<T> Default for ModifierRegistry<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ModifierRegistry<T> {
+    /// Constructs an empty `ModifierRegistry`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self{
+            modifiers: HashMap::new()
+        }
+    }
+
+    /// Registers `modifier` under `name`, replacing any modifier previously registered under that name.
+    #[must_use]
+    pub fn with_modifier<S, F>(mut self, name: S, modifier: F) -> Self
+        where S: Into<String>,
+              F: Fn(Vec<T>) -> Vec<T> + Send + Sync + 'static
+    {
+        self.register(name, modifier);
+
+        self
+    }
+
+    /// Registers `modifier` under `name`, returning the modifier previously registered under that name, if any.
+    pub fn register<S, F>(&mut self, name: S, modifier: F) -> Option<Box<ModifierFn<T>>>
+        where S: Into<String>,
+              F: Fn(Vec<T>) -> Vec<T> + Send + Sync + 'static
+    {
+        self.modifiers.insert(name.into(), Box::new(modifier))
+    }
+
+    /// Returns `true` if a modifier is registered under `name`.
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        self.modifiers.contains_key(name)
+    }
+
+    /// Applies the modifier registered under `name` to `values`,
+    /// or returns `None` if there is no such modifier.
+    pub fn apply(&self, name: &str, values: Vec<T>) -> Option<Vec<T>> {
+        self.modifiers.get(name)
+            .map(|modifier| modifier(values))
+    }
+}
+
+impl ModifierRegistry<String> {
+    /// Constructs a `ModifierRegistry` with built-in modifiers for `String` terminal values:
+    ///
+    /// * `capitalize` capitalizes the first word,
+    /// * `capitalize_all` capitalizes every word,
+    /// * `upper` and `lower` convert all values to upper and lower case respectively,
+    /// * `s` replaces the last word with its English plural form,
+    /// * `a` inserts an English indefinite article ("a" or "an") before the first value,
+    /// * `in_quotes` encloses the values in double quotes.
+    #[must_use]
+    pub fn with_builtin_modifiers() -> Self {
+        Self::new()
+            .with_modifier("capitalize", |mut values: Vec<String>| {
+                for value in &mut values {
+                    if capitalize(value) {
+                        break;
+                    }
+                }
+
+                values
+            })
+            .with_modifier("capitalize_all", |values: Vec<String>| {
+                values.into_iter()
+                    .map(|value| {
+                        value.split(' ')
+                            .map(|word| {
+                                let mut word = word.to_string();
+
+                                capitalize(&mut word);
+
+                                word
+                            })
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    })
+                    .collect()
+            })
+            .with_modifier("upper", |values: Vec<String>| values.iter().map(|value| value.to_uppercase()).collect())
+            .with_modifier("lower", |values: Vec<String>| values.iter().map(|value| value.to_lowercase()).collect())
+            .with_modifier("s", |mut values: Vec<String>| {
+                if let Some(value) = values.iter_mut().rev().find(|value| !value.trim().is_empty()) {
+                    let value_without_trailing_whitespace = value.trim_end();
+
+                    let last_word_idx = value_without_trailing_whitespace
+                        .rfind(char::is_whitespace)
+                        .map_or(0, |whitespace_idx| whitespace_idx + 1);
+
+                    let plural = English.pluralize(&value_without_trailing_whitespace[last_word_idx..]);
+
+                    value.replace_range(last_word_idx..value_without_trailing_whitespace.len(), &plural);
+                }
+
+                values
+            })
+            .with_modifier("a", |mut values: Vec<String>| {
+                let next_word = values.iter()
+                    .find_map(|value| value.split_whitespace().next())
+                    .unwrap_or("");
+
+                let article = English.agree_article("a", next_word)
+                    .expect("\"a\" must be an English article");
+
+                values.insert(0, article);
+
+                values
+            })
+            .with_modifier("in_quotes", |mut values: Vec<String>| {
+                if let Some(first_value) = values.first_mut() {
+                    first_value.insert(0, '"');
+                }
+
+                if let Some(last_value) = values.last_mut() {
+                    last_value.push('"');
+                }
+
+                values
+            })
+    }
+
+    /// Adapts the modifiers of this registry to terminal values, which were compiled into
+    /// [`SymbolId`](struct.SymbolId.html)s using `symbol_table`, e.g. by
+    /// [`SymbolTable::compile_rules()`](struct.SymbolTable.html#method.compile_rules).
+    ///
+    /// Every adapted modifier resolves the values to `String`s, applies the original modifier and interns the results,
+    /// which is why the symbol table is shared with the returned registry. Unlike the rest of the expansion
+    /// of a compiled grammar, applying modifiers allocates, and every distinct modified value stays in the symbol table.
+    ///
+    /// ```
+    /// use std::{
+    ///     sync::{
+    ///         Arc,
+    ///         Mutex
+    ///     }
+    /// };
+    ///
+    /// use branchy::{
+    ///     Symbol,
+    ///     ExpanderBuilder,
+    ///     ModifierRegistry,
+    ///     SymbolTable,
+    ///     parse_grammar
+    /// };
+    ///
+    /// let rules = parse_grammar(r#"
+    ///     greeting -> <animal>.a.capitalize
+    ///     animal   -> "owl"
+    /// "#).unwrap();
+    ///
+    /// let symbol_table = Arc::new(Mutex::new(SymbolTable::new()));
+    ///
+    /// let compiled_rules = symbol_table.lock().unwrap().compile_rules(&rules);
+    ///
+    /// let mut expander = ExpanderBuilder::from(compiled_rules)
+    ///     .with_modifier_registry(ModifierRegistry::with_builtin_modifiers().compile(&symbol_table))
+    ///     .build();
+    ///
+    /// let greeting = symbol_table.lock().unwrap().get("greeting").unwrap();
+    ///
+    /// let output = expander.expand(vec![Symbol::Nonterminal(greeting)]).unwrap();
+    ///
+    /// assert_eq!(symbol_table.lock().unwrap().resolve_all(&output), ["An", "owl"]);
+    /// ```
+    ///
+    /// # Panics
+    /// The adapted modifiers panic if the mutex of `symbol_table` is poisoned
+    /// or if the values they are applied to do not belong to it.
+    /// They block while `symbol_table` is locked elsewhere, so locking it on the expanding thread deadlocks.
+    #[must_use]
+    pub fn compile(self, symbol_table: &Arc<Mutex<SymbolTable>>) -> ModifierRegistry<SymbolId> {
+        ModifierRegistry{
+            modifiers: self.modifiers.into_iter()
+                .map(|(name, modifier)| (name, compile_modifier(modifier, Arc::clone(symbol_table))))
+                .collect()
+        }
+    }
+}
+
+//
+// Service
+//
+
+fn compile_modifier(modifier: Box<ModifierFn<String>>, symbol_table: Arc<Mutex<SymbolTable>>) -> Box<ModifierFn<SymbolId>> {
+    Box::new(move |ids: Vec<SymbolId>| {
+        let values = {
+            let symbol_table = symbol_table.lock().expect("symbol table mutex must not be poisoned");
+
+            ids.iter()
+                .map(|&id| symbol_table.resolve(id).to_string())
+                .collect()
+        };
+
+        let mut symbol_table = symbol_table.lock().expect("symbol table mutex must not be poisoned");
+
+        modifier(values).iter()
+            .map(|value| symbol_table.intern(value))
+            .collect()
+    })
+}
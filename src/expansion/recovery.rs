@@ -0,0 +1,100 @@
+use crate::grammar::Symbol;
+
+//
+// Interface types
+//
+
+/// Function producing a replacement for a non-terminal symbol without matching rules,
+/// used by [`RecoveryPolicy::Fallback`](enum.RecoveryPolicy.html#variant.Fallback).
+pub type FallbackFn<Nt, T> = dyn Fn(&Nt) -> Vec<Symbol<Nt, T>> + Send + Sync;
+
+//
+// RecoveryPolicy<Nt, T>: Default
+//
+
+/// Describes what [`Expander`](struct.Expander.html) does when there is no rule to expand a non-terminal symbol.
+///
+/// Set via [`ExpanderBuilder::with_recovery_policy()`](struct.ExpanderBuilder.html#method.with_recovery_policy).
+/// Every recovered non-terminal symbol is reported as an [`ExpansionWarning`](struct.ExpansionWarning.html)
+/// by [`Expander::expand_with_warnings()`](struct.Expander.html#method.expand_with_warnings).
+#[derive(Default)]
+pub enum RecoveryPolicy<Nt, T> {
+    /// Fail the whole expansion with [`ErrorKind::NontermExpansionFailed`](enum.ErrorKind.html#variant.NontermExpansionFailed).
+    /// This is the default.
+    #[default]
+    Fail,
+    /// Replace the non-terminal symbol with the given terminal value.
+    Placeholder(T),
+    /// Remove the non-terminal symbol, as if it was expanded using an empty replacement.
+    Drop,
+    /// Replace the non-terminal symbol with the sequence of symbols returned by the function.
+    /// Non-terminal symbols in that sequence are expanded further.
+    Fallback(Box<FallbackFn<Nt, T>>)
+}
+
+impl<Nt, T> RecoveryPolicy<Nt, T>
+    where T: Clone
+{
+    /// Constructs [`Fallback`](#variant.Fallback) policy from a function or closure.
+    #[must_use]
+    pub fn fallback<F>(fallback: F) -> Self
+        where F: Fn(&Nt) -> Vec<Symbol<Nt, T>> + Send + Sync + 'static
+    {
+        Self::Fallback(Box::new(fallback))
+    }
+
+    //
+    // Friend interface
+    //
+
+    /// Returns the replacement for a non-terminal symbol with `nonterm_value`, which cannot be expanded,
+    /// or `None` if the expansion must fail.
+    pub(super) fn recover(&self, nonterm_value: &Nt) -> Option<Vec<Symbol<Nt, T>>> {
+        match self {
+            Self::Fail               => None,
+            Self::Placeholder(value) => Some(vec![Symbol::Terminal(value.clone())]),
+            Self::Drop               => Some(Vec::new()),
+            Self::Fallback(fallback) => Some(fallback(nonterm_value))
+        }
+    }
+}
+
+//
+// ExpansionWarning<Nt>: Debug + Clone + PartialEq
+//
+
+/// Describes a non-terminal symbol, which could not be expanded using the rules
+/// and was replaced according to the [`RecoveryPolicy`](enum.RecoveryPolicy.html).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpansionWarning<Nt> {
+    /// Value of the recovered non-terminal symbol.
+    pub nonterm_value: Nt,
+    /// Index of the recovered non-terminal symbol in the sequence of symbols being expanded
+    /// at the moment of recovery, which equals the number of terminal values produced before it.
+    pub position:      usize,
+    /// Values of the non-terminal symbols, from which the recovered non-terminal symbol was derived,
+    /// starting with the outermost one.
+    pub ancestors:     Vec<Nt>
+}
+
+//
+// Recovered<Nt, T>
+//
+
+/// Result of a successful [`Expander::expand_with_warnings()`](struct.Expander.html#method.expand_with_warnings) call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recovered<Nt, T> {
+    /// The resulting sequence of terminal values.
+    pub output:   Vec<T>,
+    /// Non-terminal symbols, which were replaced according to the [`RecoveryPolicy`](enum.RecoveryPolicy.html)
+    /// in order to produce `output`, in the order of their expansion.
+    pub warnings: Vec<ExpansionWarning<Nt>>
+}
+
+impl<Nt, T> Recovered<Nt, T> {
+    /// Returns `true` if no recovery was needed.
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.warnings.is_empty()
+    }
+}
@@ -26,6 +26,12 @@ fn expand_input_all_terminal() {
 
     assert!(mock_rule_selector_state.borrow().select_rule_calls.is_empty());
 
+    assert_eq!(
+        mock_logger_state.borrow().on_expansion_started_calls,
+        vec![
+            vec![Symbol::Terminal(0), Symbol::Terminal(1), Symbol::Terminal(2)]
+        ]
+    );
     assert!(mock_logger_state.borrow().on_nonterm_expanded_calls.is_empty());
     assert!(mock_logger_state.borrow().on_nonterm_expansion_failed_calls.is_empty());
     assert_eq!(mock_logger_state.borrow().on_input_fully_expanded_calls, vec![expansion_result]);
@@ -155,6 +161,37 @@ fn expand_input_long_nonterm_chain() {
     assert!(mock_logger_state.borrow().on_max_iterations_reached_calls.is_empty());
 }
 
+#[test]
+fn expand_input_context() {
+    let input = vec![Symbol::Nonterminal("greeting"), Symbol::Terminal("!"), Symbol::Nonterminal("name")];
+
+    let rules = vec![
+        Rule::new("greeting", vec![Symbol::Terminal("Hello"), Symbol::Nonterminal("addressee")]),
+        Rule::new("addressee", vec![Symbol::Nonterminal("name"), Symbol::Terminal("and"), Symbol::Nonterminal("name")]),
+        Rule::new("name", vec![Symbol::Terminal("Susan")])
+    ];
+
+    let (expansion_result, mock_rule_selector_state, _) = expand_input_with_mocks(
+        input,
+        &rules,
+        UniformRandomRuleSelector::new(),
+        6
+    );
+
+    assert_eq!(expansion_result.unwrap(), vec!["Hello", "Susan", "and", "Susan", "!", "Susan"]);
+
+    assert_eq!(
+        mock_rule_selector_state.borrow().select_rule_contexts,
+        vec![
+            (vec![], vec![], 0),
+            (vec!["greeting"], vec!["Hello"], 1),
+            (vec!["greeting", "addressee"], vec!["Hello"], 2),
+            (vec!["greeting", "addressee"], vec!["Hello", "Susan", "and"], 3),
+            (vec![], vec!["Hello", "Susan", "and", "Susan", "!"], 4)
+        ]
+    );
+}
+
 #[test]
 fn expand_input_err_nonterm_expansion_failed() {
     let input: Vec<Symbol<_, i32>> = vec![Symbol::Nonterminal(0)];
@@ -166,8 +203,9 @@ fn expand_input_err_nonterm_expansion_failed() {
         1
     );
 
-    if let Err(Error{kind: ErrorKind::NontermExpansionFailed(0), state}) = expansion_result {
+    if let Err(Error{kind: ErrorKind::NontermExpansionFailed(0), state, ancestors}) = expansion_result {
         assert_eq!(state, vec![Symbol::Nonterminal(0)]);
+        assert!(ancestors.is_empty());
 
         assert_eq!(
             mock_rule_selector_state.borrow().select_rule_calls,
@@ -202,8 +240,9 @@ fn expand_input_err_max_iterations_reached() {
         MAX_ITERATIONS
     );
 
-    if let Err(Error{kind: ErrorKind::MaxIterationsReached(MAX_ITERATIONS), state}) = expansion_result {
+    if let Err(Error{kind: ErrorKind::MaxIterationsReached(MAX_ITERATIONS), state, ancestors}) = expansion_result {
         assert_eq!(state, vec![Symbol::Nonterminal(0)]);
+        assert_eq!(ancestors, vec![0; MAX_ITERATIONS]);
 
         assert_eq!(
             mock_rule_selector_state.borrow().select_rule_calls,
@@ -231,10 +270,122 @@ fn expand_input_err_max_iterations_reached() {
     }
 }
 
+#[test]
+fn expand_input_err_ancestors() {
+    let input = vec![Symbol::Terminal("start"), Symbol::Nonterminal("sentence")];
+
+    let rules = vec![
+        Rule::new("sentence", vec![Symbol::Nonterminal("noun_phrase"), Symbol::Terminal("sleeps")]),
+        Rule::new("noun_phrase", vec![Symbol::Terminal("a"), Symbol::Nonterminal("adjective"), Symbol::Terminal("cat")])
+    ];
+
+    let error = expand_input(input, &rules, &mut AlwaysFirstRuleSelector::new(), &mut NullExpansionLogger, 16)
+        .unwrap_err();
+
+    assert_eq!(error.position(), Some(2));
+    assert_eq!(error.ancestors, vec!["sentence", "noun_phrase"]);
+    assert_eq!(
+        error.display_nonterms().to_string(),
+        "no rule to expand nonterminal symbol \"adjective\" at position 2, derived from \"sentence\" -> \"noun_phrase\""
+    );
+    assert_eq!(
+        error.to_string(),
+        "no rule to expand nonterminal symbol at position 2, derived from 2 nonterminal symbols"
+    );
+}
+
+#[test]
+fn expand_input_err_ancestors_after_finished_expansions() {
+    let input = vec![Symbol::Nonterminal("pair")];
+
+    let rules = vec![
+        Rule::new("pair", vec![Symbol::Nonterminal("left"), Symbol::Nonterminal("right")]),
+        Rule::new("left", vec![Symbol::Terminal("x")])
+    ];
+
+    let error = expand_input(input, &rules, &mut AlwaysFirstRuleSelector::new(), &mut NullExpansionLogger, 16)
+        .unwrap_err();
+
+    assert_eq!(error.position(), Some(1));
+    assert_eq!(error.ancestors, vec!["pair"]);
+}
+
+#[test]
+fn expand_input_err_max_iterations_reached_display() {
+    let input = vec![Symbol::Nonterminal("list")];
+
+    let rules = vec![
+        Rule::new("list", vec![Symbol::Terminal("item"), Symbol::Nonterminal("list")])
+    ];
+
+    let error = expand_input(input, &rules, &mut AlwaysFirstRuleSelector::new(), &mut NullExpansionLogger, 20)
+        .unwrap_err();
+
+    assert_eq!(error.ancestors.len(), 20);
+    assert_eq!(
+        error.display_nonterms().to_string(),
+        "reached the maximum 20 iterations with 1 nonterminal symbols still unexpanded, \
+         the first one being \"list\" at position 20, derived from \"list\" -> ... (12 more) -> \
+         \"list\" -> \"list\" -> \"list\" -> \"list\" -> \"list\" -> \"list\" -> \"list\""
+    );
+}
+
+#[test]
+fn expand_input_err_debug_and_display_without_nonterm_debug() {
+    #[derive(Clone, PartialEq)]
+    struct Nonterm(u8);
+
+    let input: Vec<Symbol<_, &str>> = vec![Symbol::Terminal("start"), Symbol::Nonterminal(Nonterm(0))];
+
+    let rules = vec![
+        Rule::new(Nonterm(0), vec![Symbol::Nonterminal(Nonterm(1))])
+    ];
+
+    let error = expand_input(input, &rules, &mut AlwaysFirstRuleSelector::new(), &mut NullExpansionLogger, 16)
+        .unwrap_err();
+
+    assert_eq!(
+        format!("{:?}", error),
+        "Error { kind: NontermExpansionFailed(_), position: Some(1), ancestor_count: 1, .. }"
+    );
+    assert_eq!(error.to_string(), "no rule to expand nonterminal symbol at position 1, derived from 1 nonterminal symbols");
+}
+
 //
 // Service
 //
 
+fn expand_input<Nt, T, RS, EL>(
+    input:          Vec<Symbol<Nt, T>>,
+    rules:          &[Rule<Nt, T>],
+    rule_selector:  &mut RS,
+    logger:         &mut EL,
+    max_iterations: usize
+) -> Result<Nt, T>
+    where Nt: NonterminalValue,
+          T:  TerminalValue,
+          RS: RuleSelector<Nt, T>,
+          EL: ExpansionLogger<Nt, T>
+{
+    let mut output = Vec::new();
+
+    expand_input_recovering(
+        &input,
+        rules,
+        &IndexedGrammar::new(rules).into_index(),
+        rule_selector,
+        logger,
+        max_iterations,
+        &RecoveryPolicy::Fail,
+        None,
+        &mut ExpansionStack::new(),
+        &mut output,
+        &mut Vec::new()
+    )?;
+
+    Ok(output)
+}
+
 fn expand_input_with_mocks<Nt, T, RS>(
     input:               Vec<Symbol<Nt, T>>,
     rules:               &[Rule<Nt, T>],
@@ -250,7 +401,7 @@ fn expand_input_with_mocks<Nt, T, RS>(
     let expansion_result = expand_input(
         input,
         rules,
-        &MockRuleSelector::new(inner_rule_selector, Rc::clone(&mock_rule_selector_state)),
+        &mut MockRuleSelector::new(inner_rule_selector, Rc::clone(&mock_rule_selector_state)),
         &mut MockLogger::new(Rc::clone(&mock_logger_state)),
         max_iterations
     );
@@ -276,7 +427,11 @@ fn make_helper_mock_states<Nt, T>() -> (Rc<RefCell<MockRuleSelectorState<Nt, T>>
 struct AlwaysFirstRuleSelector;
 
 impl<Nt, T> RuleSelector<Nt, T> for AlwaysFirstRuleSelector {
-    fn select_matching_rule<'a>(&self, matching_rules: &[&'a Rule<Nt, T>]) -> Option<&'a Rule<Nt, T>> {
+    fn select_matching_rule<'a>(
+        &mut self,
+        matching_rules: &[&'a Rule<Nt, T>],
+        _context:       &ExpansionContext<Nt, T>
+    ) -> Option<&'a Rule<Nt, T>> {
         if matching_rules.is_empty() {
             None
         } else {
@@ -315,11 +470,18 @@ impl<Nt, T, RS> RuleSelector<Nt, T> for MockRuleSelector<Nt, T, RS>
           T:  TerminalValue,
           RS: RuleSelector<Nt, T>
 {
-    fn select_rule<'a>(&self, all_rules: &'a [Rule<Nt, T>], nonterm_value: &Nt) -> Option<&'a Rule<Nt,T>>
+    fn select_rule<'a>(&mut self, all_rules: &'a [Rule<Nt, T>], context: &ExpansionContext<Nt, T>) -> Option<&'a Rule<Nt,T>>
     {
-        self.state.borrow_mut().select_rule_calls.push((Vec::from(all_rules), nonterm_value.clone()));
+        let mut state = self.state.borrow_mut();
+
+        state.select_rule_calls.push((Vec::from(all_rules), context.nonterm_value().clone()));
+        state.select_rule_contexts.push((
+            Vec::from(context.ancestors()),
+            context.partial_output().cloned().collect(),
+            context.iteration()
+        ));
 
-        self.inner.select_rule(all_rules, nonterm_value)
+        self.inner.select_rule(all_rules, context)
     }
 
     fn find_matching_rules<'a>(&self, _all_rules: &'a [Rule<Nt, T>], _nonterm_value: &Nt) -> Vec<&'a Rule<Nt, T>>
@@ -327,7 +489,11 @@ impl<Nt, T, RS> RuleSelector<Nt, T> for MockRuleSelector<Nt, T, RS>
         panic!("did not expect find_matching_rules() to be called directly")
     }
 
-    fn select_matching_rule<'a>(&self, _matching_rules: &[&'a Rule<Nt, T>]) -> Option<&'a Rule<Nt, T>> {
+    fn select_matching_rule<'a>(
+        &mut self,
+        _matching_rules: &[&'a Rule<Nt, T>],
+        _context:        &ExpansionContext<Nt, T>
+    ) -> Option<&'a Rule<Nt, T>> {
         panic!("did not expect select_matching_rule() to be called directly")
     }
 }
@@ -348,7 +514,8 @@ impl<Nt, T, RS> MockRuleSelector<Nt, T, RS>
 
 struct MockRuleSelectorState<Nt, T>
 {
-    select_rule_calls: Vec<(Vec<Rule<Nt, T>>, Nt)>
+    select_rule_calls:    Vec<(Vec<Rule<Nt, T>>, Nt)>,
+    select_rule_contexts: Vec<(Vec<Nt>, Vec<T>, usize)>
 }
 
 impl<Nt, T> Default for MockRuleSelectorState<Nt, T> {
@@ -360,7 +527,8 @@ impl<Nt, T> Default for MockRuleSelectorState<Nt, T> {
 impl<Nt, T> MockRuleSelectorState<Nt, T> {
     fn new() -> Self {
         Self{
-            select_rule_calls: Vec::new()
+            select_rule_calls:    Vec::new(),
+            select_rule_contexts: Vec::new()
         }
     }
 }
@@ -378,6 +546,10 @@ impl<Nt, T> ExpansionLogger<Nt, T> for MockLogger<Nt, T>
     where Nt: NonterminalValue,
           T:  TerminalValue
 {
+    fn on_expansion_started(&mut self, input: &[Symbol<Nt, T>]) {
+        self.state.borrow_mut().on_expansion_started_calls.push(Vec::from(input));
+    }
+
     fn on_nonterm_expanded(&mut self, expanded_nonterm_value: &Nt, rule: &Rule<Nt, T>) {
         self.state.borrow_mut().on_nonterm_expanded_calls.push((expanded_nonterm_value.clone(), rule.clone()));
     }
@@ -407,6 +579,7 @@ impl<Nt, T> MockLogger<Nt, T> {
 
 struct MockLoggerState<Nt, T>
 {
+    on_expansion_started_calls:        Vec<Vec<Symbol<Nt, T>>>,
     on_nonterm_expanded_calls:         Vec<(Nt, Rule<Nt, T>)>,
     on_nonterm_expansion_failed_calls: Vec<Nt>,
     on_input_fully_expanded_calls:     Vec<Vec<T>>,
@@ -422,6 +595,7 @@ impl<Nt, T> Default for MockLoggerState<Nt, T> {
 impl<Nt, T> MockLoggerState<Nt, T> {
     fn new() -> Self {
         Self{
+            on_expansion_started_calls:        Vec::new(),
             on_nonterm_expanded_calls:         Vec::new(),
             on_nonterm_expansion_failed_calls: Vec::new(),
             on_input_fully_expanded_calls:     Vec::new(),
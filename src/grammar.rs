@@ -1,3 +1,16 @@
+pub(crate) mod indexed;
+
+use std::fmt::{
+    self,
+    Display
+};
+
+//
+// Constants
+//
+
+pub(crate) const DEFAULT_RULE_WEIGHT: f64 = 1.0;
+
 //
 // Interface traits
 //
@@ -39,7 +52,7 @@ impl<T> TerminalValue for T
 //
 
 //
-// enum Symbol<Nt, T>: Debug + Clone + Copy + PartialEq
+// enum Symbol<Nt, T>: Debug + Clone + Copy + PartialEq + Display
 //
 
 /// Used to describe non-terminal and terminal symbols in [`Rule`](struct.Rule.html)s
@@ -114,10 +127,103 @@ impl<Nt, T> Symbol<Nt, T> {
             panic!("{}", message);
         }
     }
+
+    /// Converts from `&Symbol<Nt, T>` to `Symbol<&Nt, &T>`.
+    pub fn as_ref(&self) -> Symbol<&Nt, &T> {
+        match self {
+            Self::Nonterminal(value) => Symbol::Nonterminal(value),
+            Self::Terminal(value)    => Symbol::Terminal(value)
+        }
+    }
+
+    /// Converts the symbol's value using `map_nonterm` if it is [`Nonterminal`](enum.Symbol.html#variant.Nonterminal)
+    /// or using `map_term` if it is [`Terminal`](enum.Symbol.html#variant.Terminal).
+    ///
+    /// ```
+    /// use branchy::Symbol;
+    ///
+    /// let symbol: Symbol<&str, &str> = Symbol::Nonterminal("name");
+    ///
+    /// assert_eq!(symbol.map(str::len, str::to_uppercase), Symbol::Nonterminal(4));
+    /// ```
+    pub fn map<Nt2, T2, FN, FT>(self, map_nonterm: FN, map_term: FT) -> Symbol<Nt2, T2>
+        where FN: FnOnce(Nt) -> Nt2,
+              FT: FnOnce(T) -> T2
+    {
+        match self {
+            Self::Nonterminal(value) => Symbol::Nonterminal(map_nonterm(value)),
+            Self::Terminal(value)    => Symbol::Terminal(map_term(value))
+        }
+    }
+
+    /// Converts the value of a [`Nonterminal`](enum.Symbol.html#variant.Nonterminal) symbol using `map_nonterm`,
+    /// leaving a [`Terminal`](enum.Symbol.html#variant.Terminal) symbol unchanged.
+    pub fn map_nonterm<Nt2, F>(self, map_nonterm: F) -> Symbol<Nt2, T>
+        where F: FnOnce(Nt) -> Nt2
+    {
+        self.map(map_nonterm, |value| value)
+    }
+
+    /// Converts the value of a [`Terminal`](enum.Symbol.html#variant.Terminal) symbol using `map_term`,
+    /// leaving a [`Nonterminal`](enum.Symbol.html#variant.Nonterminal) symbol unchanged.
+    pub fn map_term<T2, F>(self, map_term: F) -> Symbol<Nt, T2>
+        where F: FnOnce(T) -> T2
+    {
+        self.map(|value| value, map_term)
+    }
+
+    /// Fallible version of [`map()`](#method.map), returning the first error produced by either function.
+    ///
+    /// # Errors
+    /// Returns the error produced by `map_nonterm` or `map_term`.
+    pub fn try_map<Nt2, T2, E, FN, FT>(self, map_nonterm: FN, map_term: FT) -> Result<Symbol<Nt2, T2>, E>
+        where FN: FnOnce(Nt) -> Result<Nt2, E>,
+              FT: FnOnce(T) -> Result<T2, E>
+    {
+        match self {
+            Self::Nonterminal(value) => map_nonterm(value).map(Symbol::Nonterminal),
+            Self::Terminal(value)    => map_term(value).map(Symbol::Terminal)
+        }
+    }
+
+    /// Fallible version of [`map_nonterm()`](#method.map_nonterm).
+    ///
+    /// # Errors
+    /// Returns the error produced by `map_nonterm`.
+    pub fn try_map_nonterm<Nt2, E, F>(self, map_nonterm: F) -> Result<Symbol<Nt2, T>, E>
+        where F: FnOnce(Nt) -> Result<Nt2, E>
+    {
+        self.try_map(map_nonterm, Ok)
+    }
+
+    /// Fallible version of [`map_term()`](#method.map_term).
+    ///
+    /// # Errors
+    /// Returns the error produced by `map_term`.
+    pub fn try_map_term<T2, E, F>(self, map_term: F) -> Result<Symbol<Nt, T2>, E>
+        where F: FnOnce(T) -> Result<T2, E>
+    {
+        self.try_map(Ok, map_term)
+    }
+}
+
+/// Formats non-terminal symbols as `<value>` and terminal symbols as `"value"`,
+/// escaping `>`, `"` and `\\` in values with a backslash where needed
+/// and control characters as `\n`, `\r`, `\t` or `\u{7f}`.
+impl<Nt, T> Display for Symbol<Nt, T>
+    where Nt: Display,
+          T:  Display
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Nonterminal(value) => write!(f, "<{}>", escape(&value.to_string(), '>')),
+            Self::Terminal(value)    => write!(f, "\"{}\"", escape(&value.to_string(), '"'))
+        }
+    }
 }
 
 //
-// struct Rule<Nt, T>: Debug + Clone + PartialEq
+// struct Rule<Nt, T>: Debug + Clone + PartialEq + Display
 //
 
 /// Describes a rule (or production) of a context-free grammar.
@@ -127,14 +233,228 @@ pub struct Rule<Nt, T> {
     pub pattern:     Nt,
     /// Right-hand side of the rule (any sequence of symbols,
     /// with which to replace the encountered `pattern`).
-    pub replacement: Vec<Symbol<Nt, T>>
+    pub replacement: Vec<Symbol<Nt, T>>,
+    /// Relative weight of the rule among the rules with the same `pattern`, `1.0` by default.
+    /// Expected to be non-negative and finite.
+    ///
+    /// Taken into account by [`WeightedRandomRuleSelector`](struct.WeightedRandomRuleSelector.html),
+    /// ignored by rule selectors which choose between matching rules uniformly.
+    pub weight:      f64,
+    /// [`Modifier`](struct.Modifier.html)s attached to symbols of `replacement`, empty by default.
+    pub modifiers:   Vec<Modifier>
 }
 
 impl<Nt, T> Rule<Nt, T> {
     pub fn new(pattern: Nt, replacement: Vec<Symbol<Nt, T>>) -> Self {
         Self{
             pattern,
-            replacement
+            replacement,
+            weight:    DEFAULT_RULE_WEIGHT,
+            modifiers: Vec::new()
+        }
+    }
+
+    #[must_use]
+    pub fn with_weight(self, weight: f64) -> Self {
+        Self{
+            weight,
+            ..self
+        }
+    }
+
+    /// Attaches the modifier named `name` to the symbol at `symbol_idx` in `replacement`,
+    /// after any modifiers already attached to it.
+    #[must_use]
+    pub fn with_modifier<S>(mut self, symbol_idx: usize, name: S) -> Self
+        where S: Into<String>
+    {
+        self.modifiers.push(Modifier{
+            symbol_idx,
+            name: name.into()
+        });
+
+        self
+    }
+
+    /// Returns names of the modifiers attached to the symbol at `symbol_idx` in `replacement`,
+    /// in the order of their application.
+    pub fn symbol_modifiers(&self, symbol_idx: usize) -> impl Iterator<Item = &str> {
+        self.modifiers.iter()
+            .filter(move |modifier| modifier.symbol_idx == symbol_idx)
+            .map(|modifier| modifier.name.as_str())
+    }
+
+    /// Converts values of all non-terminal symbols (including `pattern`) using `map_nonterm`
+    /// and values of all terminal symbols using `map_term`, keeping weight and modifiers.
+    ///
+    /// ```
+    /// use branchy::{
+    ///     Symbol,
+    ///     Rule
+    /// };
+    ///
+    /// #[derive(Debug, Clone, Copy, PartialEq)]
+    /// enum Nonterm {
+    ///     Greeting,
+    ///     Name
+    /// }
+    ///
+    /// let rule = Rule::new("greeting", vec![Symbol::Terminal("hello"), Symbol::Nonterminal("name")]);
+    ///
+    /// let mapped_rule = rule.map(
+    ///     |value| if value == "greeting" { Nonterm::Greeting } else { Nonterm::Name },
+    ///     String::from
+    /// );
+    ///
+    /// assert_eq!(
+    ///     mapped_rule,
+    ///     Rule::new(Nonterm::Greeting, vec![Symbol::Terminal("hello".to_string()), Symbol::Nonterminal(Nonterm::Name)])
+    /// );
+    /// ```
+    pub fn map<Nt2, T2, FN, FT>(self, mut map_nonterm: FN, mut map_term: FT) -> Rule<Nt2, T2>
+        where FN: FnMut(Nt) -> Nt2,
+              FT: FnMut(T) -> T2
+    {
+        Rule{
+            pattern:     map_nonterm(self.pattern),
+            replacement: self.replacement.into_iter()
+                .map(|symbol| symbol.map(&mut map_nonterm, &mut map_term))
+                .collect(),
+            weight:      self.weight,
+            modifiers:   self.modifiers
+        }
+    }
+
+    /// Fallible version of [`map()`](#method.map), stopping at the first error produced by either function.
+    ///
+    /// # Errors
+    /// Returns the first error produced by `map_nonterm` or `map_term`.
+    pub fn try_map<Nt2, T2, E, FN, FT>(self, mut map_nonterm: FN, mut map_term: FT) -> Result<Rule<Nt2, T2>, E>
+        where FN: FnMut(Nt) -> Result<Nt2, E>,
+              FT: FnMut(T) -> Result<T2, E>
+    {
+        Ok(Rule{
+            pattern:     map_nonterm(self.pattern)?,
+            replacement: self.replacement.into_iter()
+                .map(|symbol| symbol.try_map(&mut map_nonterm, &mut map_term))
+                .collect::<Result<_, _>>()?,
+            weight:      self.weight,
+            modifiers:   self.modifiers
+        })
+    }
+}
+
+/// Formats rules in conventional grammar notation, e.g. `x -> <y> "z"`, with `ε` standing for an empty replacement
+/// and a non-default weight following the replacement in square brackets, e.g. `x -> "z" [0.5]`.
+/// Modifiers follow the symbols they are attached to, each preceded by a dot, e.g. `x -> <y>.s.capitalize`.
+///
+/// The pattern is enclosed in angle brackets only if it would otherwise be ambiguous,
+/// so that the output can be parsed back by [`parse_grammar()`](fn.parse_grammar.html).
+impl<Nt, T> Display for Rule<Nt, T>
+    where Nt: Display,
+          T:  Display
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_pattern(f, &self.pattern)?;
+        write!(f, " -> ")?;
+
+        write_replacement(f, self)
+    }
+}
+
+//
+// struct Modifier: Debug + Clone + PartialEq + Eq
+//
+
+/// Attaches a named modifier to a symbol in a [`Rule`](struct.Rule.html)'s replacement, like `.s` in Tracery's `#animal.s#`.
+///
+/// Once a non-terminal symbol with modifiers is fully expanded, [`Expander`](struct.Expander.html) replaces
+/// the terminal values produced from it with the result of applying the modifiers, looked up by `name`
+/// in its [`ModifierRegistry`](struct.ModifierRegistry.html). Modifiers attached to a terminal symbol
+/// are applied to its value as soon as the rule is applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Modifier {
+    /// Index of the modified symbol in the replacement.
+    pub symbol_idx: usize,
+    /// Name, under which the modifier is registered.
+    pub name:       String
+}
+
+//
+// Friend
+//
+
+pub(crate) fn write_pattern<Nt>(f: &mut fmt::Formatter<'_>, pattern: &Nt) -> fmt::Result
+    where Nt: Display
+{
+    let pattern = pattern.to_string();
+
+    let is_plain = !pattern.is_empty() && !pattern.contains("->") && !pattern.chars()
+        .any(|c| c.is_whitespace() || c.is_control() || "<>\"|#[]\\ε".contains(c));
+
+    if is_plain {
+        write!(f, "{}", pattern)
+    } else {
+        write!(f, "<{}>", escape(&pattern, '>'))
+    }
+}
+
+pub(crate) fn write_replacement<Nt, T>(f: &mut fmt::Formatter<'_>, rule: &Rule<Nt, T>) -> fmt::Result
+    where Nt: Display,
+          T:  Display
+{
+    if rule.replacement.is_empty() {
+        write!(f, "ε")?;
+    }
+
+    for (symbol_idx, symbol) in rule.replacement.iter().enumerate() {
+        if symbol_idx > 0 {
+            write!(f, " ")?;
+        }
+
+        write!(f, "{}", symbol)?;
+
+        for modifier_name in rule.symbol_modifiers(symbol_idx) {
+            let is_plain = !modifier_name.is_empty() && modifier_name.chars()
+                .all(|c| c.is_alphanumeric() || c == '_');
+
+            if is_plain {
+                write!(f, ".{}", modifier_name)?;
+            } else {
+                write!(f, ".\"{}\"", escape(modifier_name, '"'))?;
+            }
+        }
+    }
+
+    if rule.weight.is_nan() || (rule.weight - DEFAULT_RULE_WEIGHT).abs() > f64::EPSILON {
+        write!(f, " [{}]", rule.weight)?;
+    }
+
+    Ok(())
+}
+
+//
+// Service
+//
+
+fn escape(value: &str, delimiter: char) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            _ if c.is_control() => escaped.push_str(&format!("\\u{{{:x}}}", u32::from(c))),
+            _ => {
+                if c == delimiter || c == '\\' {
+                    escaped.push('\\');
+                }
+
+                escaped.push(c);
+            }
         }
     }
+
+    escaped
 }
@@ -0,0 +1,371 @@
+use std::{
+    ops::Deref,
+    sync::Arc
+};
+
+use super::{
+    NonterminalValue,
+    Symbol,
+    Rule
+};
+
+//
+// Friend types
+//
+
+//
+// IndexedGrammar<'r, Nt, T>
+//
+
+/// Borrowed view of a set of rules, in which every distinct non-terminal symbol value is assigned an index.
+///
+/// Since non-terminal symbol values are only required to be comparable by `==`, analyses of a whole grammar
+/// map them to indices once and then work with indices only. Looking up an index takes time linear
+/// in the number of distinct non-terminal symbols, so an `IndexedGrammar` should be constructed once
+/// and shared by everything analyzing the same rules. Its [`GrammarIndex`] does not borrow the rules
+/// and can be kept to quickly reconstruct the view later (see [`with_index()`](#method.with_index)).
+pub(crate) struct IndexedGrammar<'r, Nt, T> {
+    pub rules:    &'r [Rule<Nt, T>],
+    pub nonterms: Vec<&'r Nt>,
+    index:        Arc<GrammarIndex>
+}
+
+impl<'r, Nt, T> IndexedGrammar<'r, Nt, T>
+    where Nt: NonterminalValue
+{
+    pub fn new(rules: &'r [Rule<Nt, T>]) -> Self {
+        let mut nonterms:            Vec<&'r Nt>                = Vec::new();
+        let mut nonterm_occurrences: Vec<(usize, Option<usize>)> = Vec::new();
+
+        // Rules for the same non-terminal symbol usually follow one another, in which case the pattern
+        // is the same as the previous one and need not be looked up among all non-terminal symbols.
+        let mut rule_patterns: Vec<usize> = Vec::with_capacity(rules.len());
+
+        for (rule_idx, rule) in rules.iter().enumerate() {
+            let pattern = if rule_idx > 0 && rules[rule_idx - 1].pattern == rule.pattern {
+                rule_patterns[rule_idx - 1]
+            } else {
+                intern(&mut nonterms, &mut nonterm_occurrences, &rule.pattern, (rule_idx, None))
+            };
+
+            rule_patterns.push(pattern);
+        }
+
+        let rule_symbols: Vec<Vec<_>> = rules.iter()
+            .enumerate()
+            .map(|(rule_idx, rule)| {
+                rule.replacement.iter()
+                    .enumerate()
+                    .map(|(symbol_idx, symbol)| match symbol {
+                        Symbol::Nonterminal(value) => {
+                            Some(intern(&mut nonterms, &mut nonterm_occurrences, value, (rule_idx, Some(symbol_idx))))
+                        },
+                        Symbol::Terminal(_) => None
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut rules_by_nonterm = vec![Vec::new(); nonterms.len()];
+
+        for (rule_idx, &pattern) in rule_patterns.iter().enumerate() {
+            rules_by_nonterm[pattern].push(rule_idx);
+        }
+
+        Self{
+            rules,
+            nonterms,
+            index: Arc::new(GrammarIndex{
+                rule_patterns,
+                rule_symbols,
+                rules_by_nonterm,
+                nonterm_occurrences
+            })
+        }
+    }
+
+    /// Reconstructs the view of `rules` from the `index` of a view of the same rules,
+    /// without comparing any non-terminal symbol values.
+    ///
+    /// # Panics
+    /// Panics if `index` was built for rules with a different structure.
+    #[cfg(any(feature = "proptest", feature = "quickcheck"))]
+    pub fn with_index(rules: &'r [Rule<Nt, T>], index: &Arc<GrammarIndex>) -> Self {
+        let nonterms = index.nonterm_occurrences.iter()
+            .map(|&occurrence| nonterm_at(rules, occurrence))
+            .collect();
+
+        Self{
+            rules,
+            nonterms,
+            index: Arc::clone(index)
+        }
+    }
+
+    /// Returns the part of this view, which does not borrow the rules.
+    #[cfg(any(feature = "proptest", feature = "quickcheck"))]
+    pub fn index(&self) -> &Arc<GrammarIndex> {
+        &self.index
+    }
+
+    /// Consumes this view, returning the part of it, which does not borrow the rules.
+    pub fn into_index(self) -> Arc<GrammarIndex> {
+        self.index
+    }
+
+    pub fn nonterm_idx(&self, value: &Nt) -> Option<usize> {
+        self.nonterms.iter().position(|nonterm| *nonterm == value)
+    }
+
+    pub fn nonterm_count(&self) -> usize {
+        self.nonterms.len()
+    }
+
+    /// Computes the minimal number of nodes in a derivation tree for every non-terminal
+    /// and the rule to start such a derivation with. Both are `None` for non-terminals,
+    /// from which no fully expanded sequence can be derived.
+    pub fn minimal_derivations(&self) -> MinimalDerivations {
+        let mut costs:      Vec<Option<usize>> = vec![None; self.nonterm_count()];
+        let mut best_rules: Vec<Option<usize>> = vec![None; self.nonterm_count()];
+
+        let mut changed = true;
+
+        while changed {
+            changed = false;
+
+            for (rule_idx, symbols) in self.rule_symbols.iter().enumerate() {
+                let maybe_cost = symbols.iter()
+                    .try_fold(1_usize, |cost, symbol| match symbol {
+                        Some(nonterm_idx) => costs[*nonterm_idx].map(|nonterm_cost| cost.saturating_add(nonterm_cost)),
+                        None              => Some(cost.saturating_add(1))
+                    });
+
+                let pattern = self.rule_patterns[rule_idx];
+
+                if let Some(cost) = maybe_cost {
+                    if costs[pattern].is_none_or(|current_cost| cost < current_cost) {
+                        costs[pattern]      = Some(cost);
+                        best_rules[pattern] = Some(rule_idx);
+                        changed             = true;
+                    }
+                }
+            }
+        }
+
+        MinimalDerivations{
+            costs,
+            best_rules
+        }
+    }
+
+    /// Returns `true` for rules, all of whose non-terminal symbols can be fully expanded.
+    pub fn productive_rules(&self, minimal_derivations: &MinimalDerivations) -> Vec<bool> {
+        self.rule_symbols.iter()
+            .map(|symbols| {
+                symbols.iter()
+                    .flatten()
+                    .all(|nonterm_idx| minimal_derivations.costs[*nonterm_idx].is_some())
+            })
+            .collect()
+    }
+}
+
+impl<'r, Nt, T> Deref for IndexedGrammar<'r, Nt, T> {
+    type Target = GrammarIndex;
+
+    fn deref(&self) -> &GrammarIndex {
+        &self.index
+    }
+}
+
+//
+// GrammarIndex
+//
+
+/// Indices of the non-terminal symbols of every rule of an [`IndexedGrammar`].
+#[derive(Default, Clone)]
+pub(crate) struct GrammarIndex {
+    pub rule_patterns:    Vec<usize>,
+    pub rule_symbols:     Vec<Vec<Option<usize>>>,
+    pub rules_by_nonterm: Vec<Vec<usize>>,
+    /// Rule index and, unless it is the pattern, symbol index of the first occurrence of every non-terminal symbol.
+    nonterm_occurrences:  Vec<(usize, Option<usize>)>
+}
+
+impl GrammarIndex {
+    /// Looks up the index of the non-terminal symbol `value` among `rules`, for which this index was built.
+    /// Takes time linear in the number of distinct non-terminal symbols.
+    ///
+    /// # Panics
+    /// Panics if this index was built for rules with a different structure.
+    pub fn find_nonterm<Nt, T>(&self, rules: &[Rule<Nt, T>], value: &Nt) -> Option<usize>
+        where Nt: NonterminalValue
+    {
+        self.nonterm_occurrences.iter()
+            .position(|&occurrence| nonterm_at(rules, occurrence) == value)
+    }
+
+    /// Indexes the rules, which were appended to the rules this index was built for, so that `rules`
+    /// can grow without rebuilding the whole index. Yields the same index as [`IndexedGrammar::new()`]
+    /// would for `rules`.
+    ///
+    /// # Panics
+    /// Panics if the rules this index was built for are not a prefix of `rules`.
+    pub fn extend<Nt, T>(&mut self, rules: &[Rule<Nt, T>])
+        where Nt: NonterminalValue
+    {
+        for rule_idx in self.rule_patterns.len()..rules.len() {
+            let rule = &rules[rule_idx];
+
+            let pattern = if rule_idx > 0 && rules[rule_idx - 1].pattern == rule.pattern {
+                self.rule_patterns[rule_idx - 1]
+            } else {
+                self.intern(rules, &rule.pattern, (rule_idx, None))
+            };
+
+            self.rule_patterns.push(pattern);
+            self.rules_by_nonterm[pattern].push(rule_idx);
+
+            let mut symbols = Vec::with_capacity(rule.replacement.len());
+
+            for (symbol_idx, symbol) in rule.replacement.iter().enumerate() {
+                symbols.push(match symbol {
+                    Symbol::Nonterminal(value) => Some(self.intern(rules, value, (rule_idx, Some(symbol_idx)))),
+                    Symbol::Terminal(_)        => None
+                });
+            }
+
+            self.rule_symbols.push(symbols);
+        }
+    }
+
+    fn intern<Nt, T>(&mut self, rules: &[Rule<Nt, T>], value: &Nt, occurrence: (usize, Option<usize>)) -> usize
+        where Nt: NonterminalValue
+    {
+        self.find_nonterm(rules, value).unwrap_or_else(|| {
+            self.nonterm_occurrences.push(occurrence);
+            self.rules_by_nonterm.push(Vec::new());
+
+            self.nonterm_occurrences.len() - 1
+        })
+    }
+}
+
+//
+// MinimalDerivations
+//
+
+pub(crate) struct MinimalDerivations {
+    pub costs:      Vec<Option<usize>>,
+    pub best_rules: Vec<Option<usize>>
+}
+
+//
+// Friend
+//
+
+/// Returns strongly connected components of the graph described by `successors`, listing indices of the successors
+/// of every node along with any values attached to the edges (using Tarjan's algorithm),
+/// ordered so that every component comes after all components reachable from it.
+pub(crate) fn strongly_connected_components<W>(successors: &[Vec<(usize, W)>]) -> Vec<Vec<usize>> {
+    let node_count = successors.len();
+
+    let mut components: Vec<Vec<usize>> = Vec::new();
+
+    let mut discovery_idxs: Vec<Option<usize>> = vec![None; node_count];
+    let mut low_links:      Vec<usize>         = vec![0; node_count];
+    let mut is_on_stack:    Vec<bool>          = vec![false; node_count];
+    let mut stack:          Vec<usize>         = Vec::new();
+    let mut discovery_count                    = 0;
+
+    for root in 0..node_count {
+        if discovery_idxs[root].is_some() {
+            continue;
+        }
+
+        // Nodes being visited along with the indices of their next successors to visit.
+        let mut call_stack = vec![(root, 0)];
+
+        while let Some(&mut (node, ref mut successor_pos)) = call_stack.last_mut() {
+            if *successor_pos == 0 && discovery_idxs[node].is_none() {
+                discovery_idxs[node] = Some(discovery_count);
+                low_links[node]      = discovery_count;
+                discovery_count     += 1;
+
+                stack.push(node);
+                is_on_stack[node] = true;
+            }
+
+            if let Some(&(successor, _)) = successors[node].get(*successor_pos) {
+                *successor_pos += 1;
+
+                match discovery_idxs[successor] {
+                    None => call_stack.push((successor, 0)),
+                    Some(successor_discovery_idx) if is_on_stack[successor] => {
+                        low_links[node] = low_links[node].min(successor_discovery_idx);
+                    },
+                    Some(_) => ()
+                }
+
+                continue;
+            }
+
+            call_stack.pop();
+
+            if let Some(&(parent, _)) = call_stack.last() {
+                low_links[parent] = low_links[parent].min(low_links[node]);
+            }
+
+            if Some(low_links[node]) == discovery_idxs[node] {
+                let mut component = Vec::new();
+
+                while let Some(component_node) = stack.pop() {
+                    is_on_stack[component_node] = false;
+
+                    component.push(component_node);
+
+                    if component_node == node {
+                        break;
+                    }
+                }
+
+                components.push(component);
+            }
+        }
+    }
+
+    components
+}
+
+//
+// Service
+//
+
+/// Returns the value of the non-terminal symbol at `occurrence` in `rules`, as stored in a [`GrammarIndex`].
+fn nonterm_at<Nt, T>(rules: &[Rule<Nt, T>], (rule_idx, maybe_symbol_idx): (usize, Option<usize>)) -> &Nt {
+    match maybe_symbol_idx {
+        None             => &rules[rule_idx].pattern,
+        Some(symbol_idx) => match &rules[rule_idx].replacement[symbol_idx] {
+            Symbol::Nonterminal(value) => value,
+            Symbol::Terminal(_)        => panic!("index must belong to the same rules")
+        }
+    }
+}
+
+fn intern<'r, Nt>(
+    nonterms:            &mut Vec<&'r Nt>,
+    nonterm_occurrences: &mut Vec<(usize, Option<usize>)>,
+    value:               &'r Nt,
+    occurrence:          (usize, Option<usize>)
+) -> usize
+    where Nt: NonterminalValue
+{
+    if let Some(nonterm_idx) = nonterms.iter().position(|nonterm| *nonterm == value) {
+        nonterm_idx
+    } else {
+        nonterms.push(value);
+        nonterm_occurrences.push(occurrence);
+
+        nonterms.len() - 1
+    }
+}
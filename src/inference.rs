@@ -0,0 +1,394 @@
+use std::collections::HashMap;
+
+use crate::grammar::{
+    NonterminalValue,
+    TerminalValue,
+    Symbol,
+    Rule
+};
+
+//
+// Interface types
+//
+
+//
+// GrammarInducer
+//
+
+/// Induces rules, which produce sequences similar to a set of example sequences of terminal values.
+///
+/// Rules are induced in two steps:
+/// 1. Generalization: terminal values, which occur between the same pair of neighbours in some examples
+///    (e.g. "cat" and "dog" in "the cat sleeps" and "the dog sleeps"), are replaced everywhere with a new non-terminal
+///    symbol, which can be expanded to any of them. Such non-terminal symbols are merged when they occur
+///    between the same neighbours as well. This step allows the rules to produce sequences, which are not among
+///    the examples, and can be disabled via [`with_generalization()`](#method.with_generalization).
+/// 2. Compression: pairs of adjacent symbols, which occur more than once in the examples, are repeatedly
+///    replaced with new non-terminal symbols, starting with the most frequent pair, producing a hierarchy of rules
+///    in the style of Sequitur (Re-Pair algorithm). Non-terminal symbols, which end up being used only once,
+///    are replaced with their expansions.
+///
+/// The resulting rules expand the start non-terminal symbol to one of the (generalized) examples, with weights
+/// equal to the number of examples it represents. Rules for non-terminal symbols introduced by generalization
+/// have weights equal to the number of occurrences of their terminal values in the examples. Use
+/// [`WeightedRandomRuleSelector`](struct.WeightedRandomRuleSelector.html) in order to take these weights into account.
+///
+/// ```
+/// use branchy::{
+///     Symbol,
+///     ExpanderBuilder,
+///     GrammarInducer
+/// };
+///
+/// let examples = vec![
+///     vec!["the", "cat", "sleeps"],
+///     vec!["the", "dog", "sleeps"],
+///     vec!["the", "cat", "eats"]
+/// ];
+///
+/// let mut nonterm_count = 0;
+///
+/// let rules = GrammarInducer::new().induce("sentence".to_string(), &examples, || {
+///     nonterm_count += 1;
+///
+///     format!("N{}", nonterm_count)
+/// });
+///
+/// let mut expander = ExpanderBuilder::from(rules).build();
+///
+/// let sentence = expander.expand(vec![Symbol::Nonterminal("sentence".to_string())]).unwrap();
+///
+/// assert_eq!(sentence.len(), 3);
+/// assert_eq!(sentence[0], "the");
+/// ```
+#[derive(Debug, Clone)]
+pub struct GrammarInducer {
+    generalization: bool
+}
+
+impl Default for GrammarInducer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GrammarInducer {
+    /// Creates an inducer with generalization enabled.
+    #[must_use]
+    pub fn new() -> Self {
+        Self{
+            generalization: true
+        }
+    }
+
+    /// Enables or disables generalization. Without generalization, the induced rules produce only the examples.
+    #[must_use]
+    pub fn with_generalization(mut self, generalization: bool) -> Self {
+        self.generalization = generalization;
+
+        self
+    }
+
+    /// Induces rules, which expand a non-terminal symbol with `start` value to sequences similar to `examples`.
+    /// Values of the additional non-terminal symbols are produced by `new_nonterm`,
+    /// which must return a distinct value on every call.
+    #[must_use]
+    pub fn induce<Nt, T, I, S, F>(&self, start: Nt, examples: I, mut new_nonterm: F) -> Vec<Rule<Nt, T>>
+        where Nt: NonterminalValue,
+              T:  TerminalValue + PartialEq,
+              I:  IntoIterator<Item = S>,
+              S:  AsRef<[T]>,
+              F:  FnMut() -> Nt
+    {
+        let mut terminals: Vec<T> = Vec::new();
+
+        let mut examples: Vec<Vec<InducedSymbol>> = examples.into_iter()
+            .map(|example| {
+                example.as_ref().iter()
+                    .map(|value| InducedSymbol::Terminal(intern(&mut terminals, value)))
+                    .collect()
+            })
+            .collect();
+
+        let mut terminal_counts = vec![0; terminals.len()];
+
+        for symbol in examples.iter().flatten() {
+            if let InducedSymbol::Terminal(terminal_idx) = symbol {
+                terminal_counts[*terminal_idx] += 1;
+            }
+        }
+
+        let classes = if self.generalization {
+            generalize(&mut examples, terminals.len())
+        } else {
+            Vec::new()
+        };
+
+        let mut distinct_examples: Vec<(Vec<InducedSymbol>, usize)> = Vec::new();
+
+        for example in examples {
+            match distinct_examples.iter_mut().find(|(distinct_example, _)| *distinct_example == example) {
+                Some((_, count)) => *count += 1,
+                None             => distinct_examples.push((example, 1))
+            }
+        }
+
+        let mut compressed_examples: Vec<_> = distinct_examples.iter()
+            .map(|(example, _)| example.clone())
+            .collect();
+
+        let pair_rules = compress(&mut compressed_examples);
+
+        let class_nonterms: Vec<Option<Nt>> = classes.iter()
+            .map(|class| if class.is_empty() { None } else { Some(new_nonterm()) })
+            .collect();
+
+        let pair_rule_nonterms: Vec<Option<Nt>> = pair_rules.iter()
+            .map(|pair_rule| pair_rule.as_ref().map(|_| new_nonterm()))
+            .collect();
+
+        let to_symbol = |symbol: &InducedSymbol| match symbol {
+            InducedSymbol::Terminal(terminal_idx)  => Symbol::Terminal(terminals[*terminal_idx].clone()),
+            InducedSymbol::Class(class_idx)        => Symbol::Nonterminal(
+                class_nonterms[*class_idx].clone().expect("merged classes must not be referenced")
+            ),
+            InducedSymbol::PairRule(pair_rule_idx) => Symbol::Nonterminal(
+                pair_rule_nonterms[*pair_rule_idx].clone().expect("inlined rules must not be referenced")
+            )
+        };
+
+        let mut rules = Vec::new();
+
+        for (example, (_, count)) in compressed_examples.iter().zip(&distinct_examples) {
+            rules.push(Rule::new(start.clone(), example.iter().map(to_symbol).collect()).with_weight(*count as f64));
+        }
+
+        for (class, class_nonterm) in classes.iter().zip(&class_nonterms) {
+            if let Some(class_nonterm) = class_nonterm {
+                for &terminal_idx in class {
+                    rules.push(
+                        Rule::new(class_nonterm.clone(), vec![Symbol::Terminal(terminals[terminal_idx].clone())])
+                            .with_weight(terminal_counts[terminal_idx] as f64)
+                    );
+                }
+            }
+        }
+
+        for (pair_rule, pair_rule_nonterm) in pair_rules.iter().zip(&pair_rule_nonterms) {
+            if let (Some(replacement), Some(pair_rule_nonterm)) = (pair_rule, pair_rule_nonterm) {
+                rules.push(Rule::new(pair_rule_nonterm.clone(), replacement.iter().map(to_symbol).collect()));
+            }
+        }
+
+        rules
+    }
+}
+
+//
+// Service types
+//
+
+//
+// enum InducedSymbol: Debug + Clone + Copy + PartialEq + Eq + Hash
+//
+
+/// Symbol of an example being generalized and compressed, referring to a terminal value, a class of terminal values
+/// introduced by generalization or a rule introduced by compression by its index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum InducedSymbol {
+    Terminal(usize),
+    Class(usize),
+    PairRule(usize)
+}
+
+//
+// Service
+//
+
+fn intern<T>(values: &mut Vec<T>, value: &T) -> usize
+    where T: TerminalValue + PartialEq
+{
+    match values.iter().position(|existing_value| existing_value == value) {
+        Some(idx) => idx,
+        None      => {
+            values.push(value.clone());
+
+            values.len() - 1
+        }
+    }
+}
+
+/// Repeatedly merges symbols occurring between the same neighbours into classes, until there are no such symbols.
+/// Returns terminal indices of every class, which are empty for classes merged into other ones.
+fn generalize(examples: &mut [Vec<InducedSymbol>], terminal_count: usize) -> Vec<Vec<usize>> {
+    let mut classes: Vec<Vec<usize>> = Vec::new();
+
+    while let Some((first_symbol, second_symbol)) = find_substitutable_symbols(examples) {
+        let class_idx = match (first_symbol, second_symbol) {
+            (InducedSymbol::Class(class_idx), InducedSymbol::Class(other_class_idx)) => {
+                let (class_idx, other_class_idx) = (class_idx.min(other_class_idx), class_idx.max(other_class_idx));
+
+                let other_class = std::mem::take(&mut classes[other_class_idx]);
+
+                classes[class_idx].extend(other_class);
+
+                class_idx
+            },
+            (InducedSymbol::Class(class_idx), InducedSymbol::Terminal(terminal_idx))
+                | (InducedSymbol::Terminal(terminal_idx), InducedSymbol::Class(class_idx)) =>
+            {
+                classes[class_idx].push(terminal_idx);
+
+                class_idx
+            },
+            (InducedSymbol::Terminal(terminal_idx), InducedSymbol::Terminal(other_terminal_idx)) => {
+                classes.push(vec![terminal_idx, other_terminal_idx]);
+
+                classes.len() - 1
+            },
+            _ => unreachable!("examples must not contain rules before compression")
+        };
+
+        let mut terminal_classes = vec![None; terminal_count];
+
+        for &terminal_idx in &classes[class_idx] {
+            terminal_classes[terminal_idx] = Some(class_idx);
+        }
+
+        for symbol in examples.iter_mut().flatten() {
+            let is_merged = match *symbol {
+                InducedSymbol::Terminal(terminal_idx) => terminal_classes[terminal_idx].is_some(),
+                InducedSymbol::Class(other_class_idx) => classes[other_class_idx].is_empty(),
+                InducedSymbol::PairRule(_)            => false
+            };
+
+            if is_merged {
+                *symbol = InducedSymbol::Class(class_idx);
+            }
+        }
+    }
+
+    classes
+}
+
+/// Returns the first pair of distinct symbols found between the same neighbours, where the beginning and the end
+/// of an example count as neighbours as well.
+fn find_substitutable_symbols(examples: &[Vec<InducedSymbol>]) -> Option<(InducedSymbol, InducedSymbol)> {
+    let mut context_symbols: HashMap<(Option<InducedSymbol>, Option<InducedSymbol>), InducedSymbol> = HashMap::new();
+
+    for example in examples {
+        for (symbol_idx, &symbol) in example.iter().enumerate() {
+            let context = (
+                symbol_idx.checked_sub(1).map(|previous_idx| example[previous_idx]),
+                example.get(symbol_idx + 1).copied()
+            );
+
+            match context_symbols.get(&context) {
+                Some(&context_symbol) if context_symbol != symbol => return Some((context_symbol, symbol)),
+                Some(_)                                          => (),
+                None                                             => {
+                    context_symbols.insert(context, symbol);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Repeatedly replaces the most frequent pair of adjacent symbols, occurring at least twice, with a new rule.
+/// Returns replacements of all rules, which are `None` for rules inlined because they ended up being used only once.
+fn compress(examples: &mut [Vec<InducedSymbol>]) -> Vec<Option<Vec<InducedSymbol>>> {
+    let mut pair_rules: Vec<Option<Vec<InducedSymbol>>> = Vec::new();
+
+    while let Some((first_symbol, second_symbol)) = find_most_frequent_pair(examples) {
+        let pair_rule = InducedSymbol::PairRule(pair_rules.len());
+
+        pair_rules.push(Some(vec![first_symbol, second_symbol]));
+
+        for example in examples.iter_mut() {
+            let mut compressed_example = Vec::with_capacity(example.len());
+            let mut symbol_idx         = 0;
+
+            while symbol_idx < example.len() {
+                if example[symbol_idx] == first_symbol && example.get(symbol_idx + 1) == Some(&second_symbol) {
+                    compressed_example.push(pair_rule);
+
+                    symbol_idx += 2;
+                } else {
+                    compressed_example.push(example[symbol_idx]);
+
+                    symbol_idx += 1;
+                }
+            }
+
+            *example = compressed_example;
+        }
+    }
+
+    inline_single_use_rules(examples, &mut pair_rules);
+
+    pair_rules
+}
+
+/// Returns the most frequent pair of adjacent symbols, which occurs at least twice without overlapping,
+/// preferring pairs occurring earlier in the examples.
+fn find_most_frequent_pair(examples: &[Vec<InducedSymbol>]) -> Option<(InducedSymbol, InducedSymbol)> {
+    let mut pair_counts: HashMap<(InducedSymbol, InducedSymbol), (usize, usize)> = HashMap::new();
+
+    for example in examples {
+        // Index of the last counted occurrence of every pair in this example, so that overlapping occurrences
+        // in runs of the same symbol are not counted.
+        let mut last_pair_idxs: HashMap<(InducedSymbol, InducedSymbol), usize> = HashMap::new();
+
+        for (pair_idx, pair) in example.windows(2).enumerate() {
+            let pair = (pair[0], pair[1]);
+
+            if last_pair_idxs.get(&pair).is_some_and(|&last_pair_idx| last_pair_idx + 1 == pair_idx) {
+                continue;
+            }
+
+            last_pair_idxs.insert(pair, pair_idx);
+
+            let first_occurrence = pair_counts.len();
+
+            pair_counts.entry(pair).or_insert((0, first_occurrence)).0 += 1;
+        }
+    }
+
+    pair_counts.into_iter()
+        .filter(|(_, (count, _))| *count >= 2)
+        .max_by(|(_, (count, first_occurrence)), (_, (other_count, other_first_occurrence))| {
+            count.cmp(other_count).then(other_first_occurrence.cmp(first_occurrence))
+        })
+        .map(|(pair, _)| pair)
+}
+
+fn inline_single_use_rules(examples: &mut [Vec<InducedSymbol>], pair_rules: &mut [Option<Vec<InducedSymbol>>]) {
+    loop {
+        let mut use_counts = vec![0; pair_rules.len()];
+
+        for symbol in examples.iter().flatten().chain(pair_rules.iter().flatten().flatten()) {
+            if let InducedSymbol::PairRule(pair_rule_idx) = symbol {
+                use_counts[*pair_rule_idx] += 1;
+            }
+        }
+
+        let single_use_rule_idx = match use_counts.iter().position(|&use_count| use_count == 1) {
+            Some(pair_rule_idx) => pair_rule_idx,
+            None                => break
+        };
+
+        let replacement = pair_rules[single_use_rule_idx].take()
+            .expect("used rules must not be inlined");
+
+        let inline = |symbols: &mut Vec<InducedSymbol>| {
+            if let Some(symbol_idx) = symbols.iter().position(|&symbol| symbol == InducedSymbol::PairRule(single_use_rule_idx)) {
+                symbols.splice(symbol_idx..=symbol_idx, replacement.iter().copied());
+            }
+        };
+
+        examples.iter_mut().for_each(inline);
+        pair_rules.iter_mut().flatten().for_each(inline);
+    }
+}
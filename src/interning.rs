@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::{
+    self,
+    Display
+};
+
+use crate::grammar::{
+    Symbol,
+    Rule
+};
+
+//
+// Interface types
+//
+
+//
+// SymbolId: Debug + Clone + Copy + PartialEq + Eq + Hash + PartialOrd + Ord + Display
+//
+
+/// Compact identifier of a string value interned in a [`SymbolTable`](struct.SymbolTable.html).
+///
+/// Cloning and comparing identifiers is much cheaper than cloning and comparing `String`s,
+/// which makes `Rule<SymbolId, SymbolId>` a faster alternative to `Rule<String, String>` for expansion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SymbolId(u32);
+
+impl SymbolId {
+    /// Returns the index of the identified value in its [`SymbolTable`](struct.SymbolTable.html),
+    /// i.e. the number of values interned before it.
+    #[must_use]
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Formats the identifier as `#` followed by its index.
+impl Display for SymbolId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+//
+// SymbolTable: Debug + Clone + Default
+//
+
+/// Maps string values of symbols to [`SymbolId`](struct.SymbolId.html)s and back.
+///
+/// Grammars loaded as `Rule<String, String>`, e.g. by [`parse_grammar()`](fn.parse_grammar.html), can be compiled
+/// into `Rule<SymbolId, SymbolId>` via [`compile_rules()`](#method.compile_rules), so that expansion compares
+/// and clones identifiers instead of strings. The resulting identifiers are resolved back to `&str`
+/// via [`resolve_all()`](#method.resolve_all). Non-terminal and terminal values share the same table.
+///
+/// Compiled rules keep their [`Modifier`](struct.Modifier.html)s, which operate on strings, so applying them requires
+/// a [`ModifierRegistry`](struct.ModifierRegistry.html) adapted to the table via
+/// [`ModifierRegistry::compile()`](struct.ModifierRegistry.html#method.compile).
+///
+/// ```
+/// use branchy::{
+///     Symbol,
+///     ExpanderBuilder,
+///     SymbolTable,
+///     parse_grammar
+/// };
+///
+/// let rules = parse_grammar(r#"
+///     greeting -> "Hello," <name> "!"
+///     name     -> "Alice" | "Bob"
+/// "#).unwrap();
+///
+/// let mut symbol_table = SymbolTable::new();
+///
+/// let mut expander = ExpanderBuilder::from(symbol_table.compile_rules(&rules)).build();
+///
+/// let greeting = symbol_table.get("greeting").unwrap();
+///
+/// let output = expander.expand(vec![Symbol::Nonterminal(greeting)]).unwrap();
+///
+/// let words = symbol_table.resolve_all(&output);
+///
+/// assert!(words == ["Hello,", "Alice", "!"] || words == ["Hello,", "Bob", "!"]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable {
+    values: Vec<Box<str>>,
+    ids:    HashMap<Box<str>, SymbolId>
+}
+
+impl SymbolTable {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the identifier of `value`, interning it first if needed.
+    ///
+    /// # Panics
+    /// Panics if the table already contains `u32::MAX` values.
+    pub fn intern(&mut self, value: &str) -> SymbolId {
+        if let Some(&id) = self.ids.get(value) {
+            return id;
+        }
+
+        let id = SymbolId(u32::try_from(self.values.len()).expect("symbol table must not overflow"));
+
+        self.values.push(value.into());
+        self.ids.insert(value.into(), id);
+
+        id
+    }
+
+    /// Returns the identifier of `value`, or `None` if it has not been interned.
+    #[must_use]
+    pub fn get(&self, value: &str) -> Option<SymbolId> {
+        self.ids.get(value).copied()
+    }
+
+    /// Returns the value identified by `id`, or `None` if `id` does not belong to this table.
+    #[must_use]
+    pub fn value(&self, id: SymbolId) -> Option<&str> {
+        self.values.get(id.index()).map(AsRef::as_ref)
+    }
+
+    /// Returns the value identified by `id`.
+    ///
+    /// # Panics
+    /// Panics if `id` does not belong to this table.
+    #[must_use]
+    pub fn resolve(&self, id: SymbolId) -> &str {
+        self.value(id)
+            .expect("symbol id must belong to the symbol table")
+    }
+
+    /// Returns the values identified by `ids`, e.g. the output of an expansion.
+    ///
+    /// # Panics
+    /// Panics if any of `ids` does not belong to this table.
+    #[must_use]
+    pub fn resolve_all(&self, ids: &[SymbolId]) -> Vec<&str> {
+        ids.iter()
+            .map(|&id| self.resolve(id))
+            .collect()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Interns values of `symbol`.
+    pub fn compile_symbol<Nt, T>(&mut self, symbol: &Symbol<Nt, T>) -> Symbol<SymbolId, SymbolId>
+        where Nt: AsRef<str>,
+              T:  AsRef<str>
+    {
+        match symbol {
+            Symbol::Nonterminal(value) => Symbol::Nonterminal(self.intern(value.as_ref())),
+            Symbol::Terminal(value)    => Symbol::Terminal(self.intern(value.as_ref()))
+        }
+    }
+
+    /// Interns values of all `symbols`, e.g. of the input sequence for an expansion.
+    pub fn compile_symbols<Nt, T>(&mut self, symbols: &[Symbol<Nt, T>]) -> Vec<Symbol<SymbolId, SymbolId>>
+        where Nt: AsRef<str>,
+              T:  AsRef<str>
+    {
+        symbols.iter()
+            .map(|symbol| self.compile_symbol(symbol))
+            .collect()
+    }
+
+    /// Interns all values of `rule`, keeping its weight and modifiers.
+    ///
+    /// Modifier names are not interned, so expanding the compiled rule with modifiers requires a
+    /// [`ModifierRegistry`](struct.ModifierRegistry.html) adapted via
+    /// [`ModifierRegistry::compile()`](struct.ModifierRegistry.html#method.compile).
+    pub fn compile_rule<Nt, T>(&mut self, rule: &Rule<Nt, T>) -> Rule<SymbolId, SymbolId>
+        where Nt: AsRef<str>,
+              T:  AsRef<str>
+    {
+        Rule{
+            pattern:     self.intern(rule.pattern.as_ref()),
+            replacement: self.compile_symbols(&rule.replacement),
+            weight:      rule.weight,
+            modifiers:   rule.modifiers.clone()
+        }
+    }
+
+    /// Interns all values of `rules`.
+    pub fn compile_rules<Nt, T>(&mut self, rules: &[Rule<Nt, T>]) -> Vec<Rule<SymbolId, SymbolId>>
+        where Nt: AsRef<str>,
+              T:  AsRef<str>
+    {
+        rules.iter()
+            .map(|rule| self.compile_rule(rule))
+            .collect()
+    }
+
+    /// Resolves all values of a compiled `rule`, e.g. in order to display it.
+    ///
+    /// # Panics
+    /// Panics if any of the rule's identifiers does not belong to this table.
+    #[must_use]
+    pub fn resolve_rule(&self, rule: &Rule<SymbolId, SymbolId>) -> Rule<&str, &str> {
+        rule.clone().map(|id| self.resolve(id), |id| self.resolve(id))
+    }
+}
@@ -64,6 +64,18 @@
 //! to select rules while expanding, therefore the result is randomized. As we'll see below,
 //! this can be changed, if needed, via [`ExpanderBuilder`](struct.ExpanderBuilder.html).
 //! 
+//! Uniform random selection may well pick the same rule several times in a row. If that is undesirable,
+//! [`ShuffleBagRuleSelector`](struct.ShuffleBagRuleSelector.html) goes through all matching rules
+//! in random order before repeating any of them, while
+//! [`NoImmediateRepeatRuleSelector`](struct.NoImmediateRepeatRuleSelector.html) only avoids
+//! selecting the same rule twice in a row.
+//! [`WeightedRandomRuleSelector`](struct.WeightedRandomRuleSelector.html) makes some rules more likely to be selected
+//! than others according to their [`weight`](struct.Rule.html#structfield.weight)s.
+//! 
+//! Rules of an existing `Expander` can be added, removed, replaced and re-weighted in place
+//! (see [`Expander::update_rules()`](struct.Expander.html#method.update_rules) and related methods),
+//! as can its rule selector and logger, e.g. in order to unlock new content at runtime.
+//! 
 //! ## Using a custom rule selector
 //! 
 //! When constructing an [`Expander`](struct.Expander.html), you can provide your own
@@ -75,19 +87,27 @@
 //! As you can see, rule selectors need to implement at least the
 //! [`select_matching_rule()`](trait.RuleSelector.html#method.select_matching_rule) method
 //! from the [`RuleSelector`](trait.RuleSelector.html) trait.
+//! The [`ExpansionContext`](struct.ExpansionContext.html) passed to rule selectors describes the non-terminal symbol
+//! being expanded, its ancestors in the derivation, the output produced so far and the current iteration,
+//! which allows for context-sensitive rule selection.
 //! 
 //! ```
 //! use branchy::{
 //!     Symbol,
 //!     Rule,
 //!     ExpanderBuilder,
-//!     RuleSelector
+//!     RuleSelector,
+//!     ExpansionContext
 //! };
 //! 
 //! struct AlwaysFirstRuleSelector;
 //! 
 //! impl<Nt, T> RuleSelector<Nt, T> for AlwaysFirstRuleSelector {
-//!     fn select_matching_rule<'a>(&self, matching_rules: &[&'a Rule<Nt, T>]) -> Option<&'a Rule<Nt, T>> {
+//!     fn select_matching_rule<'a>(
+//!         &mut self,
+//!         matching_rules: &[&'a Rule<Nt, T>],
+//!         _context:       &ExpansionContext<Nt, T>
+//!     ) -> Option<&'a Rule<Nt, T>> {
 //!         if matching_rules.is_empty() {
 //!             None
 //!         } else {
@@ -176,6 +196,169 @@
 //! expanded "direction" to [Terminal("east")]
 //! ```
 //! 
+//! In order to find out which rules of your grammar are used and how often, use the built-in
+//! [`StatsExpansionLogger`](struct.StatsExpansionLogger.html), which accumulates statistics across
+//! many expansions and can produce a [`CoverageReport`](struct.CoverageReport.html), listing the rules
+//! that have never been used.
+//! 
+//! Loggers can be combined via the methods of the [`ExpansionLoggerExt`](trait.ExpansionLoggerExt.html) trait.
+//! Since [`ExpansionLogger`](trait.ExpansionLogger.html) is implemented for mutable references, a logger can also
+//! be borrowed by an [`Expander`](struct.Expander.html), rather than moved into it:
+//! 
+//! ```
+//! use branchy::{
+//!     Symbol,
+//!     ExpanderBuilder,
+//!     ExpansionLoggerExt,
+//!     StatsExpansionLogger
+//! };
+//! 
+//! let mut greeting_stats = StatsExpansionLogger::new();
+//! let mut failure_stats  = StatsExpansionLogger::new();
+//! 
+//! let mut expander = ExpanderBuilder::new()
+//!     .with_new_rule("greeting", vec![Symbol::Terminal("Hello"), Symbol::Nonterminal("name")])
+//!     .with_new_rule("name", vec![Symbol::Terminal("world")])
+//!     .with_logger(
+//!         (&mut greeting_stats)
+//!             .filter_nonterm(|nonterm_value| *nonterm_value == "greeting")
+//!             .and((&mut failure_stats).only_failures())
+//!     )
+//!     .build();
+//! 
+//! expander.expand(vec![Symbol::Nonterminal("greeting")]).unwrap();
+//! expander.expand(vec![Symbol::Nonterminal("farewell")]).unwrap_err();
+//! 
+//! drop(expander);
+//! 
+//! assert_eq!(greeting_stats.nonterm_expansion_count(&"greeting"), 1);
+//! assert_eq!(greeting_stats.nonterm_expansion_count(&"name"), 0);
+//! assert_eq!(failure_stats.nonterm_failure_count(&"farewell"), 1);
+//! assert_eq!(failure_stats.nonterm_expansion_count(&"greeting"), 0);
+//! ```
+//! 
+//! If you already use [`tracing`](https://docs.rs/tracing) or [`log`](https://docs.rs/log) for diagnostics,
+//! enable the `tracing` or `log` cargo feature of `branchy` and use the built-in
+//! `TracingExpansionLogger` or `LogExpansionLogger` respectively instead of writing your own logger.
+//! 
+//! ## Formatting text
+//! 
+//! Joining terminal values with spaces, as in the example above, produces text like "Bob comes from a old village .".
+//! [`TextFormatter`](struct.TextFormatter.html) joins them with punctuation-aware spacing instead, capitalizes sentences,
+//! makes articles agree with the following words and supports markers for gluing words together and pluralizing them.
+//! Language-specific rules are described by the [`Language`](trait.Language.html) trait,
+//! which is implemented for [`English`](struct.English.html).
+//! 
+//! ## Modifying expanded symbols
+//! 
+//! Symbols in a rule's replacement may have named [`Modifier`](struct.Modifier.html)s attached via
+//! [`Rule::with_modifier()`](struct.Rule.html#method.with_modifier), like `#animal.s#` in Tracery, e.g. in order to
+//! capitalize or pluralize whatever a non-terminal symbol expands to. Modifiers are looked up in the
+//! [`ModifierRegistry`](struct.ModifierRegistry.html) of the `Expander`, which can hold user-defined functions
+//! as well as the built-in modifiers for `String` terminal values.
+//! 
+//! ## Recovering from missing rules
+//! 
+//! By default, expansion fails as soon as a non-terminal symbol has no matching rules. A [`RecoveryPolicy`](enum.RecoveryPolicy.html),
+//! set via [`ExpanderBuilder::with_recovery_policy()`](struct.ExpanderBuilder.html#method.with_recovery_policy),
+//! can instead replace such symbols with a placeholder, drop them or substitute a fallback sequence of symbols.
+//! [`Expander::expand_with_warnings()`](struct.Expander.html#method.expand_with_warnings) then returns the output
+//! together with an [`ExpansionWarning`](struct.ExpansionWarning.html) for every recovered symbol,
+//! which is handy for drafting grammars that are not complete yet.
+//! 
+//! ## Analyzing probabilities
+//! 
+//! [`ProbabilisticGrammar`](struct.ProbabilisticGrammar.html), returned by
+//! [`Expander::probabilistic_grammar()`](struct.Expander.html#method.probabilistic_grammar), treats rules as
+//! selected according to the [`RuleDistribution`](enum.RuleDistribution.html) of the rule selector. It computes
+//! probabilities of derivations and output sentences, expected output lengths and entropies per non-terminal symbol,
+//! and detects non-terminal symbols, whose expansion is expected to never finish because recursive rules are too likely.
+//! 
+//! ## Training weights
+//! 
+//! [`WeightTrainer`](struct.WeightTrainer.html) estimates rule weights from a corpus of example outputs, so that
+//! [`WeightedRandomRuleSelector`](struct.WeightedRandomRuleSelector.html) produces similar outputs. Example outputs,
+//! which can be produced in more than one way, are attributed to their parses using the inside-outside algorithm.
+//! 
+//! ## Inducing grammars from examples
+//! 
+//! [`GrammarInducer`](struct.GrammarInducer.html) bootstraps rules from a set of example sequences, generalizing
+//! over values, which occur in the same contexts, and compressing repeated parts into a hierarchy of non-terminal
+//! symbols. The induced rules can be passed to [`ExpanderBuilder`](struct.ExpanderBuilder.html) directly,
+//! in order to generate new sequences in the style of the examples.
+//! 
+//! ## Rewriting with context
+//! 
+//! [`Rewriter`](struct.Rewriter.html) applies [`RewriteRule`](struct.RewriteRule.html)s, which replace sequences
+//! of symbols (terminal ones included) only when they occur between given left and right contexts. In
+//! [parallel mode](enum.RewriteMode.html#variant.Parallel) every iteration rewrites all symbols at once, producing
+//! the next generation of a context-sensitive L-system, while in [sequential mode](enum.RewriteMode.html#variant.Sequential)
+//! rules are applied one at a time, e.g. in order to post-process the output of an `Expander`.
+//! 
+//! ## Reusing output buffers
+//! 
+//! [`Expander::expand_into()`](struct.Expander.html#method.expand_into) appends the resulting terminal values
+//! to a caller-provided vector instead of allocating a new one. Together with the work stack and the index of rules
+//! for every non-terminal symbol, which the `Expander` keeps between expansions, this allows generating large numbers
+//! of short sequences without allocating, as long as symbol values are cheap to clone, e.g. `&str`, `Rc<str>` or
+//! `Arc<str>`, the applied rules have no modifiers and the rule selector does not allocate, like the default one.
+//! 
+//! ## Covering every rule
+//! 
+//! When testing a grammar or a consumer of its output, it is often more useful to have a small set of outputs
+//! which together exercise every rule, than to rely on random expansion eventually selecting all of them.
+//! [`CoverageGenerator`](struct.CoverageGenerator.html) computes such a set of [`Derivation`](struct.Derivation.html)s
+//! for a given input sequence, optionally covering every parent-child pair of rules as well
+//! (see [`CoverageCriterion`](enum.CoverageCriterion.html)).
+//! 
+//! ## Mutating derivations
+//! 
+//! [`Expander::expand_derivation()`](struct.Expander.html#method.expand_derivation) returns the complete
+//! [`Derivation`](struct.Derivation.html) of an input sequence rather than just its output.
+//! [`DerivationMutator`](struct.DerivationMutator.html) turns such derivations into mutants by regenerating
+//! random subtrees, splicing in subtrees from other derivations or repeating recursive parts,
+//! so that every mutant is still a valid sentence of the grammar, which is useful for grammar-based fuzzing.
+//! 
+//! ## Transforming grammars
+//! 
+//! Rules can be converted to equivalent normal forms using [`to_chomsky_normal_form()`](fn.to_chomsky_normal_form.html)
+//! and [`to_greibach_normal_form()`](fn.to_greibach_normal_form.html), or simplified by
+//! [`remove_empty_rules()`](fn.remove_empty_rules.html), [`remove_unit_rules()`](fn.remove_unit_rules.html)
+//! and [`remove_left_recursion()`](fn.remove_left_recursion.html). Since values of non-terminal symbols
+//! can be of any type, transformations which need additional non-terminal symbols take a closure producing new values.
+//! 
+//! ## Grammar notation
+//! 
+//! When values of symbols implement `Display`, so do [`Symbol`](enum.Symbol.html) and [`Rule`](struct.Rule.html),
+//! using conventional grammar notation like `x -> <y> "z"`. [`display_grammar()`](fn.display_grammar.html)
+//! formats a whole grammar grouping alternatives by pattern, and [`parse_grammar()`](fn.parse_grammar.html)
+//! parses such text back into rules with `String` symbol values, so grammars can also be kept in text files.
+//! 
+//! ## Visualizing grammars
+//! 
+//! [`grammar_graph()`](fn.grammar_graph.html) describes the dependencies between non-terminal symbols
+//! of a grammar as a [Graphviz](https://graphviz.org) DOT or [Mermaid](https://mermaid.js.org) graph
+//! (see [`GraphFormat`](enum.GraphFormat.html)), highlighting recursion cycles, non-terminal symbols without rules
+//! and ones which can never be fully expanded. [`derivation_graph()`](fn.derivation_graph.html) does the same
+//! for individual derivation trees.
+//! 
+//! ## Composing grammars from modules
+//! 
+//! Larger grammars can be split into [`GrammarModule`](struct.GrammarModule.html)s, each with its own namespace
+//! of non-terminal symbols. Modules explicitly export the non-terminal symbols they offer and import the ones they use
+//! from other modules, and may replace or extend rules of imported non-terminal symbols (see [`OverrideMode`](enum.OverrideMode.html)).
+//! [`GrammarLinker`](struct.GrammarLinker.html) validates the modules and links them into a single set of rules
+//! over [`QualifiedNonterm`](struct.QualifiedNonterm.html)s, so that equally named symbols in different modules never collide.
+//! 
+//! ## Property-based testing
+//! 
+//! Grammars are a convenient way to describe structured inputs for fuzzing and property-based tests.
+//! With the `proptest` cargo feature enabled, `GrammarStrategy` turns a set of rules and an input sequence
+//! into a [`proptest`](https://docs.rs/proptest) strategy. With the `quickcheck` feature enabled,
+//! `GrammarSample` implements [`quickcheck`](https://docs.rs/quickcheck)'s `Arbitrary` for any grammar
+//! described by an `ArbitraryGrammar` type. In both cases failing inputs are shrunk on their derivation trees,
+//! so shrunk inputs are still produced by the grammar.
+//! 
 //! ## Generating non-text sequences
 //! 
 //! Even though the primary use-case for `branchy` is generating text strings, it can be used for
@@ -183,15 +366,44 @@
 //! used for values of non-terminal symbols and any type implementing `Clone` can be used for
 //! terminals. See [`NonterminalValue`](trait.NonterminalValue.html) and
 //! [`TerminalValue`](trait.TerminalValue.html) traits.
+//! 
+//! A grammar authored with one representation of values, e.g. string literals, can be converted to another one,
+//! e.g. interned identifiers or enums, using [`map_grammar()`](fn.map_grammar.html) or
+//! [`try_map_grammar()`](fn.try_map_grammar.html), as well as [`Rule::map()`](struct.Rule.html#method.map)
+//! and [`Symbol::map()`](enum.Symbol.html#method.map) for individual rules and symbols.
+//! 
+//! In particular, grammars with `String` values, e.g. loaded from files, can be compiled into grammars
+//! with compact [`SymbolId`](struct.SymbolId.html)s using a [`SymbolTable`](struct.SymbolTable.html),
+//! which avoids cloning and comparing strings during expansion and resolves the output back to `&str`.
+//! Modifiers of compiled grammars are applied by adapting a registry for strings via
+//! [`ModifierRegistry::compile()`](struct.ModifierRegistry.html#method.compile).
 
 mod grammar;
 mod expansion;
+mod derivation;
+mod coverage;
+mod mutation;
+mod transform;
+mod linking;
+mod visualization;
+mod notation;
+mod text;
+mod probability;
+mod inference;
+mod rewriting;
+mod interning;
+
+#[cfg(feature = "proptest")]
+mod proptest_strategy;
+#[cfg(feature = "quickcheck")]
+mod quickcheck_arbitrary;
 
 pub use grammar::{
     NonterminalValue,
     TerminalValue,
     Symbol,
-    Rule
+    Rule,
+    Modifier
 };
 
 pub use expansion::{
@@ -199,9 +411,117 @@ pub use expansion::{
     ExpanderBuilder,
     RuleSelector,
     ExpansionLogger,
+    ExpansionLoggerExt,
+    ExpansionContext,
     UniformRandomRuleSelector,
+    WeightedRandomRuleSelector,
+    ShuffleBagRuleSelector,
+    NoImmediateRepeatRuleSelector,
     NullExpansionLogger,
+    StatsExpansionLogger,
+    CoverageReport,
+    TeeExpansionLogger,
+    NontermFilterExpansionLogger,
+    FailuresOnlyExpansionLogger,
+    RecoveryPolicy,
+    FallbackFn,
+    ExpansionWarning,
+    Recovered,
+    ModifierRegistry,
+    ModifierFn,
     Error,
     ErrorKind,
+    ErrorDisplay,
     Result
 };
+
+pub use derivation::{
+    Derivation,
+    DerivationTree
+};
+
+pub use coverage::{
+    CoverageGenerator,
+    CoverageCriterion,
+    CoverageSuite
+};
+
+pub use mutation::DerivationMutator;
+
+pub use linking::{
+    GrammarModule,
+    GrammarLinker,
+    QualifiedNonterm,
+    OverrideMode,
+    LinkError
+};
+
+pub use visualization::{
+    GraphFormat,
+    grammar_graph,
+    derivation_graph
+};
+
+pub use notation::{
+    GrammarDisplay,
+    ParseError,
+    ParseErrorKind,
+    display_grammar,
+    parse_grammar
+};
+
+pub use text::{
+    Language,
+    English,
+    TextFormatter
+};
+
+pub use probability::{
+    RuleDistribution,
+    ProbabilisticGrammar,
+    WeightTrainer,
+    TrainingReport
+};
+
+pub use inference::GrammarInducer;
+
+pub use interning::{
+    SymbolId,
+    SymbolTable
+};
+
+pub use rewriting::{
+    RewriteRule,
+    RewriteMode,
+    Rewriter,
+    Rewritten
+};
+
+pub use transform::{
+    nullable_nonterms,
+    remove_empty_rules,
+    remove_unit_rules,
+    remove_left_recursion,
+    to_chomsky_normal_form,
+    to_greibach_normal_form,
+    map_grammar,
+    try_map_grammar
+};
+
+#[cfg(feature = "tracing")]
+pub use expansion::TracingExpansionLogger;
+
+#[cfg(feature = "log")]
+pub use expansion::LogExpansionLogger;
+
+#[cfg(feature = "proptest")]
+pub use proptest_strategy::{
+    GrammarStrategy,
+    DerivationValueTree
+};
+
+#[cfg(feature = "quickcheck")]
+pub use quickcheck_arbitrary::{
+    ArbitraryGrammar,
+    GrammarSample
+};
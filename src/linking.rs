@@ -0,0 +1,516 @@
+use std::fmt::{
+    self,
+    Debug,
+    Display
+};
+
+use crate::grammar::{
+    NonterminalValue,
+    TerminalValue,
+    Symbol,
+    Rule
+};
+
+//
+// Interface types
+//
+
+//
+// QualifiedNonterm<Nt>: Debug + Clone + PartialEq + Eq + Hash + Display
+//
+
+/// Value of a non-terminal symbol in rules produced by [`GrammarLinker`](struct.GrammarLinker.html):
+/// the original value, qualified with the name of the [`GrammarModule`](struct.GrammarModule.html) defining it.
+///
+/// Displayed as `module::name` if the original value implements `Display`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QualifiedNonterm<Nt> {
+    pub module: String,
+    pub name:   Nt
+}
+
+impl<Nt> QualifiedNonterm<Nt> {
+    #[must_use]
+    pub fn new<S>(module: S, name: Nt) -> Self
+        where S: Into<String>
+    {
+        Self{
+            module: module.into(),
+            name
+        }
+    }
+}
+
+impl<Nt> Display for QualifiedNonterm<Nt>
+    where Nt: Display
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}::{}", self.module, self.name)
+    }
+}
+
+//
+// OverrideMode: Debug + Clone + Copy + PartialEq + Eq
+//
+
+/// Determines how rules added by [`GrammarModule::with_override()`](struct.GrammarModule.html#method.with_override)
+/// are combined with the rules of the overridden non-terminal symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverrideMode {
+    /// The overriding rules are used instead of the rules defined by the exporting module.
+    Replace,
+    /// The overriding rules are used in addition to the rules defined by the exporting module.
+    Extend
+}
+
+//
+// GrammarModule<Nt, T>
+//
+
+/// A named set of rules, which can use non-terminal symbols exported by other modules
+/// and export its own non-terminal symbols to them.
+///
+/// Non-terminal symbols of different modules never clash, even if their values are equal,
+/// since [`GrammarLinker`](struct.GrammarLinker.html) qualifies them with module names.
+/// A non-terminal symbol used in a module's rules refers to an imported symbol if it matches the local name
+/// of one of the module's imports, or to the module's own symbol otherwise.
+///
+/// ```
+/// use branchy::{
+///     Symbol,
+///     GrammarModule,
+///     GrammarLinker,
+///     QualifiedNonterm,
+///     ExpanderBuilder
+/// };
+///
+/// let names = GrammarModule::new("names")
+///     .with_new_rule("name", vec![Symbol::Terminal("Alice")])
+///     .with_new_rule("name", vec![Symbol::Terminal("Bob")])
+///     .with_export("name");
+///
+/// let greetings = GrammarModule::new("greetings")
+///     .with_import("names", "name")
+///     .with_new_rule("greeting", vec![Symbol::Nonterminal("salutation"), Symbol::Nonterminal("name")])
+///     .with_new_rule("salutation", vec![Symbol::Terminal("Hello")]);
+///
+/// let rules = GrammarLinker::new()
+///     .with_module(names)
+///     .with_module(greetings)
+///     .link()
+///     .unwrap();
+///
+/// let mut expander = ExpanderBuilder::from(rules).build();
+///
+/// let greeting = expander.expand(vec![Symbol::Nonterminal(QualifiedNonterm::new("greetings", "greeting"))]).unwrap();
+///
+/// assert_eq!(greeting[0], "Hello");
+/// ```
+pub struct GrammarModule<Nt, T> {
+    name:      String,
+    rules:     Vec<Rule<Nt, T>>,
+    exports:   Vec<Nt>,
+    imports:   Vec<Import<Nt>>,
+    overrides: Vec<(OverrideMode, Rule<Nt, T>)>
+}
+
+impl<Nt, T> GrammarModule<Nt, T>
+    where Nt: NonterminalValue,
+          T:  TerminalValue
+{
+    //
+    // Interface
+    //
+
+    #[must_use]
+    pub fn new<S>(name: S) -> Self
+        where S: Into<String>
+    {
+        Self{
+            name:      name.into(),
+            rules:     Vec::new(),
+            exports:   Vec::new(),
+            imports:   Vec::new(),
+            overrides: Vec::new()
+        }
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub fn with_new_rule<Ss>(self, pattern: Nt, replacement: Ss) -> Self
+        where Ss: IntoIterator<Item = Symbol<Nt, T>>
+    {
+        self.with_rule(Rule::new(pattern, replacement.into_iter().collect()))
+    }
+
+    #[must_use]
+    pub fn with_rule(mut self, rule: Rule<Nt, T>) -> Self {
+        self.rules.push(rule);
+
+        self
+    }
+
+    #[must_use]
+    pub fn with_rules<Rs>(mut self, rules: Rs) -> Self
+        where Rs: IntoIterator<Item = Rule<Nt, T>>
+    {
+        self.rules.extend(rules);
+
+        self
+    }
+
+    /// Makes the module's own non-terminal symbol `name` available for import by other modules.
+    #[must_use]
+    pub fn with_export(mut self, name: Nt) -> Self {
+        self.exports.push(name);
+
+        self
+    }
+
+    /// Makes the non-terminal symbol `name` exported by module `module` available in this module under the same name.
+    #[must_use]
+    pub fn with_import<S>(self, module: S, name: Nt) -> Self
+        where S: Into<String>
+    {
+        let local_name = name.clone();
+
+        self.with_import_as(module, name, local_name)
+    }
+
+    /// Makes the non-terminal symbol `name` exported by module `module` available in this module as `local_name`.
+    #[must_use]
+    pub fn with_import_as<S>(mut self, module: S, name: Nt, local_name: Nt) -> Self
+        where S: Into<String>
+    {
+        self.imports.push(Import{
+            module: module.into(),
+            name,
+            local_name
+        });
+
+        self
+    }
+
+    /// Adds a rule for an imported non-terminal symbol, whose pattern is the local name of an import.
+    /// Non-terminal symbols in the rule's replacement are resolved in this module.
+    ///
+    /// The override applies to the imported non-terminal symbol wherever it is used, not only in this module.
+    #[must_use]
+    pub fn with_override(mut self, mode: OverrideMode, rule: Rule<Nt, T>) -> Self {
+        self.overrides.push((mode, rule));
+
+        self
+    }
+
+    //
+    // Service
+    //
+
+    fn find_import(&self, local_name: &Nt) -> Option<&Import<Nt>> {
+        self.imports.iter().find(|import| import.local_name == *local_name)
+    }
+
+    fn defines(&self, name: &Nt) -> bool {
+        self.rules.iter().any(|rule| rule.pattern == *name)
+    }
+
+    fn qualify(&self, name: &Nt) -> QualifiedNonterm<Nt> {
+        match self.find_import(name) {
+            Some(import) => QualifiedNonterm::new(import.module.clone(), import.name.clone()),
+            None         => QualifiedNonterm::new(self.name.clone(), name.clone())
+        }
+    }
+
+    fn qualify_replacement(&self, replacement: &[Symbol<Nt, T>]) -> Vec<Symbol<QualifiedNonterm<Nt>, T>> {
+        replacement.iter()
+            .map(|symbol| match symbol {
+                Symbol::Nonterminal(value) => Symbol::Nonterminal(self.qualify(value)),
+                Symbol::Terminal(value)    => Symbol::Terminal(value.clone())
+            })
+            .collect()
+    }
+}
+
+//
+// GrammarLinker<Nt, T>
+//
+
+/// Combines [`GrammarModule`](struct.GrammarModule.html)s into a single set of rules,
+/// suitable for [`ExpanderBuilder`](struct.ExpanderBuilder.html),
+/// whose non-terminal symbols are [`QualifiedNonterm`](struct.QualifiedNonterm.html)s.
+pub struct GrammarLinker<Nt, T> {
+    modules: Vec<GrammarModule<Nt, T>>
+}
+
+impl<Nt, T> Default for GrammarLinker<Nt, T>
+    where Nt: NonterminalValue,
+          T:  TerminalValue
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Nt, T> GrammarLinker<Nt, T>
+    where Nt: NonterminalValue,
+          T:  TerminalValue
+{
+    //
+    // Interface
+    //
+
+    #[must_use]
+    pub fn new() -> Self {
+        Self{modules: Vec::new()}
+    }
+
+    #[must_use]
+    pub fn with_module(mut self, module: GrammarModule<Nt, T>) -> Self {
+        self.modules.push(module);
+
+        self
+    }
+
+    /// Resolves imports and overrides of all modules and returns the combined rules,
+    /// grouped by module in the order, in which modules were added.
+    ///
+    /// # Errors
+    /// Will result in a [`LinkError`](enum.LinkError.html) if module names are not unique,
+    /// if an import refers to an unknown module or to a non-terminal symbol not exported by it,
+    /// if a non-terminal symbol is used or exported, but has no rules,
+    /// if an override does not refer to an import, or if several modules replace rules of the same non-terminal symbol.
+    pub fn link(self) -> Result<Vec<Rule<QualifiedNonterm<Nt>, T>>, LinkError<Nt>> {
+        self.validate()?;
+
+        let overrides: Vec<_> = self.modules.iter()
+            .flat_map(|module| {
+                module.overrides.iter()
+                    .map(move |(mode, rule)| (*mode, module.qualify(&rule.pattern), module, rule))
+            })
+            .collect();
+
+        let mut rules = Vec::new();
+
+        for module in &self.modules {
+            for rule in &module.rules {
+                let pattern = QualifiedNonterm::new(module.name.clone(), rule.pattern.clone());
+
+                let is_replaced = overrides.iter()
+                    .any(|(mode, target, _, _)| *mode == OverrideMode::Replace && *target == pattern);
+
+                if !is_replaced {
+                    rules.push(Rule{
+                        modifiers: rule.modifiers.clone(),
+                        ..Rule::new(pattern, module.qualify_replacement(&rule.replacement)).with_weight(rule.weight)
+                    });
+                }
+            }
+
+            for (_, target, overriding_module, rule) in &overrides {
+                if target.module == module.name {
+                    rules.push(Rule{
+                        modifiers: rule.modifiers.clone(),
+                        ..Rule::new(target.clone(), overriding_module.qualify_replacement(&rule.replacement)).with_weight(rule.weight)
+                    });
+                }
+            }
+        }
+
+        Ok(rules)
+    }
+
+    //
+    // Service
+    //
+
+    fn find_module(&self, name: &str) -> Option<&GrammarModule<Nt, T>> {
+        self.modules.iter().find(|module| module.name == name)
+    }
+
+    fn validate(&self) -> Result<(), LinkError<Nt>> {
+        for (module_idx, module) in self.modules.iter().enumerate() {
+            if self.modules[..module_idx].iter().any(|other_module| other_module.name == module.name) {
+                return Err(LinkError::DuplicateModule(module.name.clone()));
+            }
+
+            for (import_idx, import) in module.imports.iter().enumerate() {
+                let imported_module = self.find_module(&import.module)
+                    .ok_or_else(|| LinkError::UnknownModule{
+                        module:          module.name.clone(),
+                        imported_module: import.module.clone()
+                    })?;
+
+                if !imported_module.exports.contains(&import.name) {
+                    return Err(LinkError::NotExported{
+                        module:          module.name.clone(),
+                        imported_module: import.module.clone(),
+                        name:            import.name.clone()
+                    });
+                }
+
+                let is_conflicting = module.imports[..import_idx].iter()
+                    .any(|other_import| other_import.local_name == import.local_name)
+                    || module.defines(&import.local_name);
+
+                if is_conflicting {
+                    return Err(LinkError::ConflictingImport{
+                        module:     module.name.clone(),
+                        local_name: import.local_name.clone()
+                    });
+                }
+            }
+
+            for export in &module.exports {
+                if !module.defines(export) {
+                    return Err(LinkError::UndefinedNonterm{
+                        module: module.name.clone(),
+                        name:   export.clone()
+                    });
+                }
+            }
+
+            let used_names = module.rules.iter()
+                .chain(module.overrides.iter().map(|(_, rule)| rule))
+                .flat_map(|rule| rule.replacement.iter())
+                .filter_map(|symbol| match symbol {
+                    Symbol::Nonterminal(value) => Some(value),
+                    Symbol::Terminal(_)        => None
+                });
+
+            for name in used_names {
+                if module.find_import(name).is_none() && !module.defines(name) {
+                    return Err(LinkError::UndefinedNonterm{
+                        module: module.name.clone(),
+                        name:   name.clone()
+                    });
+                }
+            }
+
+            for (mode, rule) in &module.overrides {
+                if module.find_import(&rule.pattern).is_none() {
+                    return Err(LinkError::InvalidOverride{
+                        module: module.name.clone(),
+                        name:   rule.pattern.clone()
+                    });
+                }
+
+                let target = module.qualify(&rule.pattern);
+
+                let conflicting_module = self.modules[..module_idx].iter()
+                    .find(|other_module| {
+                        other_module.overrides.iter().any(|(other_mode, other_rule)| {
+                            *mode == OverrideMode::Replace
+                                && *other_mode == OverrideMode::Replace
+                                && other_module.qualify(&other_rule.pattern) == target
+                        })
+                    });
+
+                if let Some(conflicting_module) = conflicting_module {
+                    return Err(LinkError::ConflictingOverrides{
+                        target,
+                        modules: (conflicting_module.name.clone(), module.name.clone())
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+//
+// LinkError<Nt>: Debug + Display + Error
+//
+
+/// Describes why [`GrammarLinker::link()`](struct.GrammarLinker.html#method.link) failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinkError<Nt> {
+    /// Several modules have the same name.
+    DuplicateModule(String),
+    /// `module` imports from `imported_module`, which has not been added to the linker.
+    UnknownModule{
+        module:          String,
+        imported_module: String
+    },
+    /// `module` imports `name` from `imported_module`, which does not export it.
+    NotExported{
+        module:          String,
+        imported_module: String,
+        name:            Nt
+    },
+    /// `module` imports several non-terminal symbols as `local_name` or has its own rules for `local_name`.
+    ConflictingImport{
+        module:     String,
+        local_name: Nt
+    },
+    /// `module` uses or exports `name`, which neither has rules in `module`, nor is imported.
+    UndefinedNonterm{
+        module: String,
+        name:   Nt
+    },
+    /// `module` overrides `name`, which is not imported.
+    InvalidOverride{
+        module: String,
+        name:   Nt
+    },
+    /// Both `modules` replace rules of `target`.
+    ConflictingOverrides{
+        target:  QualifiedNonterm<Nt>,
+        modules: (String, String)
+    }
+}
+
+impl<Nt> Display for LinkError<Nt>
+    where Nt: Debug
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateModule(module) => write!(
+                f, "module {:?} is defined more than once", module
+            ),
+            Self::UnknownModule{module, imported_module} => write!(
+                f, "module {:?} imports from unknown module {:?}", module, imported_module
+            ),
+            Self::NotExported{module, imported_module, name} => write!(
+                f, "module {:?} imports {:?}, which is not exported by module {:?}", module, name, imported_module
+            ),
+            Self::ConflictingImport{module, local_name} => write!(
+                f, "{:?} is imported more than once or also defined in module {:?}", local_name, module
+            ),
+            Self::UndefinedNonterm{module, name} => write!(
+                f, "nonterminal symbol {:?} is neither defined in, nor imported by module {:?}", name, module
+            ),
+            Self::InvalidOverride{module, name} => write!(
+                f, "module {:?} overrides {:?}, which it does not import", module, name
+            ),
+            Self::ConflictingOverrides{target, modules: (first_module, second_module)} => write!(
+                f,
+                "both module {:?} and module {:?} replace rules of {:?} from module {:?}",
+                first_module,
+                second_module,
+                target.name,
+                target.module
+            )
+        }
+    }
+}
+
+impl<Nt> std::error::Error for LinkError<Nt>
+    where Nt: Debug
+{
+    // Default
+}
+
+//
+// Service types
+//
+
+struct Import<Nt> {
+    module:     String,
+    name:       Nt,
+    local_name: Nt
+}
@@ -0,0 +1,322 @@
+use rand::Rng;
+
+use crate::grammar::{
+    NonterminalValue,
+    TerminalValue,
+    Symbol,
+    Rule,
+    indexed::IndexedGrammar
+};
+use crate::derivation::{
+    Derivation,
+    DerivationTree,
+    random::RandomDerivationGenerator,
+    nonterm_paths,
+    subtree_at,
+    subtree_at_mut
+};
+
+//
+// Constants
+//
+
+const DEFAULT_MAX_NODE_COUNT: usize = 64;
+
+//
+// Interface types
+//
+
+//
+// DerivationMutator<'r, Nt, T>
+//
+
+/// Produces mutants of existing [`Derivation`](struct.Derivation.html)s, which are still valid derivations
+/// of the same input using the same rules, e.g. for grammar-based fuzzing.
+///
+/// The following mutations are supported:
+/// * [`regenerate_subtree()`](#method.regenerate_subtree) replaces a random subtree with a newly generated random one,
+/// * [`splice_subtree()`](#method.splice_subtree) replaces a random subtree with a subtree for the same non-terminal
+///   symbol from another derivation,
+/// * [`duplicate_recursion()`](#method.duplicate_recursion) repeats a random recursive part of the derivation,
+///   i.e. a subtree containing a deeper subtree for the same non-terminal symbol, once more.
+///
+/// All derivations passed to a mutator are expected to use the rules, with which it was created,
+/// e.g. be returned by [`Expander::expand_derivation()`](struct.Expander.html#method.expand_derivation)
+/// or [`CoverageGenerator`](struct.CoverageGenerator.html) for the same rules.
+///
+/// ```
+/// use branchy::{
+///     Symbol,
+///     Rule,
+///     ExpanderBuilder,
+///     DerivationMutator
+/// };
+///
+/// let rules = vec![
+///     Rule::new("list", vec![Symbol::Terminal("item")]),
+///     Rule::new("list", vec![Symbol::Terminal("item"), Symbol::Terminal(","), Symbol::Nonterminal("list")])
+/// ];
+///
+/// let mut expander = ExpanderBuilder::from(rules.clone()).build();
+///
+/// let derivation = expander.expand_derivation(vec![Symbol::Nonterminal("list")]).unwrap();
+///
+/// let mutator = DerivationMutator::new(&rules);
+///
+/// let mutant = mutator.mutate(&derivation, &[], &mut rand::thread_rng()).unwrap();
+///
+/// assert_eq!(mutant.terminals().len() % 2, 1);
+/// ```
+pub struct DerivationMutator<'r, Nt, T> {
+    generator:      RandomDerivationGenerator<'r, Nt, T>,
+    max_node_count: usize
+}
+
+impl<'r, Nt, T> DerivationMutator<'r, Nt, T>
+    where Nt: NonterminalValue,
+          T:  TerminalValue
+{
+    //
+    // Interface
+    //
+
+    #[must_use]
+    pub fn new(rules: &'r [Rule<Nt, T>]) -> Self {
+        Self{
+            generator:      RandomDerivationGenerator::new(IndexedGrammar::new(rules)),
+            max_node_count: DEFAULT_MAX_NODE_COUNT
+        }
+    }
+
+    /// Sets roughly the maximal number of nodes, generated using randomly selected rules by
+    /// [`generate()`](#method.generate) and [`regenerate_subtree()`](#method.regenerate_subtree), after which the remaining non-terminal symbols
+    /// are expanded as briefly as possible.
+    #[must_use]
+    pub fn with_max_node_count(self, max_node_count: usize) -> Self {
+        Self{
+            max_node_count,
+            ..self
+        }
+    }
+
+    /// Generates a random derivation of `input`, e.g. to seed a corpus of derivations to mutate.
+    /// Unlike [`Expander::expand_derivation()`](struct.Expander.html#method.expand_derivation),
+    /// this always terminates, since non-terminal symbols are expanded as briefly as possible after the maximal
+    /// number of nodes is reached.
+    ///
+    /// Returns `None` if `input` cannot be fully expanded using the rules.
+    pub fn generate<R>(&self, input: &[Symbol<Nt, T>], rng: &mut R) -> Option<Derivation<Nt, T>>
+        where R: Rng + ?Sized
+    {
+        let budget = rng.gen_range(0, self.max_node_count + 1);
+
+        self.generator.generate(input, budget, &mut |option_count| rng.gen_range(0, option_count))
+    }
+
+    /// Applies one of the supported mutations, chosen at random, to `derivation`.
+    /// Subtrees to splice are taken from a random derivation among `donors`, if there are any.
+    ///
+    /// Returns `None` if none of the mutations are applicable.
+    pub fn mutate<R>(
+        &self,
+        derivation: &Derivation<Nt, T>,
+        donors:     &[Derivation<Nt, T>],
+        rng:        &mut R
+    ) -> Option<Derivation<Nt, T>>
+        where R: Rng + ?Sized
+    {
+        const MUTATION_COUNT: usize = 3;
+
+        let first_mutation_idx = rng.gen_range(0, MUTATION_COUNT);
+
+        (0..MUTATION_COUNT)
+            .map(|offset| (first_mutation_idx + offset) % MUTATION_COUNT)
+            .find_map(|mutation_idx| match mutation_idx {
+                0 => self.regenerate_subtree(derivation, rng),
+                1 if !donors.is_empty() => {
+                    let donor = &donors[rng.gen_range(0, donors.len())];
+
+                    self.splice_subtree(derivation, donor, rng)
+                },
+                1 => None,
+                _ => self.duplicate_recursion(derivation, rng)
+            })
+    }
+
+    /// Replaces a random subtree of `derivation` with a newly generated random subtree for the same non-terminal symbol.
+    /// The new subtree may happen to coincide with the replaced one.
+    ///
+    /// Returns `None` if `derivation` contains no non-terminal symbols, which can be expanded using the rules.
+    pub fn regenerate_subtree<R>(&self, derivation: &Derivation<Nt, T>, rng: &mut R) -> Option<Derivation<Nt, T>>
+        where R: Rng + ?Sized
+    {
+        let regenerable_paths: Vec<_> = nonterm_paths(derivation).into_iter()
+            .filter_map(|path| {
+                self.productive_nonterm_idx(subtree_at(derivation, &path))
+                    .map(|nonterm_idx| (path, nonterm_idx))
+            })
+            .collect();
+
+        if regenerable_paths.is_empty() {
+            return None;
+        }
+
+        let (path, nonterm_idx) = &regenerable_paths[rng.gen_range(0, regenerable_paths.len())];
+
+        let mut budget = rng.gen_range(0, self.max_node_count + 1);
+
+        let subtree = self.generator.generate_tree(*nonterm_idx, &mut budget, &mut |option_count| {
+            rng.gen_range(0, option_count)
+        });
+
+        let mut mutant = derivation.clone();
+
+        *subtree_at_mut(&mut mutant, path) = subtree;
+
+        Some(mutant)
+    }
+
+    /// Replaces a random subtree of `derivation` with a random subtree of `donor` for the same non-terminal symbol.
+    ///
+    /// Returns `None` if `derivation` and `donor` have no non-terminal symbols in common.
+    pub fn splice_subtree<R>(
+        &self,
+        derivation: &Derivation<Nt, T>,
+        donor:      &Derivation<Nt, T>,
+        rng:        &mut R
+    ) -> Option<Derivation<Nt, T>>
+        where R: Rng + ?Sized
+    {
+        let donor_paths = nonterm_paths(donor);
+
+        let splicable_paths: Vec<_> = nonterm_paths(derivation).into_iter()
+            .filter(|path| {
+                let value = nonterm_value(subtree_at(derivation, path));
+
+                donor_paths.iter().any(|donor_path| nonterm_value(subtree_at(donor, donor_path)) == value)
+            })
+            .collect();
+
+        if splicable_paths.is_empty() {
+            return None;
+        }
+
+        let path  = &splicable_paths[rng.gen_range(0, splicable_paths.len())];
+        let value = nonterm_value(subtree_at(derivation, path));
+
+        let matching_donor_paths: Vec<_> = donor_paths.iter()
+            .filter(|donor_path| nonterm_value(subtree_at(donor, donor_path)) == value)
+            .collect();
+
+        let donor_path = matching_donor_paths[rng.gen_range(0, matching_donor_paths.len())];
+
+        let mut mutant = derivation.clone();
+
+        *subtree_at_mut(&mut mutant, path) = subtree_at(donor, donor_path).clone();
+
+        Some(mutant)
+    }
+
+    /// Picks a random subtree of `derivation`, which contains a deeper subtree for the same non-terminal symbol,
+    /// and replaces that deeper subtree with a copy of the outer one, so that the recursion is repeated once more.
+    ///
+    /// Returns `None` if `derivation` is not recursive.
+    pub fn duplicate_recursion<R>(&self, derivation: &Derivation<Nt, T>, rng: &mut R) -> Option<Derivation<Nt, T>>
+        where R: Rng + ?Sized
+    {
+        let mut paths = nonterm_paths(derivation);
+
+        // Nodes are tried in random order, until one of them contains a deeper node for the same non-terminal symbol.
+        while !paths.is_empty() {
+            let path = paths.swap_remove(rng.gen_range(0, paths.len()));
+
+            let outer_tree = subtree_at(derivation, &path);
+
+            let descendant_path = if let Some(descendant_path) = sample_recursion_path(outer_tree, rng) {
+                descendant_path
+            } else {
+                continue;
+            };
+
+            let mut duplicated_tree = outer_tree.clone();
+
+            *duplicated_tree.subtree_mut(&descendant_path) = outer_tree.clone();
+
+            let mut mutant = derivation.clone();
+
+            *subtree_at_mut(&mut mutant, &path) = duplicated_tree;
+
+            return Some(mutant);
+        }
+
+        None
+    }
+
+    //
+    // Service
+    //
+
+    fn productive_nonterm_idx(&self, tree: &DerivationTree<Nt, T>) -> Option<usize> {
+        match tree {
+            DerivationTree::Nonterminal{value, ..} => self.generator.grammar().nonterm_idx(value)
+                .filter(|nonterm_idx| self.generator.is_productive(*nonterm_idx)),
+            DerivationTree::Terminal(_) => None
+        }
+    }
+}
+
+//
+// Service
+//
+
+/// Returns the path of a uniformly chosen proper descendant of `tree` for the same non-terminal symbol,
+/// relative to `tree`, or `None` if there is no such descendant.
+fn sample_recursion_path<Nt, T, R>(tree: &DerivationTree<Nt, T>, rng: &mut R) -> Option<Vec<usize>>
+    where Nt: PartialEq,
+          R:  Rng + ?Sized
+{
+    let value = nonterm_value(tree)?;
+
+    let mut sampled_path = None;
+
+    sample_descendant_path(tree, value, &mut Vec::new(), &mut 0, &mut sampled_path, rng);
+
+    sampled_path
+}
+
+/// Visits proper descendants of `tree` equal to `value`, keeping the path of each one in `sampled_path`
+/// with the probability of `1 / match_count`, so that every one of them is equally likely to be kept.
+fn sample_descendant_path<Nt, T, R>(
+    tree:         &DerivationTree<Nt, T>,
+    value:        &Nt,
+    path:         &mut Vec<usize>,
+    match_count:  &mut usize,
+    sampled_path: &mut Option<Vec<usize>>,
+    rng:          &mut R
+)
+    where Nt: PartialEq,
+          R:  Rng + ?Sized
+{
+    for (child_idx, child) in tree.children().iter().enumerate() {
+        path.push(child_idx);
+
+        if nonterm_value(child) == Some(value) {
+            *match_count += 1;
+
+            if rng.gen_range(0, *match_count) == 0 {
+                *sampled_path = Some(path.clone());
+            }
+        }
+
+        sample_descendant_path(child, value, path, match_count, sampled_path, rng);
+
+        path.pop();
+    }
+}
+
+fn nonterm_value<Nt, T>(tree: &DerivationTree<Nt, T>) -> Option<&Nt> {
+    match tree {
+        DerivationTree::Nonterminal{value, ..} => Some(value),
+        DerivationTree::Terminal(_)            => None
+    }
+}
@@ -0,0 +1,501 @@
+use std::fmt::{
+    self,
+    Display
+};
+
+use crate::grammar::{
+    NonterminalValue,
+    Symbol,
+    Rule,
+    Modifier,
+    write_pattern,
+    write_replacement
+};
+
+//
+// Interface types
+//
+
+//
+// GrammarDisplay<'r, Nt, T>: Display
+//
+
+/// Formats a whole grammar in the notation of [`Rule`](struct.Rule.html)'s `Display` implementation,
+/// with one line per non-terminal symbol, listing alternative replacements separated by `|`, e.g.
+///
+/// ```text
+/// greeting -> "hello" <name> | "hi" [0.5]
+/// name -> "Alice" | "Bob"
+/// ```
+///
+/// Lines are ordered by the first rule for every non-terminal symbol, so rules are regrouped,
+/// if rules for different non-terminal symbols are interleaved.
+///
+/// Returned by [`display_grammar()`](fn.display_grammar.html).
+pub struct GrammarDisplay<'r, Nt, T> {
+    rules: &'r [Rule<Nt, T>]
+}
+
+impl<'r, Nt, T> Display for GrammarDisplay<'r, Nt, T>
+    where Nt: NonterminalValue + Display,
+          T:  Display
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (rule_idx, rule) in self.rules.iter().enumerate() {
+            if self.rules[..rule_idx].iter().any(|other_rule| other_rule.pattern == rule.pattern) {
+                continue;
+            }
+
+            write_pattern(f, &rule.pattern)?;
+            write!(f, " ->")?;
+
+            let alternatives = self.rules[rule_idx..].iter()
+                .filter(|other_rule| other_rule.pattern == rule.pattern);
+
+            for (alternative_idx, alternative) in alternatives.enumerate() {
+                write!(f, "{}", if alternative_idx > 0 { " | " } else { " " })?;
+
+                write_replacement(f, alternative)?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+//
+// ParseErrorKind: Debug + Clone + PartialEq
+//
+
+/// Enumerates kinds of errors reported by [`parse_grammar()`](fn.parse_grammar.html).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    /// A line, which does not start with `|`, contains no `->`.
+    MissingArrow,
+    /// The pattern before `->` is empty and not enclosed in angle brackets.
+    EmptyPattern,
+    /// A line starts with `|`, but there are no preceding rules to add alternatives to.
+    ContinuationWithoutRule,
+    /// There are no symbols (not even `ε`) between `->` and `|`, between two `|` or after the last one.
+    EmptyAlternative,
+    /// A non-terminal symbol misses its closing `>`, a terminal symbol misses its closing `"`
+    /// or a weight misses its closing `]`.
+    UnterminatedSymbol,
+    /// The contents of square brackets following a replacement are not a valid number.
+    InvalidWeight(String),
+    /// A backslash followed by `u` within a symbol is not followed by a valid Unicode code point
+    /// in hexadecimal notation enclosed in braces, e.g. `\u{7f}`.
+    InvalidEscape(String),
+    /// A character, which cannot start a symbol, `ε`, a weight or a comment, or a dot not followed by a modifier name.
+    UnexpectedCharacter(char)
+}
+
+//
+// ParseError: Error
+//
+
+/// Describes why [`parse_grammar()`](fn.parse_grammar.html) failed and where.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// Line number, starting from 1.
+    pub line:   usize,
+    /// Column number in characters, starting from 1.
+    pub column: usize,
+    pub kind:   ParseErrorKind
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+
+        match &self.kind {
+            ParseErrorKind::MissingArrow            => write!(f, "expected \"->\" after rule pattern"),
+            ParseErrorKind::EmptyPattern            => write!(f, "rule pattern must not be empty"),
+            ParseErrorKind::ContinuationWithoutRule => write!(f, "\"|\" must follow a rule"),
+            ParseErrorKind::EmptyAlternative        => write!(f, "expected symbols or \"ε\""),
+            ParseErrorKind::UnterminatedSymbol      => write!(f, "symbol is not terminated"),
+            ParseErrorKind::InvalidWeight(weight)   => write!(f, "invalid rule weight \"{}\"", weight),
+            ParseErrorKind::InvalidEscape(escape)   => write!(f, "invalid escape sequence \"{}\"", escape),
+            ParseErrorKind::UnexpectedCharacter(c)  => write!(f, "unexpected character '{}'", c)
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    // Default
+}
+
+//
+// Interface
+//
+
+/// Returns a [`GrammarDisplay`](struct.GrammarDisplay.html), which formats `rules` grouped by pattern.
+///
+/// ```
+/// use branchy::{
+///     Symbol,
+///     Rule,
+///     display_grammar
+/// };
+///
+/// let rules = vec![
+///     Rule::new("greeting", vec![Symbol::Terminal("hello"), Symbol::Nonterminal("name")]),
+///     Rule::new("name", vec![Symbol::Terminal("Alice")]),
+///     Rule::new("greeting", vec![Symbol::Terminal("hi")]).with_weight(0.5)
+/// ];
+///
+/// assert_eq!(
+///     display_grammar(&rules).to_string(),
+///     "greeting -> \"hello\" <name> | \"hi\" [0.5]\nname -> \"Alice\"\n"
+/// );
+/// ```
+#[must_use]
+pub fn display_grammar<Nt, T>(rules: &[Rule<Nt, T>]) -> GrammarDisplay<'_, Nt, T> {
+    GrammarDisplay{rules}
+}
+
+/// Parses rules written in the notation produced by [`display_grammar()`](fn.display_grammar.html)
+/// and [`Rule`](struct.Rule.html)'s `Display` implementation.
+///
+/// * Every rule line consists of a pattern, `->` and one or more alternative replacements separated by `|`.
+///   A pattern may optionally be enclosed in angle brackets.
+/// * A line starting with `|` adds more alternatives to the pattern of the preceding line.
+/// * A replacement is a sequence of non-terminal symbols `<value>` and terminal symbols `"value"`,
+///   or `ε` for an empty replacement. Within symbols, `\n`, `\r`, `\t` and `\u{7f}` stand for control characters
+///   and `\` escapes any other character.
+/// * Every symbol may be immediately followed by names of [`Modifier`](struct.Modifier.html)s attached to it,
+///   each preceded by a dot, e.g. `<animal>.s.capitalize`. Names with characters other than letters, digits
+///   and `_` are enclosed in double quotes, e.g. `<title>."title case"`.
+/// * A replacement may be followed by its weight in square brackets, e.g. `[0.5]`.
+///   Like [`Rule::with_weight()`](struct.Rule.html#method.with_weight), any number is accepted, e.g. `[-1]` or `[NaN]`.
+/// * Everything from `#` outside of symbols to the end of the line is a comment. Empty lines are ignored.
+///
+/// ```
+/// use branchy::{
+///     Symbol,
+///     Rule,
+///     parse_grammar
+/// };
+///
+/// let rules = parse_grammar(r#"
+///     ## Greetings
+///     greeting -> "hello" <name> | "hi" [0.5]
+///              | ε
+///     name -> "Alice"
+/// "#).unwrap();
+///
+/// assert_eq!(
+///     rules,
+///     vec![
+///         Rule::new("greeting".to_string(), vec![Symbol::Terminal("hello".to_string()), Symbol::Nonterminal("name".to_string())]),
+///         Rule::new("greeting".to_string(), vec![Symbol::Terminal("hi".to_string())]).with_weight(0.5),
+///         Rule::new("greeting".to_string(), vec![]),
+///         Rule::new("name".to_string(), vec![Symbol::Terminal("Alice".to_string())])
+///     ]
+/// );
+/// ```
+///
+/// # Errors
+/// Will result in a [`ParseError`](struct.ParseError.html) describing the first syntax error in `text`.
+pub fn parse_grammar(text: &str) -> Result<Vec<Rule<String, String>>, ParseError> {
+    let mut rules = Vec::new();
+
+    let mut current_pattern: Option<String> = None;
+
+    for (line_idx, line) in text.lines().enumerate() {
+        let mut cursor = Cursor::new(line, line_idx + 1);
+
+        cursor.skip_whitespace();
+
+        if cursor.is_at_end_of_line() {
+            continue;
+        }
+
+        let pattern = if cursor.peek() == Some('|') {
+            let pattern = current_pattern.clone()
+                .ok_or_else(|| cursor.error(ParseErrorKind::ContinuationWithoutRule))?;
+
+            cursor.next();
+
+            pattern
+        } else {
+            let pattern = cursor.parse_pattern()?;
+
+            current_pattern = Some(pattern.clone());
+
+            pattern
+        };
+
+        loop {
+            rules.push(cursor.parse_alternative(&pattern)?);
+
+            if cursor.is_at_end_of_line() {
+                break;
+            }
+
+            // Anything other than the end of the line would have been rejected by parse_alternative().
+            cursor.next();
+        }
+    }
+
+    Ok(rules)
+}
+
+//
+// Service types
+//
+
+//
+// Cursor
+//
+
+struct Cursor {
+    chars: Vec<char>,
+    pos:   usize,
+    line:  usize
+}
+
+impl Cursor {
+    fn new(line_text: &str, line: usize) -> Self {
+        Self{
+            chars: line_text.chars().collect(),
+            pos:   0,
+            line
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+
+        if c.is_some() {
+            self.pos += 1;
+        }
+
+        c
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// Returns `true` at the end of the line or at the start of a comment.
+    fn is_at_end_of_line(&self) -> bool {
+        matches!(self.peek(), None | Some('#'))
+    }
+
+    fn error(&self, kind: ParseErrorKind) -> ParseError {
+        self.error_at(self.pos, kind)
+    }
+
+    fn error_at(&self, pos: usize, kind: ParseErrorKind) -> ParseError {
+        ParseError{
+            line:   self.line,
+            column: pos + 1,
+            kind
+        }
+    }
+
+    /// Parses the pattern along with the following `->`.
+    fn parse_pattern(&mut self) -> Result<String, ParseError> {
+        let pattern_pos = self.pos;
+
+        let pattern = if self.peek() == Some('<') {
+            self.next();
+
+            let pattern = self.parse_delimited('>', pattern_pos)?;
+
+            self.skip_whitespace();
+
+            if !self.chars[self.pos..].starts_with(&['-', '>']) {
+                return Err(self.error(ParseErrorKind::MissingArrow));
+            }
+
+            pattern
+        } else {
+            let arrow_pos = self.chars[self.pos..].windows(2)
+                .position(|pair| pair == ['-', '>'])
+                .map(|offset| self.pos + offset)
+                .ok_or_else(|| self.error_at(self.chars.len(), ParseErrorKind::MissingArrow))?;
+
+            let pattern: String = self.chars[self.pos..arrow_pos].iter().collect();
+
+            self.pos = arrow_pos;
+
+            let pattern = pattern.trim_end().to_string();
+
+            // Only `<>` stands for an empty pattern.
+            if pattern.is_empty() {
+                return Err(self.error_at(pattern_pos, ParseErrorKind::EmptyPattern));
+            }
+
+            pattern
+        };
+
+        self.pos += 2;
+
+        Ok(pattern)
+    }
+
+    /// Parses a replacement with optional modifiers and weight, stopping before `|` or at the end of the line.
+    fn parse_alternative(&mut self, pattern: &str) -> Result<Rule<String, String>, ParseError> {
+        let mut replacement = Vec::new();
+        let mut modifiers   = Vec::new();
+        let mut is_empty    = false;
+        let mut weight      = None;
+
+        loop {
+            self.skip_whitespace();
+
+            let symbol_pos = self.pos;
+
+            match self.peek() {
+                None | Some('#') | Some('|') => break,
+                Some(c) if weight.is_some() => {
+                    return Err(self.error(ParseErrorKind::UnexpectedCharacter(c)));
+                },
+                Some('<') if !is_empty => {
+                    self.next();
+
+                    replacement.push(Symbol::Nonterminal(self.parse_delimited('>', symbol_pos)?));
+
+                    self.parse_modifiers(replacement.len() - 1, &mut modifiers)?;
+                },
+                Some('"') if !is_empty => {
+                    self.next();
+
+                    replacement.push(Symbol::Terminal(self.parse_delimited('"', symbol_pos)?));
+
+                    self.parse_modifiers(replacement.len() - 1, &mut modifiers)?;
+                },
+                Some('ε') if !is_empty && replacement.is_empty() => {
+                    self.next();
+
+                    is_empty = true;
+                },
+                Some('[') if is_empty || !replacement.is_empty() => {
+                    self.next();
+
+                    weight = Some(self.parse_weight(symbol_pos)?);
+                },
+                Some(c) => {
+                    return Err(self.error(ParseErrorKind::UnexpectedCharacter(c)));
+                }
+            }
+        }
+
+        if !is_empty && replacement.is_empty() {
+            return Err(self.error(ParseErrorKind::EmptyAlternative));
+        }
+
+        Ok(Rule{
+            modifiers,
+            ..Rule::new(pattern.to_string(), replacement).with_weight(weight.unwrap_or(1.0))
+        })
+    }
+
+    /// Parses modifiers immediately following the symbol at `symbol_idx`, e.g. `.s.capitalize` or `."title case"`.
+    fn parse_modifiers(&mut self, symbol_idx: usize, modifiers: &mut Vec<Modifier>) -> Result<(), ParseError> {
+        while self.peek() == Some('.') {
+            let dot_pos = self.pos;
+
+            self.next();
+
+            let mut name = String::new();
+
+            if self.peek() == Some('"') {
+                self.next();
+
+                name = self.parse_delimited('"', dot_pos)?;
+            } else {
+                while let Some(c) = self.peek().filter(|c| c.is_alphanumeric() || *c == '_') {
+                    name.push(c);
+
+                    self.next();
+                }
+
+                if name.is_empty() {
+                    return Err(self.error_at(dot_pos, ParseErrorKind::UnexpectedCharacter('.')));
+                }
+            }
+
+            modifiers.push(Modifier{
+                symbol_idx,
+                name
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Parses the rest of a symbol, which started at `start_pos`, up to the closing `delimiter`.
+    fn parse_delimited(&mut self, delimiter: char, start_pos: usize) -> Result<String, ParseError> {
+        let mut value = String::new();
+
+        loop {
+            match self.next() {
+                Some(c) if c == delimiter => return Ok(value),
+                Some('\\') => match self.next() {
+                    Some('n') => value.push('\n'),
+                    Some('r') => value.push('\r'),
+                    Some('t') => value.push('\t'),
+                    Some('u') => value.push(self.parse_unicode_escape()?),
+                    Some(c)   => value.push(c),
+                    None      => break
+                },
+                Some(c) => value.push(c),
+                None    => break
+            }
+        }
+
+        Err(self.error_at(start_pos, ParseErrorKind::UnterminatedSymbol))
+    }
+
+    /// Parses the rest of a `\u{7f}` escape sequence following `\u`.
+    fn parse_unicode_escape(&mut self) -> Result<char, ParseError> {
+        let escape_pos = self.pos - 2;
+
+        if self.next() == Some('{') {
+            let mut code = String::new();
+
+            while let Some(c) = self.next() {
+                if c == '}' {
+                    if let Some(c) = u32::from_str_radix(&code, 16).ok().and_then(char::from_u32) {
+                        return Ok(c);
+                    }
+
+                    break;
+                }
+
+                code.push(c);
+            }
+        }
+
+        let escape = self.chars[escape_pos..self.pos].iter().collect();
+
+        Err(self.error_at(escape_pos, ParseErrorKind::InvalidEscape(escape)))
+    }
+
+    /// Parses the rest of a weight, which started at `start_pos`, up to the closing `]`.
+    fn parse_weight(&mut self, start_pos: usize) -> Result<f64, ParseError> {
+        let mut text = String::new();
+
+        loop {
+            match self.next() {
+                Some(']') => break,
+                Some(c)   => text.push(c),
+                None      => return Err(self.error_at(start_pos, ParseErrorKind::UnterminatedSymbol))
+            }
+        }
+
+        text.trim().parse::<f64>().ok()
+            .ok_or_else(|| self.error_at(start_pos, ParseErrorKind::InvalidWeight(text)))
+    }
+}
@@ -0,0 +1,570 @@
+use crate::grammar::{
+    NonterminalValue,
+    Symbol,
+    Rule,
+    indexed::{
+        IndexedGrammar,
+        strongly_connected_components
+    }
+};
+use crate::derivation::Derivation;
+
+mod training;
+
+pub use training::{
+    WeightTrainer,
+    TrainingReport
+};
+
+//
+// Constants
+//
+
+/// Maximal number of times probabilities for a single span of a sentence are recomputed,
+/// which only takes more than two times for grammars with cycles of empty and unit rules.
+const MAX_INSIDE_ITERATIONS: usize = 1000;
+
+/// Largest change of any inside or outside probability, at which the computation is considered converged.
+const INSIDE_TOLERANCE: f64 = 1e-12;
+
+/// Pivots smaller than this in absolute value are considered zero when solving linear systems.
+const PIVOT_TOLERANCE: f64 = 1e-12;
+
+//
+// Interface types
+//
+
+//
+// enum RuleDistribution: Debug + Clone + Copy + PartialEq + Eq
+//
+
+/// Describes the probabilities, with which rules are selected among the ones matching a non-terminal symbol.
+///
+/// Returned for a rule selector by [`RuleSelector::rule_distribution()`](trait.RuleSelector.html#method.rule_distribution).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleDistribution {
+    /// All matching rules are equally likely to be selected.
+    Uniform,
+    /// Matching rules are selected with probabilities proportional to their [`weight`](struct.Rule.html#structfield.weight)s.
+    /// Rules with non-positive weights are never selected.
+    Weighted
+}
+
+//
+// ProbabilisticGrammar<'r, Nt, T>
+//
+
+/// Treats a set of rules as a probabilistic grammar, in which every non-terminal symbol is expanded
+/// using a rule selected according to a [`RuleDistribution`](enum.RuleDistribution.html).
+///
+/// Expected output lengths and entropies are computed for every non-terminal symbol once on construction.
+/// They are infinite for non-terminal symbols, whose expansion is expected to never finish, e.g. because
+/// the weights of recursive rules are too high (see [`is_divergent()`](#method.is_divergent)).
+/// Non-terminal symbols without rules are treated as expanding to nothing.
+///
+/// ```
+/// use branchy::{
+///     Symbol,
+///     Rule,
+///     ProbabilisticGrammar,
+///     RuleDistribution
+/// };
+///
+/// let rules = vec![
+///     Rule::new("list", vec![Symbol::Terminal("item")]).with_weight(3.0),
+///     Rule::new("list", vec![Symbol::Terminal("item"), Symbol::Nonterminal("list")])
+/// ];
+///
+/// let grammar = ProbabilisticGrammar::new(&rules, RuleDistribution::Weighted);
+///
+/// assert_eq!(grammar.rule_probability(0), 0.75);
+/// assert!((grammar.expected_length(&"list").unwrap() - 4.0 / 3.0).abs() < 1e-9);
+/// assert!(!grammar.is_divergent(&"list"));
+///
+/// let diverging_rules = vec![
+///     Rule::new("tree", vec![Symbol::Terminal("leaf")]),
+///     Rule::new("tree", vec![Symbol::Nonterminal("tree"), Symbol::Nonterminal("tree")]).with_weight(2.0)
+/// ];
+///
+/// let diverging_grammar = ProbabilisticGrammar::new(&diverging_rules, RuleDistribution::Weighted);
+///
+/// assert!(diverging_grammar.is_divergent(&"tree"));
+/// assert_eq!(diverging_grammar.expected_length(&"tree"), Some(f64::INFINITY));
+/// ```
+pub struct ProbabilisticGrammar<'r, Nt, T> {
+    grammar:              IndexedGrammar<'r, Nt, T>,
+    rule_probabilities:   Vec<f64>,
+    expected_lengths:     Vec<f64>,
+    rule_entropies:       Vec<f64>,
+    derivation_entropies: Vec<f64>,
+    divergent:            Vec<bool>
+}
+
+impl<'r, Nt, T> ProbabilisticGrammar<'r, Nt, T>
+    where Nt: NonterminalValue
+{
+    #[must_use]
+    pub fn new(rules: &'r [Rule<Nt, T>], distribution: RuleDistribution) -> Self {
+        let grammar = IndexedGrammar::new(rules);
+
+        let rule_probabilities = rule_probabilities(&grammar, distribution);
+
+        let mut analysis = Self{
+            rule_probabilities,
+            expected_lengths:     vec![0.0; grammar.nonterm_count()],
+            rule_entropies:       vec![0.0; grammar.nonterm_count()],
+            derivation_entropies: vec![0.0; grammar.nonterm_count()],
+            divergent:            vec![false; grammar.nonterm_count()],
+            grammar
+        };
+
+        analysis.analyze();
+
+        analysis
+    }
+
+    /// Returns the probability of the rule with index `rule_idx` being selected to expand its pattern,
+    /// or `0.0` if there is no such rule.
+    #[must_use]
+    pub fn rule_probability(&self, rule_idx: usize) -> f64 {
+        self.rule_probabilities.get(rule_idx)
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Returns the probability of `derivation`, given its input, i.e. the product of the probabilities
+    /// of all rules it applies.
+    #[must_use]
+    pub fn derivation_probability(&self, derivation: &Derivation<Nt, T>) -> f64 {
+        derivation.rule_idxs().into_iter()
+            .map(|rule_idx| self.rule_probability(rule_idx))
+            .product()
+    }
+
+    /// Returns the probability of `input` being expanded to `sentence`,
+    /// summed over all derivations producing it.
+    #[must_use]
+    pub fn sentence_probability(&self, input: &[Symbol<Nt, T>], sentence: &[T]) -> f64
+        where T: PartialEq
+    {
+        let inside = InsideChart::new(&self.grammar, &self.rule_probabilities, &indexed_rule_symbols(&self.grammar), sentence);
+
+        let input_symbols = indexed_symbols(&self.grammar, input);
+
+        inside.prefix_probabilities(&input_symbols, sentence, 0, sentence.len())[input_symbols.len()][sentence.len()]
+    }
+
+    /// Returns the expected number of terminal values produced by expanding a non-terminal symbol with `nonterm_value`,
+    /// or `None` if it does not occur in the rules.
+    #[must_use]
+    pub fn expected_length(&self, nonterm_value: &Nt) -> Option<f64> {
+        self.grammar.nonterm_idx(nonterm_value)
+            .map(|nonterm_idx| self.expected_lengths[nonterm_idx])
+    }
+
+    /// Returns the Shannon entropy in bits of the selection of a rule to expand a non-terminal symbol with `nonterm_value`,
+    /// or `None` if it does not occur in the rules.
+    #[must_use]
+    pub fn rule_entropy(&self, nonterm_value: &Nt) -> Option<f64> {
+        self.grammar.nonterm_idx(nonterm_value)
+            .map(|nonterm_idx| self.rule_entropies[nonterm_idx])
+    }
+
+    /// Returns the Shannon entropy in bits of complete derivations of a non-terminal symbol with `nonterm_value`,
+    /// i.e. the expected total entropy of all rule selections made while expanding it, or `None` if it does not occur
+    /// in the rules. Describes how much variety the non-terminal symbol produces, including the variety
+    /// of the non-terminal symbols it is expanded to.
+    #[must_use]
+    pub fn derivation_entropy(&self, nonterm_value: &Nt) -> Option<f64> {
+        self.grammar.nonterm_idx(nonterm_value)
+            .map(|nonterm_idx| self.derivation_entropies[nonterm_idx])
+    }
+
+    /// Returns `true` if expanding a non-terminal symbol with `nonterm_value` is expected to take infinitely many
+    /// rule applications, either because it may never finish or because it finishes too rarely.
+    /// With the default maximum number of iterations, such expansions often fail with
+    /// [`ErrorKind::MaxIterationsReached`](enum.ErrorKind.html#variant.MaxIterationsReached).
+    #[must_use]
+    pub fn is_divergent(&self, nonterm_value: &Nt) -> bool {
+        self.grammar.nonterm_idx(nonterm_value)
+            .is_some_and(|nonterm_idx| self.divergent[nonterm_idx])
+    }
+
+    /// Returns values of all divergent non-terminal symbols (see [`is_divergent()`](#method.is_divergent)).
+    #[must_use]
+    pub fn divergent_nonterms(&self) -> Vec<&'r Nt> {
+        self.grammar.nonterms.iter()
+            .zip(&self.divergent)
+            .filter(|(_, is_divergent)| **is_divergent)
+            .map(|(nonterm, _)| *nonterm)
+            .collect()
+    }
+
+    //
+    // Service
+    //
+
+    /// Computes expectations for all non-terminal symbols, one strongly connected component
+    /// of the dependency graph at a time, so that divergence of one component does not affect unrelated ones.
+    fn analyze(&mut self) {
+        let nonterm_count = self.grammar.nonterm_count();
+
+        // Expected numbers of occurrences of every non-terminal symbol in a single-step expansion of every other one,
+        // along with expected numbers of terminal symbols and rule selection entropies.
+        let mut successors:      Vec<Vec<(usize, f64)>> = vec![Vec::new(); nonterm_count];
+        let mut terminal_counts: Vec<f64>               = vec![0.0; nonterm_count];
+
+        for (nonterm_idx, nonterm_rules) in self.grammar.rules_by_nonterm.iter().enumerate() {
+            for &rule_idx in nonterm_rules {
+                let probability = self.rule_probabilities[rule_idx];
+
+                if probability <= 0.0 {
+                    continue;
+                }
+
+                self.rule_entropies[nonterm_idx] -= probability * probability.log2();
+
+                for symbol in &self.grammar.rule_symbols[rule_idx] {
+                    match symbol {
+                        Some(successor_idx) => add_successor(&mut successors[nonterm_idx], *successor_idx, probability),
+                        None                => terminal_counts[nonterm_idx] += probability
+                    }
+                }
+            }
+        }
+
+        for component in strongly_connected_components(&successors) {
+            let is_divergent = component.iter()
+                .flat_map(|&nonterm_idx| &successors[nonterm_idx])
+                .any(|&(successor_idx, _)| self.divergent[successor_idx]);
+
+            let solution = if is_divergent {
+                None
+            } else {
+                solve_component(
+                    &component,
+                    &successors,
+                    [&terminal_counts, &self.rule_entropies],
+                    [&self.expected_lengths, &self.derivation_entropies]
+                )
+            };
+
+            for (component_nonterm_idx, &nonterm_idx) in component.iter().enumerate() {
+                match &solution {
+                    Some([expected_lengths, derivation_entropies]) => {
+                        self.expected_lengths[nonterm_idx]     = expected_lengths[component_nonterm_idx];
+                        self.derivation_entropies[nonterm_idx] = derivation_entropies[component_nonterm_idx];
+                    },
+                    None => {
+                        self.expected_lengths[nonterm_idx]     = f64::INFINITY;
+                        self.derivation_entropies[nonterm_idx] = f64::INFINITY;
+                        self.divergent[nonterm_idx]            = true;
+                    }
+                }
+            }
+        }
+    }
+}
+
+//
+// Service types
+//
+
+/// Symbol of a sentence or a rule's replacement with non-terminal symbols represented by their indices
+/// in an `IndexedGrammar`, or `None` if they do not occur in it.
+type IndexedSymbol<'s, T> = Symbol<Option<usize>, &'s T>;
+
+//
+// InsideChart
+//
+
+/// Inside probabilities of every non-terminal symbol deriving every span of a sentence.
+struct InsideChart {
+    sentence_len:  usize,
+    probabilities: Vec<f64>
+}
+
+impl InsideChart {
+    /// Computes inside probabilities for `sentence`, given the probabilities of all rules.
+    /// Spans are processed from the shortest to the longest, and probabilities for every span are recomputed
+    /// until they converge, since cycles of empty and unit rules allow a span to be derived from itself.
+    fn new<Nt, T>(
+        grammar:            &IndexedGrammar<'_, Nt, T>,
+        rule_probabilities: &[f64],
+        rule_symbols:       &[Vec<IndexedSymbol<'_, T>>],
+        sentence:           &[T]
+    ) -> Self
+        where Nt: NonterminalValue,
+              T:  PartialEq
+    {
+        let sentence_len = sentence.len();
+
+        let mut chart = Self{
+            sentence_len,
+            probabilities: vec![0.0; grammar.nonterm_count() * (sentence_len + 1) * (sentence_len + 1)]
+        };
+
+        for span_len in 0..=sentence_len {
+            for start in 0..=(sentence_len - span_len) {
+                let end = start + span_len;
+
+                for _ in 0..MAX_INSIDE_ITERATIONS {
+                    let mut max_change: f64 = 0.0;
+
+                    for (nonterm_idx, nonterm_rules) in grammar.rules_by_nonterm.iter().enumerate() {
+                        let span_probability: f64 = nonterm_rules.iter()
+                            .filter(|&&rule_idx| rule_probabilities[rule_idx] > 0.0)
+                            .map(|&rule_idx| {
+                                rule_probabilities[rule_idx]
+                                    * chart.prefix_probabilities(&rule_symbols[rule_idx], sentence, start, end)[rule_symbols[rule_idx].len()][span_len]
+                            })
+                            .sum();
+
+                        let chart_idx = chart.idx(nonterm_idx, start, end);
+
+                        max_change = max_change.max((chart.probabilities[chart_idx] - span_probability).abs());
+
+                        chart.probabilities[chart_idx] = span_probability;
+                    }
+
+                    if max_change <= INSIDE_TOLERANCE {
+                        break;
+                    }
+                }
+            }
+        }
+
+        chart
+    }
+
+    fn probability(&self, nonterm_idx: usize, start: usize, end: usize) -> f64 {
+        self.probabilities[self.idx(nonterm_idx, start, end)]
+    }
+
+    /// Returns the probabilities of the first `prefix_len` of `symbols` deriving `sentence[start..end]`
+    /// for every `prefix_len` and every `end` from `start` to `max_end`, indexed by `prefix_len` and `end - start`.
+    /// Non-terminal symbols, which do not occur in the rules, are represented by `None` and derive nothing.
+    fn prefix_probabilities<T>(&self, symbols: &[IndexedSymbol<'_, T>], sentence: &[T], start: usize, max_end: usize) -> Vec<Vec<f64>>
+        where T: PartialEq
+    {
+        let mut probabilities = vec![vec![0.0; max_end + 1 - start]; symbols.len() + 1];
+
+        probabilities[0][0] = 1.0;
+
+        for (symbol_idx, symbol) in symbols.iter().enumerate() {
+            for offset in 0..=(max_end - start) {
+                let probability = probabilities[symbol_idx][offset];
+
+                if probability == 0.0 {
+                    continue;
+                }
+
+                let position = start + offset;
+
+                match symbol {
+                    Symbol::Terminal(value) => {
+                        if position < max_end && sentence[position] == **value {
+                            probabilities[symbol_idx + 1][offset + 1] += probability;
+                        }
+                    },
+                    Symbol::Nonterminal(Some(nonterm_idx)) => {
+                        for end in position..=max_end {
+                            probabilities[symbol_idx + 1][end - start] += probability * self.probability(*nonterm_idx, position, end);
+                        }
+                    },
+                    Symbol::Nonterminal(None) => ()
+                }
+            }
+        }
+
+        probabilities
+    }
+
+    /// Returns the probabilities of `symbols` without their first `prefix_len` deriving `sentence[start..end]`
+    /// for every `prefix_len` and every `start` up to `end`, indexed by `prefix_len` and `start`.
+    fn suffix_probabilities<T>(&self, symbols: &[IndexedSymbol<'_, T>], sentence: &[T], end: usize) -> Vec<Vec<f64>>
+        where T: PartialEq
+    {
+        let mut probabilities = vec![vec![0.0; end + 1]; symbols.len() + 1];
+
+        probabilities[symbols.len()][end] = 1.0;
+
+        for (symbol_idx, symbol) in symbols.iter().enumerate().rev() {
+            for position in 0..=end {
+                probabilities[symbol_idx][position] = match symbol {
+                    Symbol::Terminal(value) => {
+                        if position < end && sentence[position] == **value {
+                            probabilities[symbol_idx + 1][position + 1]
+                        } else {
+                            0.0
+                        }
+                    },
+                    Symbol::Nonterminal(Some(nonterm_idx)) => {
+                        (position..=end)
+                            .map(|symbol_end| self.probability(*nonterm_idx, position, symbol_end) * probabilities[symbol_idx + 1][symbol_end])
+                            .sum()
+                    },
+                    Symbol::Nonterminal(None) => 0.0
+                };
+            }
+        }
+
+        probabilities
+    }
+
+    fn idx(&self, nonterm_idx: usize, start: usize, end: usize) -> usize {
+        (nonterm_idx * (self.sentence_len + 1) + start) * (self.sentence_len + 1) + end
+    }
+}
+
+//
+// Service
+//
+
+/// Returns the replacements of all rules with non-terminal symbols replaced by their indices.
+fn indexed_rule_symbols<'r, Nt, T>(grammar: &IndexedGrammar<'r, Nt, T>) -> Vec<Vec<IndexedSymbol<'r, T>>> {
+    grammar.rules.iter()
+        .zip(&grammar.rule_symbols)
+        .map(|(rule, symbols)| {
+            rule.replacement.iter()
+                .zip(symbols)
+                .map(|(symbol, nonterm_idx)| match symbol {
+                    Symbol::Nonterminal(_)  => Symbol::Nonterminal(*nonterm_idx),
+                    Symbol::Terminal(value) => Symbol::Terminal(value)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns `symbols` with non-terminal symbols replaced by their indices in `grammar`.
+fn indexed_symbols<'s, Nt, T>(grammar: &IndexedGrammar<'_, Nt, T>, symbols: &'s [Symbol<Nt, T>]) -> Vec<IndexedSymbol<'s, T>>
+    where Nt: NonterminalValue
+{
+    symbols.iter()
+        .map(|symbol| match symbol {
+            Symbol::Nonterminal(value) => Symbol::Nonterminal(grammar.nonterm_idx(value)),
+            Symbol::Terminal(value)    => Symbol::Terminal(value)
+        })
+        .collect()
+}
+
+fn rule_probabilities<Nt, T>(grammar: &IndexedGrammar<'_, Nt, T>, distribution: RuleDistribution) -> Vec<f64> {
+    let mut rule_probabilities = vec![0.0; grammar.rules.len()];
+
+    for nonterm_rules in &grammar.rules_by_nonterm {
+        let rule_weight = |rule_idx: usize| match distribution {
+            RuleDistribution::Uniform  => 1.0,
+            RuleDistribution::Weighted => {
+                let weight = grammar.rules[rule_idx].weight;
+
+                if weight > 0.0 { weight } else { 0.0 }
+            }
+        };
+
+        let total_weight: f64 = nonterm_rules.iter()
+            .map(|&rule_idx| rule_weight(rule_idx))
+            .sum();
+
+        if !(total_weight > 0.0 && total_weight.is_finite()) {
+            continue;
+        }
+
+        for &rule_idx in nonterm_rules {
+            rule_probabilities[rule_idx] = rule_weight(rule_idx) / total_weight;
+        }
+    }
+
+    rule_probabilities
+}
+
+fn add_successor(successors: &mut Vec<(usize, f64)>, successor_idx: usize, expected_count: f64) {
+    match successors.iter_mut().find(|(idx, _)| *idx == successor_idx) {
+        Some((_, total_expected_count)) => *total_expected_count += expected_count,
+        None                            => successors.push((successor_idx, expected_count))
+    }
+}
+
+/// Solves `x = c + M x` for the non-terminal symbols in `component` and every vector of constants `c`,
+/// where `M` is described by `successors` and values for non-terminal symbols outside `component`
+/// are taken from `known_solutions`. Returns `None` if the component is divergent, i.e. if the spectral radius of `M`
+/// restricted to it is at least 1, which is detected by solving for the expected number of rule applications.
+fn solve_component<const N: usize>(
+    component:       &[usize],
+    successors:      &[Vec<(usize, f64)>],
+    constants:       [&[f64]; N],
+    known_solutions: [&[f64]; N]
+) -> Option<[Vec<f64>; N]> {
+    let size = component.len();
+
+    let component_idx = |nonterm_idx: usize| component.iter().position(|&idx| idx == nonterm_idx);
+
+    // Augmented matrix of I - M with the expected number of rule applications as the first right-hand side.
+    let width = size + N + 1;
+
+    let mut matrix = vec![0.0; size * width];
+
+    for (row, &nonterm_idx) in component.iter().enumerate() {
+        matrix[row * width + row] = 1.0;
+        matrix[row * width + size] = 1.0;
+
+        for (rhs_idx, constant) in constants.iter().enumerate() {
+            matrix[row * width + size + 1 + rhs_idx] = constant[nonterm_idx];
+        }
+
+        for &(successor_idx, expected_count) in &successors[nonterm_idx] {
+            match component_idx(successor_idx) {
+                Some(column) => matrix[row * width + column] -= expected_count,
+                None         => {
+                    // Components, which the current one depends on, have already been solved and are not divergent,
+                    // so their expected numbers of rule applications are finite and only their contribution to
+                    // the other right-hand sides matters here.
+                    for (rhs_idx, known_solution) in known_solutions.iter().enumerate() {
+                        matrix[row * width + size + 1 + rhs_idx] += expected_count * known_solution[successor_idx];
+                    }
+                }
+            }
+        }
+    }
+
+    // Gaussian elimination with partial pivoting.
+    for pivot_idx in 0..size {
+        let pivot_row = (pivot_idx..size)
+            .max_by(|&a, &b| matrix[a * width + pivot_idx].abs().total_cmp(&matrix[b * width + pivot_idx].abs()))
+            .expect("pivot candidates must not be empty");
+
+        if matrix[pivot_row * width + pivot_idx].abs() < PIVOT_TOLERANCE {
+            return None;
+        }
+
+        for column in 0..width {
+            matrix.swap(pivot_idx * width + column, pivot_row * width + column);
+        }
+
+        for row in 0..size {
+            if row == pivot_idx {
+                continue;
+            }
+
+            let factor = matrix[row * width + pivot_idx] / matrix[pivot_idx * width + pivot_idx];
+
+            if factor == 0.0 {
+                continue;
+            }
+
+            for column in pivot_idx..width {
+                matrix[row * width + column] -= factor * matrix[pivot_idx * width + column];
+            }
+        }
+    }
+
+    let solution = |row: usize, rhs_column: usize| matrix[row * width + rhs_column] / matrix[row * width + row];
+
+    // Every non-terminal symbol is expanded at least once, so a finite solution is at least 1 everywhere,
+    // while a divergent component has no non-negative solution at all.
+    if (0..size).any(|row| solution(row, size) < 1.0 - 1e-9) {
+        return None;
+    }
+
+    Some(std::array::from_fn(|rhs_idx| (0..size).map(|row| solution(row, size + 1 + rhs_idx)).collect()))
+}
@@ -0,0 +1,450 @@
+use crate::grammar::{
+    NonterminalValue,
+    Symbol,
+    Rule,
+    indexed::IndexedGrammar
+};
+use super::{
+    RuleDistribution,
+    InsideChart,
+    IndexedSymbol,
+    MAX_INSIDE_ITERATIONS,
+    INSIDE_TOLERANCE,
+    indexed_rule_symbols,
+    indexed_symbols,
+    rule_probabilities
+};
+
+//
+// Constants
+//
+
+const DEFAULT_MAX_ITERATIONS: usize = 100;
+
+const DEFAULT_TOLERANCE: f64 = 1e-6;
+
+//
+// Interface types
+//
+
+//
+// WeightTrainer<'r, Nt, T>
+//
+
+/// Estimates rule weights from a corpus of example sentences, i.e. sequences of terminal values,
+/// which the rules are supposed to produce.
+///
+/// Every sentence is parsed against the rules and rule weights are set to the relative frequencies,
+/// with which rules are used in the parses. Sentences with more than one parse contribute to every parse
+/// in proportion to its probability, which is refined iteratively using the expectation-maximization
+/// (inside-outside) algorithm. If every sentence has a single parse, the weights are exact relative frequencies
+/// after the first iteration.
+///
+/// Trained weights are probabilities, summing up to 1 for the rules of every non-terminal symbol,
+/// and are meant to be used with [`WeightedRandomRuleSelector`](struct.WeightedRandomRuleSelector.html).
+/// Rules of non-terminal symbols, which do not occur in any parse, keep their initial probabilities.
+/// Rules with zero initial probability are never used in parses and keep zero weights.
+///
+/// ```
+/// use branchy::{
+///     Symbol,
+///     Rule,
+///     WeightTrainer
+/// };
+///
+/// let rules = vec![
+///     Rule::new("greeting", vec![Symbol::Terminal("hello"), Symbol::Nonterminal("name")]),
+///     Rule::new("name", vec![Symbol::Terminal("Alice")]),
+///     Rule::new("name", vec![Symbol::Terminal("Bob")])
+/// ];
+///
+/// let corpus = vec![
+///     vec!["hello", "Alice"],
+///     vec!["hello", "Alice"],
+///     vec!["hello", "Alice"],
+///     vec!["hello", "Bob"],
+///     vec!["goodbye", "Bob"]
+/// ];
+///
+/// let report = WeightTrainer::new(&rules).train(&[Symbol::Nonterminal("greeting")], &corpus);
+///
+/// assert_eq!(report.rules[1].weight, 0.75);
+/// assert_eq!(report.rules[2].weight, 0.25);
+/// assert_eq!(report.unparsed, [4]);
+/// ```
+pub struct WeightTrainer<'r, Nt, T> {
+    grammar:              IndexedGrammar<'r, Nt, T>,
+    initial_distribution: RuleDistribution,
+    max_iterations:       usize,
+    tolerance:            f64,
+    smoothing:            f64
+}
+
+impl<'r, Nt, T> WeightTrainer<'r, Nt, T>
+    where Nt: NonterminalValue
+{
+    #[must_use]
+    pub fn new(rules: &'r [Rule<Nt, T>]) -> Self {
+        Self{
+            grammar:              IndexedGrammar::new(rules),
+            initial_distribution: RuleDistribution::Uniform,
+            max_iterations:       DEFAULT_MAX_ITERATIONS,
+            tolerance:            DEFAULT_TOLERANCE,
+            smoothing:            0.0
+        }
+    }
+
+    /// Sets the distribution of rule probabilities, from which training starts. [`RuleDistribution::Uniform`](enum.RuleDistribution.html#variant.Uniform)
+    /// by default. [`RuleDistribution::Weighted`](enum.RuleDistribution.html#variant.Weighted) allows continuing training
+    /// from previously trained weights, as well as excluding rules from training by giving them zero weights.
+    #[must_use]
+    pub fn with_initial_distribution(mut self, initial_distribution: RuleDistribution) -> Self {
+        self.initial_distribution = initial_distribution;
+
+        self
+    }
+
+    /// Sets the maximal number of iterations of the expectation-maximization algorithm, 100 by default.
+    #[must_use]
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+
+        self
+    }
+
+    /// Sets the change of the corpus log-likelihood between iterations, at which training is considered converged,
+    /// `1e-6` by default.
+    #[must_use]
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+
+        self
+    }
+
+    /// Sets the pseudo-count, which is added to the number of uses of every rule (additive smoothing),
+    /// so that rules, which do not occur in parses of the corpus, still get positive weights. `0.0` by default.
+    /// Only applies to non-terminal symbols, which occur in parses of the corpus.
+    #[must_use]
+    pub fn with_smoothing(mut self, smoothing: f64) -> Self {
+        self.smoothing = smoothing;
+
+        self
+    }
+
+    /// Trains rule weights on `corpus`, in which every sentence is supposed to be produced by expanding `input`.
+    /// Sentences, which cannot be produced from `input`, are ignored and reported in
+    /// [`TrainingReport::unparsed`](struct.TrainingReport.html#structfield.unparsed).
+    #[must_use]
+    pub fn train<I, S>(&self, input: &[Symbol<Nt, T>], corpus: I) -> TrainingReport<Nt, T>
+        where I:  IntoIterator<Item = S>,
+              S:  AsRef<[T]>,
+              Nt: Clone,
+              T:  Clone + PartialEq
+    {
+        let sentences: Vec<S> = corpus.into_iter().collect();
+
+        let rule_symbols  = indexed_rule_symbols(&self.grammar);
+        let input_symbols = indexed_symbols(&self.grammar, input);
+
+        let initial_probabilities = rule_probabilities(&self.grammar, self.initial_distribution);
+
+        let compute_expectations = |probabilities: &[f64]| {
+            let mut expectations = Expectations{
+                rule_counts:    vec![0.0; probabilities.len()],
+                log_likelihood: 0.0,
+                unparsed:       Vec::new()
+            };
+
+            for (sentence_idx, sentence) in sentences.iter().enumerate() {
+                let sentence_probability = add_expected_rule_counts(
+                    &self.grammar,
+                    probabilities,
+                    &rule_symbols,
+                    &input_symbols,
+                    sentence.as_ref(),
+                    &mut expectations.rule_counts
+                );
+
+                match sentence_probability {
+                    Some(sentence_probability) => expectations.log_likelihood += sentence_probability.ln(),
+                    None                       => expectations.unparsed.push(sentence_idx)
+                }
+            }
+
+            expectations
+        };
+
+        let mut probabilities = initial_probabilities.clone();
+        let mut expectations  = compute_expectations(&probabilities);
+        let mut iterations    = 0;
+        let mut converged     = false;
+
+        while iterations < self.max_iterations && !converged {
+            probabilities = self.maximize(&expectations.rule_counts, &initial_probabilities, &probabilities);
+
+            let new_expectations = compute_expectations(&probabilities);
+
+            converged = (new_expectations.log_likelihood - expectations.log_likelihood).abs() <= self.tolerance;
+
+            expectations = new_expectations;
+            iterations  += 1;
+        }
+
+        TrainingReport{
+            rules: self.grammar.rules.iter()
+                .zip(&probabilities)
+                .map(|(rule, &probability)| Rule{weight: probability, ..rule.clone()})
+                .collect(),
+            rule_counts:    expectations.rule_counts,
+            log_likelihood: expectations.log_likelihood,
+            iterations,
+            converged,
+            unparsed:       expectations.unparsed
+        }
+    }
+
+    //
+    // Service
+    //
+
+    /// Returns rule probabilities proportional to (smoothed) expected rule counts.
+    fn maximize(&self, rule_counts: &[f64], initial_probabilities: &[f64], probabilities: &[f64]) -> Vec<f64> {
+        let mut new_probabilities = probabilities.to_vec();
+
+        for nonterm_rules in &self.grammar.rules_by_nonterm {
+            let trained_rule_idxs = nonterm_rules.iter()
+                .copied()
+                .filter(|&rule_idx| initial_probabilities[rule_idx] > 0.0);
+
+            let observed_count: f64 = trained_rule_idxs.clone()
+                .map(|rule_idx| rule_counts[rule_idx])
+                .sum();
+
+            // Without observations, smoothing alone would replace initial probabilities with uniform ones.
+            if !(observed_count > 0.0 && observed_count.is_finite()) {
+                continue;
+            }
+
+            let total_count = observed_count + self.smoothing * trained_rule_idxs.clone().count() as f64;
+
+            for rule_idx in trained_rule_idxs {
+                new_probabilities[rule_idx] = (rule_counts[rule_idx] + self.smoothing) / total_count;
+            }
+        }
+
+        new_probabilities
+    }
+}
+
+//
+// TrainingReport<Nt, T>: Debug + Clone + PartialEq
+//
+
+/// Result of [`WeightTrainer::train()`](struct.WeightTrainer.html#method.train).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrainingReport<Nt, T> {
+    /// The trained rules, in the same order as the original ones, with weights set to trained probabilities.
+    pub rules:          Vec<Rule<Nt, T>>,
+    /// Expected number of uses of every rule in parses of the corpus, given the trained probabilities.
+    pub rule_counts:    Vec<f64>,
+    /// Natural logarithm of the probability of the parsed sentences of the corpus, given the trained probabilities.
+    pub log_likelihood: f64,
+    /// Number of performed iterations of the expectation-maximization algorithm.
+    pub iterations:     usize,
+    /// `true` if training stopped because the log-likelihood converged,
+    /// `false` if it stopped after the maximal number of iterations.
+    pub converged:      bool,
+    /// Indices of sentences in the corpus, which cannot be produced using the rules and were ignored.
+    pub unparsed:       Vec<usize>
+}
+
+//
+// Service types
+//
+
+//
+// Expectations
+//
+
+struct Expectations {
+    rule_counts:    Vec<f64>,
+    log_likelihood: f64,
+    unparsed:       Vec<usize>
+}
+
+//
+// Service
+//
+
+/// Adds expected numbers of uses of every rule in parses of `sentence` to `rule_counts` and returns
+/// the probability of `sentence`, or returns `None` if it cannot be produced from `input_symbols`.
+///
+/// Expected counts are computed from inside and outside probabilities. The outside probability of a non-terminal symbol
+/// for a span is the probability of deriving everything except that span from the input, with the non-terminal symbol
+/// in place of the span. Outside probabilities are propagated from the longest spans to the shortest ones.
+fn add_expected_rule_counts<Nt, T>(
+    grammar:            &IndexedGrammar<'_, Nt, T>,
+    rule_probabilities: &[f64],
+    rule_symbols:       &[Vec<IndexedSymbol<'_, T>>],
+    input_symbols:      &[IndexedSymbol<'_, T>],
+    sentence:           &[T],
+    rule_counts:        &mut [f64]
+) -> Option<f64>
+    where Nt: NonterminalValue,
+          T:  PartialEq
+{
+    let sentence_len = sentence.len();
+
+    let inside = InsideChart::new(grammar, rule_probabilities, rule_symbols, sentence);
+
+    let input_prefixes = inside.prefix_probabilities(input_symbols, sentence, 0, sentence_len);
+
+    let sentence_probability = input_prefixes[input_symbols.len()][sentence_len];
+
+    if !(sentence_probability > 0.0 && sentence_probability.is_finite()) {
+        return None;
+    }
+
+    let input_suffixes = inside.suffix_probabilities(input_symbols, sentence, sentence_len);
+
+    let mut outside = vec![0.0; inside.probabilities.len()];
+
+    for (symbol_idx, symbol) in input_symbols.iter().enumerate() {
+        if let Symbol::Nonterminal(Some(nonterm_idx)) = symbol {
+            for start in 0..=sentence_len {
+                for end in start..=sentence_len {
+                    outside[inside.idx(*nonterm_idx, start, end)] +=
+                        input_prefixes[symbol_idx][start] * input_suffixes[symbol_idx + 1][end];
+                }
+            }
+        }
+    }
+
+    let is_used = |rule_idx: usize| rule_probabilities[rule_idx] > 0.0;
+
+    // Probabilities of the prefixes of every rule's replacement deriving spans starting at every position
+    // and of its suffixes deriving spans ending at every position.
+    let rule_prefixes: Vec<Vec<_>> = rule_symbols.iter()
+        .enumerate()
+        .map(|(rule_idx, symbols)| {
+            if !is_used(rule_idx) {
+                return Vec::new();
+            }
+
+            (0..=sentence_len)
+                .map(|start| inside.prefix_probabilities(symbols, sentence, start, sentence_len))
+                .collect()
+        })
+        .collect();
+
+    let rule_suffixes: Vec<Vec<_>> = rule_symbols.iter()
+        .enumerate()
+        .map(|(rule_idx, symbols)| {
+            if !is_used(rule_idx) {
+                return Vec::new();
+            }
+
+            (0..=sentence_len)
+                .map(|end| inside.suffix_probabilities(symbols, sentence, end))
+                .collect()
+        })
+        .collect();
+
+    for span_len in (0..=sentence_len).rev() {
+        for start in 0..=(sentence_len - span_len) {
+            let end = start + span_len;
+
+            // Non-terminal symbols may be derived from other ones for the same span via empty and unit rules,
+            // so their outside probabilities for the span are computed together, until they converge.
+            let mut same_span_derivations = Vec::new();
+
+            for (parent_idx, nonterm_rules) in grammar.rules_by_nonterm.iter().enumerate() {
+                for &rule_idx in nonterm_rules.iter().filter(|&&rule_idx| is_used(rule_idx)) {
+                    for (symbol_idx, symbol) in rule_symbols[rule_idx].iter().enumerate() {
+                        if let Symbol::Nonterminal(Some(child_idx)) = symbol {
+                            let probability = rule_probabilities[rule_idx]
+                                * rule_prefixes[rule_idx][start][symbol_idx][0]
+                                * rule_suffixes[rule_idx][end][symbol_idx + 1][end];
+
+                            if probability > 0.0 {
+                                same_span_derivations.push((parent_idx, *child_idx, probability));
+                            }
+                        }
+                    }
+                }
+            }
+
+            if !same_span_derivations.is_empty() {
+                let base_probabilities: Vec<_> = (0..grammar.nonterm_count())
+                    .map(|nonterm_idx| outside[inside.idx(nonterm_idx, start, end)])
+                    .collect();
+
+                for _ in 0..MAX_INSIDE_ITERATIONS {
+                    let mut probabilities = base_probabilities.clone();
+
+                    for &(parent_idx, child_idx, probability) in &same_span_derivations {
+                        probabilities[child_idx] += probability * outside[inside.idx(parent_idx, start, end)];
+                    }
+
+                    let mut max_change: f64 = 0.0;
+
+                    for (nonterm_idx, probability) in probabilities.into_iter().enumerate() {
+                        let outside_idx = inside.idx(nonterm_idx, start, end);
+
+                        max_change = max_change.max((outside[outside_idx] - probability).abs());
+
+                        outside[outside_idx] = probability;
+                    }
+
+                    if max_change <= INSIDE_TOLERANCE {
+                        break;
+                    }
+                }
+            }
+
+            for (parent_idx, nonterm_rules) in grammar.rules_by_nonterm.iter().enumerate() {
+                let parent_probability = outside[inside.idx(parent_idx, start, end)];
+
+                if parent_probability == 0.0 {
+                    continue;
+                }
+
+                for &rule_idx in nonterm_rules.iter().filter(|&&rule_idx| is_used(rule_idx)) {
+                    let prefixes = &rule_prefixes[rule_idx][start];
+                    let suffixes = &rule_suffixes[rule_idx][end];
+                    let symbols  = &rule_symbols[rule_idx];
+
+                    let probability = parent_probability * rule_probabilities[rule_idx];
+
+                    rule_counts[rule_idx] += probability * prefixes[symbols.len()][span_len] / sentence_probability;
+
+                    for (symbol_idx, symbol) in symbols.iter().enumerate() {
+                        let child_idx = match symbol {
+                            Symbol::Nonterminal(Some(child_idx)) => *child_idx,
+                            _                                    => continue
+                        };
+
+                        for child_start in start..=end {
+                            let prefix_probability = prefixes[symbol_idx][child_start - start];
+
+                            if prefix_probability == 0.0 {
+                                continue;
+                            }
+
+                            for child_end in child_start..=end {
+                                if child_end - child_start == span_len {
+                                    continue;
+                                }
+
+                                outside[inside.idx(child_idx, child_start, child_end)] +=
+                                    probability * prefix_probability * suffixes[symbol_idx + 1][child_end];
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Some(sentence_probability)
+}
@@ -0,0 +1,271 @@
+use std::{
+    fmt,
+    sync::Arc
+};
+
+use proptest::{
+    prelude::Rng,
+    strategy::{
+        Strategy,
+        ValueTree,
+        NewTree
+    },
+    test_runner::TestRunner
+};
+
+use crate::grammar::{
+    NonterminalValue,
+    TerminalValue,
+    Symbol,
+    Rule
+};
+use crate::expansion::{
+    Expander,
+    RuleSelector,
+    ExpansionLogger
+};
+use crate::derivation::{
+    Derivation,
+    random::PreparedGrammar,
+    shrink::{
+        DerivationShrinker,
+        ShrinkCandidate
+    }
+};
+
+//
+// Constants
+//
+
+const DEFAULT_MAX_NODE_COUNT: usize = 64;
+
+//
+// Interface types
+//
+
+//
+// GrammarStrategy<Nt, T>: Debug + Clone
+//
+
+/// A [`proptest`](https://docs.rs/proptest) `Strategy`, generating random [`Derivation`](struct.Derivation.html)s
+/// of an input sequence using a set of rules. Available with the `proptest` cargo feature.
+///
+/// Rules are selected uniformly at random until roughly `max_node_count` derivation tree nodes are generated,
+/// after which the remaining non-terminal symbols are expanded as briefly as possible, so generation always terminates.
+///
+/// Failing cases are shrunk on the derivation tree, rather than on the output: subtrees are replaced
+/// with shorter derivations of the same non-terminal symbol, so every shrunk value is still produced by the grammar.
+///
+/// The rules are analyzed once, when the strategy is created, and the results are shared by all generated values
+/// and clones of the strategy.
+///
+/// ```
+/// use branchy::{
+///     Symbol,
+///     Rule,
+///     GrammarStrategy
+/// };
+/// use proptest::{
+///     proptest,
+///     prop_assert
+/// };
+///
+/// let rules = vec![
+///     Rule::new("list", vec![Symbol::Terminal("item")]),
+///     Rule::new("list", vec![Symbol::Terminal("item"), Symbol::Terminal(","), Symbol::Nonterminal("list")])
+/// ];
+///
+/// let strategy = GrammarStrategy::new(rules, vec![Symbol::Nonterminal("list")]);
+///
+/// proptest!(|(output in strategy.outputs())| {
+///     prop_assert!(output.len() % 2 == 1);
+/// });
+/// ```
+#[derive(Clone)]
+pub struct GrammarStrategy<Nt, T> {
+    grammar:        Arc<PreparedGrammar<Nt, T>>,
+    input:          Arc<Vec<Symbol<Nt, T>>>,
+    max_node_count: usize
+}
+
+impl<Nt, T> GrammarStrategy<Nt, T>
+    where Nt: NonterminalValue + fmt::Debug,
+          T:  TerminalValue + fmt::Debug
+{
+    //
+    // Interface
+    //
+
+    #[must_use]
+    pub fn new(rules: Vec<Rule<Nt, T>>, input: Vec<Symbol<Nt, T>>) -> Self {
+        Self{
+            grammar:        Arc::new(PreparedGrammar::new(rules)),
+            input:          Arc::new(input),
+            max_node_count: DEFAULT_MAX_NODE_COUNT
+        }
+    }
+
+    /// Creates a strategy using a copy of `expander`'s rules.
+    #[must_use]
+    pub fn from_expander<RS, EL>(expander: &Expander<Nt, T, RS, EL>, input: Vec<Symbol<Nt, T>>) -> Self
+        where RS: RuleSelector<Nt, T>,
+              EL: ExpansionLogger<Nt, T>
+    {
+        Self::new(expander.rules().to_vec(), input)
+    }
+
+    #[must_use]
+    pub fn with_max_node_count(self, max_node_count: usize) -> Self {
+        Self{
+            max_node_count,
+            ..self
+        }
+    }
+
+    /// Returns a strategy generating fully expanded sequences of terminal values instead of derivations,
+    /// while still shrinking them on the derivation tree.
+    pub fn outputs(self) -> impl Strategy<Value = Vec<T>> {
+        self.prop_map(|derivation| derivation.terminals())
+    }
+}
+
+impl<Nt, T> Strategy for GrammarStrategy<Nt, T>
+    where Nt: NonterminalValue + fmt::Debug,
+          T:  TerminalValue + fmt::Debug
+{
+    type Tree  = DerivationValueTree<Nt, T>;
+    type Value = Derivation<Nt, T>;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let generator = self.grammar.generator();
+
+        let rng    = runner.rng();
+        let budget = (rng.next_u64() % (self.max_node_count as u64 + 1)) as usize;
+
+        let derivation = generator.generate(&self.input, budget, &mut |option_count| {
+            (rng.next_u64() % option_count as u64) as usize
+        });
+
+        derivation
+            .map(|derivation| {
+                DerivationValueTree::new(Arc::clone(self.grammar.shrinker()), derivation)
+            })
+            .ok_or_else(|| "input sequence cannot be fully expanded using the given rules".into())
+    }
+}
+
+impl<Nt, T> fmt::Debug for GrammarStrategy<Nt, T>
+    where Nt: fmt::Debug,
+          T:  fmt::Debug
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GrammarStrategy")
+            .field("rules", &self.grammar.rules())
+            .field("input", &self.input)
+            .field("max_node_count", &self.max_node_count)
+            .finish()
+    }
+}
+
+//
+// DerivationValueTree<Nt, T>
+//
+
+/// The `ValueTree` of [`GrammarStrategy`](struct.GrammarStrategy.html).
+///
+/// Each simplification replaces a single subtree of the current derivation, trying replacements
+/// closest to the roots first. A rejected simplification is undone by `complicate()`,
+/// after which the next replacement is tried.
+pub struct DerivationValueTree<Nt, T> {
+    shrinker: Arc<DerivationShrinker<Nt, T>>,
+    current:  ShrinkState<Nt, T>,
+    previous: Option<ShrinkState<Nt, T>>
+}
+
+impl<Nt, T> DerivationValueTree<Nt, T>
+    where Nt: NonterminalValue,
+          T:  TerminalValue
+{
+    fn new(shrinker: Arc<DerivationShrinker<Nt, T>>, derivation: Derivation<Nt, T>) -> Self {
+        let current = ShrinkState::new(&shrinker, derivation);
+
+        Self{
+            shrinker,
+            current,
+            previous: None
+        }
+    }
+}
+
+impl<Nt, T> ValueTree for DerivationValueTree<Nt, T>
+    where Nt: NonterminalValue + fmt::Debug,
+          T:  TerminalValue + fmt::Debug
+{
+    type Value = Derivation<Nt, T>;
+
+    fn current(&self) -> Self::Value {
+        self.current.derivation.clone()
+    }
+
+    fn simplify(&mut self) -> bool {
+        let candidate = if let Some(candidate) = self.current.candidates.get(self.current.next_candidate_idx) {
+            candidate
+        } else {
+            return false;
+        };
+
+        let derivation = self.shrinker.apply(&self.current.derivation, candidate);
+
+        self.current.next_candidate_idx += 1;
+
+        let previous = std::mem::replace(&mut self.current, ShrinkState::new(&self.shrinker, derivation));
+
+        self.previous = Some(previous);
+
+        true
+    }
+
+    fn complicate(&mut self) -> bool {
+        if let Some(previous) = self.previous.take() {
+            self.current = previous;
+
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<Nt, T> fmt::Debug for DerivationValueTree<Nt, T>
+    where Nt: fmt::Debug,
+          T:  fmt::Debug
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DerivationValueTree")
+            .field("current", &self.current.derivation)
+            .finish()
+    }
+}
+
+//
+// Service types
+//
+
+struct ShrinkState<Nt, T> {
+    derivation:         Derivation<Nt, T>,
+    candidates:         Vec<ShrinkCandidate>,
+    next_candidate_idx: usize
+}
+
+impl<Nt, T> ShrinkState<Nt, T>
+    where Nt: NonterminalValue,
+          T:  TerminalValue
+{
+    fn new(shrinker: &DerivationShrinker<Nt, T>, derivation: Derivation<Nt, T>) -> Self {
+        Self{
+            candidates:         shrinker.candidates(&derivation),
+            derivation,
+            next_candidate_idx: 0
+        }
+    }
+}
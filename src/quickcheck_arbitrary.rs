@@ -0,0 +1,218 @@
+use std::{
+    any::{
+        Any,
+        TypeId
+    },
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    marker::PhantomData,
+    sync::Arc
+};
+
+use quickcheck::{
+    Arbitrary,
+    Gen
+};
+
+use crate::grammar::{
+    NonterminalValue,
+    TerminalValue,
+    Symbol,
+    Rule
+};
+use crate::derivation::{
+    Derivation,
+    random::PreparedGrammar
+};
+
+//
+// Interface traits
+//
+
+/// Describes a grammar, samples of which are generated by [`GrammarSample`](struct.GrammarSample.html).
+/// Available with the `quickcheck` cargo feature.
+///
+/// Since `quickcheck` generates values by type alone, the grammar is described by a type as well,
+/// usually a unit struct.
+pub trait ArbitraryGrammar: 'static {
+    type Nonterminal: NonterminalValue + 'static;
+    type Terminal:    TerminalValue + 'static;
+
+    fn rules() -> Vec<Rule<Self::Nonterminal, Self::Terminal>>;
+
+    fn input() -> Vec<Symbol<Self::Nonterminal, Self::Terminal>>;
+
+    /// Roughly the maximal number of derivation tree nodes generated using randomly selected rules,
+    /// after which the remaining non-terminal symbols are expanded as briefly as possible.
+    /// The actual limit is also capped by the size of `quickcheck`'s `Gen`.
+    fn max_node_count() -> usize {
+        64
+    }
+}
+
+//
+// Interface types
+//
+
+//
+// GrammarSample<G>: Clone + Debug + Arbitrary
+//
+
+/// A random [`Derivation`](struct.Derivation.html) of [`ArbitraryGrammar`](trait.ArbitraryGrammar.html)'s input,
+/// implementing [`quickcheck`](https://docs.rs/quickcheck)'s `Arbitrary`. Available with the `quickcheck` cargo feature.
+///
+/// Shrinking works on the derivation tree, rather than on the output: subtrees are replaced
+/// with shorter derivations of the same non-terminal symbol, so every shrunk sample is still produced by the grammar.
+///
+/// The rules of every grammar are analyzed once per thread, and the results are shared by all samples of that grammar.
+///
+/// ```
+/// use branchy::{
+///     Symbol,
+///     Rule,
+///     ArbitraryGrammar,
+///     GrammarSample
+/// };
+/// use quickcheck::quickcheck;
+///
+/// struct ListGrammar;
+///
+/// impl ArbitraryGrammar for ListGrammar {
+///     type Nonterminal = &'static str;
+///     type Terminal    = &'static str;
+///
+///     fn rules() -> Vec<Rule<&'static str, &'static str>> {
+///         vec![
+///             Rule::new("list", vec![Symbol::Terminal("item")]),
+///             Rule::new("list", vec![Symbol::Terminal("item"), Symbol::Terminal(","), Symbol::Nonterminal("list")])
+///         ]
+///     }
+///
+///     fn input() -> Vec<Symbol<&'static str, &'static str>> {
+///         vec![Symbol::Nonterminal("list")]
+///     }
+/// }
+///
+/// fn has_odd_length(sample: GrammarSample<ListGrammar>) -> bool {
+///     sample.terminals().len() % 2 == 1
+/// }
+///
+/// quickcheck(has_odd_length as fn(GrammarSample<ListGrammar>) -> bool);
+/// ```
+pub struct GrammarSample<G>
+    where G: ArbitraryGrammar
+{
+    pub derivation: Derivation<G::Nonterminal, G::Terminal>,
+    grammar:        Arc<PreparedGrammar<G::Nonterminal, G::Terminal>>,
+    _phantom:       PhantomData<fn() -> G>
+}
+
+impl<G> GrammarSample<G>
+    where G: ArbitraryGrammar
+{
+    #[must_use]
+    pub fn new(derivation: Derivation<G::Nonterminal, G::Terminal>) -> Self {
+        Self::with_grammar(derivation, prepared_grammar::<G>())
+    }
+
+    /// Returns the fully expanded sequence of terminal values of this sample's derivation.
+    #[must_use]
+    pub fn terminals(&self) -> Vec<G::Terminal> {
+        self.derivation.terminals()
+    }
+
+    //
+    // Service
+    //
+
+    fn with_grammar(
+        derivation: Derivation<G::Nonterminal, G::Terminal>,
+        grammar:    Arc<PreparedGrammar<G::Nonterminal, G::Terminal>>
+    ) -> Self {
+        Self{
+            derivation,
+            grammar,
+            _phantom: PhantomData
+        }
+    }
+}
+
+impl<G> Clone for GrammarSample<G>
+    where G: ArbitraryGrammar
+{
+    fn clone(&self) -> Self {
+        Self::with_grammar(self.derivation.clone(), Arc::clone(&self.grammar))
+    }
+}
+
+impl<G> fmt::Debug for GrammarSample<G>
+    where G:              ArbitraryGrammar,
+          G::Nonterminal: fmt::Debug,
+          G::Terminal:    fmt::Debug
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GrammarSample")
+            .field("derivation", &self.derivation)
+            .finish()
+    }
+}
+
+impl<G> Arbitrary for GrammarSample<G>
+    where G: ArbitraryGrammar
+{
+    /// # Panics
+    ///
+    /// Panics if `G::input()` cannot be fully expanded using `G::rules()`.
+    fn arbitrary(g: &mut Gen) -> Self {
+        let grammar   = prepared_grammar::<G>();
+        let generator = grammar.generator();
+
+        let max_node_count = G::max_node_count().min(g.size());
+        let budget         = usize::arbitrary(g) % (max_node_count + 1);
+
+        let derivation = generator
+            .generate(&G::input(), budget, &mut |option_count| usize::arbitrary(g) % option_count)
+            .expect("input sequence of an ArbitraryGrammar must be fully expandable using its rules");
+
+        drop(generator);
+
+        Self::with_grammar(derivation, grammar)
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let grammar    = Arc::clone(&self.grammar);
+        let candidates = grammar.shrinker().candidates(&self.derivation);
+        let derivation = self.derivation.clone();
+
+        Box::new(
+            candidates.into_iter()
+                .map(move |candidate| {
+                    Self::with_grammar(grammar.shrinker().apply(&derivation, &candidate), Arc::clone(&grammar))
+                })
+        )
+    }
+}
+
+//
+// Service
+//
+
+thread_local! {
+    /// Prepared grammars of the `ArbitraryGrammar`s used on this thread, keyed by their types.
+    static PREPARED_GRAMMARS: RefCell<HashMap<TypeId, Box<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+/// Returns the prepared rules of `G`, preparing them on first use on the current thread.
+fn prepared_grammar<G>() -> Arc<PreparedGrammar<G::Nonterminal, G::Terminal>>
+    where G: ArbitraryGrammar
+{
+    PREPARED_GRAMMARS.with(|prepared_grammars| {
+        prepared_grammars.borrow_mut()
+            .entry(TypeId::of::<G>())
+            .or_insert_with(|| Box::new(Arc::new(PreparedGrammar::new(G::rules()))))
+            .downcast_ref::<Arc<PreparedGrammar<G::Nonterminal, G::Terminal>>>()
+            .map(Arc::clone)
+            .expect("prepared grammar must be stored under the type of its ArbitraryGrammar")
+    })
+}
@@ -0,0 +1,370 @@
+use rand::Rng;
+
+use crate::grammar::{
+    NonterminalValue,
+    TerminalValue,
+    Symbol,
+    Rule
+};
+
+//
+// Constants
+//
+
+const DEFAULT_REWRITE_WEIGHT: f64 = 1.0;
+
+/// Default maximum number of generations in `RewriteMode::Parallel`, which may grow the sequence exponentially.
+const DEFAULT_MAX_GENERATIONS: usize = 10;
+
+/// Default maximum number of iterations in `RewriteMode::Sequential`, each of which applies a single rule.
+const DEFAULT_MAX_SEQUENTIAL_ITERATIONS: usize = 1000;
+
+//
+// Interface types
+//
+
+//
+// RewriteRule<Nt, T>: Debug + Clone + PartialEq + From<Rule<Nt, T>>
+//
+
+/// Rule for a [`Rewriter`](struct.Rewriter.html), which replaces a sequence of symbols (`pattern`) with `replacement`,
+/// but only when `pattern` is immediately preceded by `left_context` and followed by `right_context`.
+///
+/// Unlike [`Rule`](struct.Rule.html)s, rewrite rules may match terminal symbols and several symbols at once,
+/// so that they can express context-sensitive L-systems (e.g. `A < B > C -> x`, which rewrites `B` to `x`
+/// only between `A` and `C`) or post-process terminal values (e.g. replace `"n"` with `"m"` before `"p"`).
+/// Rules with empty patterns never apply.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RewriteRule<Nt, T> {
+    pub left_context:  Vec<Symbol<Nt, T>>,
+    pub pattern:       Vec<Symbol<Nt, T>>,
+    pub right_context: Vec<Symbol<Nt, T>>,
+    pub replacement:   Vec<Symbol<Nt, T>>,
+    /// Relative probability of selecting this rule among the most specific applicable ones, `1.0` by default.
+    /// Rules with zero, negative or NaN weights never apply.
+    pub weight:        f64
+}
+
+impl<Nt, T> RewriteRule<Nt, T> {
+    /// Constructs a rule without context.
+    #[must_use]
+    pub fn new(pattern: Vec<Symbol<Nt, T>>, replacement: Vec<Symbol<Nt, T>>) -> Self {
+        Self{
+            left_context:  Vec::new(),
+            pattern,
+            right_context: Vec::new(),
+            replacement,
+            weight:        DEFAULT_REWRITE_WEIGHT
+        }
+    }
+
+    #[must_use]
+    pub fn with_left_context(self, left_context: Vec<Symbol<Nt, T>>) -> Self {
+        Self{
+            left_context,
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn with_right_context(self, right_context: Vec<Symbol<Nt, T>>) -> Self {
+        Self{
+            right_context,
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn with_weight(self, weight: f64) -> Self {
+        Self{
+            weight,
+            ..self
+        }
+    }
+
+    /// Returns the total number of symbols in the pattern and both contexts.
+    /// Only the most specific of the rules applicable at the same position are selected.
+    #[must_use]
+    pub fn specificity(&self) -> usize {
+        self.left_context.len() + self.pattern.len() + self.right_context.len()
+    }
+
+    /// Returns `true` if this rule can rewrite `symbols` starting at `position`.
+    #[must_use]
+    pub fn is_applicable(&self, symbols: &[Symbol<Nt, T>], position: usize) -> bool
+        where Nt: PartialEq,
+              T:  PartialEq
+    {
+        let pattern_end = position + self.pattern.len();
+
+        !self.pattern.is_empty()
+            && self.weight > 0.0
+            && position >= self.left_context.len()
+            && pattern_end + self.right_context.len() <= symbols.len()
+            && symbols[(position - self.left_context.len())..position] == self.left_context[..]
+            && symbols[position..pattern_end] == self.pattern[..]
+            && symbols[pattern_end..(pattern_end + self.right_context.len())] == self.right_context[..]
+    }
+}
+
+/// Converts a context-free [`Rule`](struct.Rule.html) to a rewrite rule, which replaces its pattern
+/// non-terminal symbol in any context. Modifiers are discarded.
+impl<Nt, T> From<Rule<Nt, T>> for RewriteRule<Nt, T> {
+    fn from(rule: Rule<Nt, T>) -> Self {
+        Self::new(vec![Symbol::Nonterminal(rule.pattern)], rule.replacement)
+            .with_weight(rule.weight)
+    }
+}
+
+//
+// enum RewriteMode: Debug + Clone + Copy + PartialEq + Eq + Default
+//
+
+/// Describes how many rules a [`Rewriter`](struct.Rewriter.html) applies in a single iteration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RewriteMode {
+    /// Every symbol is rewritten simultaneously, like in L-systems: the sequence is scanned from left to right,
+    /// and wherever a rule applies, its pattern is replaced and scanning continues after it. Contexts are matched
+    /// against the sequence as it was before the iteration. This is the default.
+    #[default]
+    Parallel,
+    /// Only the leftmost occurrence of any applicable rule's pattern is replaced.
+    Sequential
+}
+
+//
+// Rewriter<Nt, T>
+//
+
+/// Rewrites sequences of symbols using [`RewriteRule`](struct.RewriteRule.html)s, e.g. in order to generate
+/// context-sensitive L-systems or to post-process the output of an [`Expander`](struct.Expander.html).
+///
+/// Every iteration applies rules according to the [`RewriteMode`](enum.RewriteMode.html). When several rules
+/// apply at the same position, one of the most [specific](struct.RewriteRule.html#method.specificity) ones
+/// is selected at random with probability proportional to its weight, so that a rule with context takes precedence
+/// over a rule for the same pattern without one.
+///
+/// ```
+/// use branchy::{
+///     Symbol,
+///     RewriteRule,
+///     Rewriter
+/// };
+///
+/// // Signal propagation in a context-sensitive L-system: "b" turns into "a" right after "a".
+/// let rewriter = Rewriter::new(vec![
+///     RewriteRule::new(vec![Symbol::Terminal('b')], vec![Symbol::Terminal('a')])
+///         .with_left_context(vec![Symbol::Terminal('a')]),
+///     RewriteRule::new(vec![Symbol::Terminal('a')], vec![Symbol::Terminal('b')])
+///         .with_left_context(vec![Symbol::Terminal('a')])
+/// ]).with_max_iterations(2);
+///
+/// let input: Vec<Symbol<(), char>> = "abbb".chars().map(Symbol::Terminal).collect();
+///
+/// let rewritten = rewriter.rewrite(input, &mut rand::thread_rng());
+///
+/// assert_eq!(rewritten.symbols, "abab".chars().map(Symbol::Terminal).collect::<Vec<_>>());
+/// assert_eq!(rewritten.iterations, 2);
+/// ```
+pub struct Rewriter<Nt, T> {
+    rules:          Vec<RewriteRule<Nt, T>>,
+    mode:           RewriteMode,
+    /// Set via `with_max_iterations()`, otherwise the default depends on the mode.
+    max_iterations: Option<usize>
+}
+
+impl<Nt, T> Rewriter<Nt, T>
+    where Nt: NonterminalValue,
+          T:  TerminalValue + PartialEq
+{
+    #[must_use]
+    pub fn new(rules: Vec<RewriteRule<Nt, T>>) -> Self {
+        Self{
+            rules,
+            mode:           RewriteMode::default(),
+            max_iterations: None
+        }
+    }
+
+    #[must_use]
+    pub fn with_mode(self, mode: RewriteMode) -> Self {
+        Self{
+            mode,
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of iterations performed by [`rewrite()`](#method.rewrite).
+    /// For L-systems, this is the number of generations, so it should usually be set explicitly
+    /// for [`RewriteMode::Parallel`](enum.RewriteMode.html#variant.Parallel), where the sequence may grow
+    /// exponentially with every generation.
+    ///
+    /// By default, it is 10 in `RewriteMode::Parallel`
+    /// and 1000 in [`RewriteMode::Sequential`](enum.RewriteMode.html#variant.Sequential).
+    #[must_use]
+    pub fn with_max_iterations(self, max_iterations: usize) -> Self {
+        Self{
+            max_iterations: Some(max_iterations),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn rules(&self) -> &[RewriteRule<Nt, T>] {
+        &self.rules
+    }
+
+    #[must_use]
+    pub fn mode(&self) -> RewriteMode {
+        self.mode
+    }
+
+    /// Returns the maximum number of iterations performed by [`rewrite()`](#method.rewrite).
+    #[must_use]
+    pub fn max_iterations(&self) -> usize {
+        self.max_iterations.unwrap_or(match self.mode {
+            RewriteMode::Parallel   => DEFAULT_MAX_GENERATIONS,
+            RewriteMode::Sequential => DEFAULT_MAX_SEQUENTIAL_ITERATIONS
+        })
+    }
+
+    /// Performs iterations until no rule applies or the maximum number of iterations is reached.
+    pub fn rewrite<R>(&self, input: Vec<Symbol<Nt, T>>, rng: &mut R) -> Rewritten<Nt, T>
+        where R: Rng + ?Sized
+    {
+        let max_iterations = self.max_iterations();
+
+        let mut symbols    = input;
+        let mut iterations = 0;
+
+        while iterations < max_iterations {
+            match self.step(&symbols, rng) {
+                Some(rewritten_symbols) => symbols = rewritten_symbols,
+                None                    => {
+                    return Rewritten{
+                        symbols,
+                        iterations,
+                        is_complete: true
+                    };
+                }
+            }
+
+            iterations += 1;
+        }
+
+        let is_complete = !(0..symbols.len()).any(|position| self.is_applicable_at(&symbols, position));
+
+        Rewritten{
+            symbols,
+            iterations,
+            is_complete
+        }
+    }
+
+    /// Performs a single iteration, returning `None` if no rule applies to `symbols`.
+    pub fn step<R>(&self, symbols: &[Symbol<Nt, T>], rng: &mut R) -> Option<Vec<Symbol<Nt, T>>>
+        where R: Rng + ?Sized
+    {
+        match self.mode {
+            RewriteMode::Parallel   => self.step_parallel(symbols, rng),
+            RewriteMode::Sequential => self.step_sequential(symbols, rng)
+        }
+    }
+
+    //
+    // Service
+    //
+
+    fn step_parallel<R>(&self, symbols: &[Symbol<Nt, T>], rng: &mut R) -> Option<Vec<Symbol<Nt, T>>>
+        where R: Rng + ?Sized
+    {
+        let mut rewritten_symbols = Vec::with_capacity(symbols.len());
+        let mut is_rewritten      = false;
+        let mut position          = 0;
+
+        while position < symbols.len() {
+            match self.select_rule(symbols, position, rng) {
+                Some(rule) => {
+                    rewritten_symbols.extend(rule.replacement.iter().cloned());
+
+                    position    += rule.pattern.len();
+                    is_rewritten = true;
+                },
+                None => {
+                    rewritten_symbols.push(symbols[position].clone());
+
+                    position += 1;
+                }
+            }
+        }
+
+        if is_rewritten { Some(rewritten_symbols) } else { None }
+    }
+
+    fn step_sequential<R>(&self, symbols: &[Symbol<Nt, T>], rng: &mut R) -> Option<Vec<Symbol<Nt, T>>>
+        where R: Rng + ?Sized
+    {
+        (0..symbols.len()).find_map(|position| {
+            self.select_rule(symbols, position, rng)
+                .map(|rule| {
+                    let mut rewritten_symbols = symbols.to_vec();
+
+                    rewritten_symbols.splice(position..(position + rule.pattern.len()), rule.replacement.iter().cloned());
+
+                    rewritten_symbols
+                })
+        })
+    }
+
+    fn is_applicable_at(&self, symbols: &[Symbol<Nt, T>], position: usize) -> bool {
+        self.rules.iter().any(|rule| rule.is_applicable(symbols, position))
+    }
+
+    /// Selects one of the most specific rules applicable at `position` with probability proportional to its weight,
+    /// or the first one of them, if their total weight is infinite.
+    fn select_rule<R>(&self, symbols: &[Symbol<Nt, T>], position: usize, rng: &mut R) -> Option<&RewriteRule<Nt, T>>
+        where R: Rng + ?Sized
+    {
+        let mut max_specificity = 0;
+        let mut total_weight    = 0.0;
+        let mut first_rule      = None;
+        let mut selected_rule   = None;
+
+        // Weighted reservoir sampling selects among the most specific rules in a single pass:
+        // every rule replaces the rule selected so far with probability of its weight divided by the total weight so far.
+        for rule in self.rules.iter().filter(|rule| rule.is_applicable(symbols, position)) {
+            let specificity = rule.specificity();
+
+            if first_rule.is_none() || specificity > max_specificity {
+                max_specificity = specificity;
+                total_weight    = rule.weight;
+                first_rule      = Some(rule);
+                selected_rule   = Some(rule);
+            } else if specificity == max_specificity {
+                total_weight += rule.weight;
+
+                if total_weight.is_finite() && rng.gen_range(0.0, total_weight) < rule.weight {
+                    selected_rule = Some(rule);
+                }
+            }
+        }
+
+        if total_weight.is_finite() { selected_rule } else { first_rule }
+    }
+}
+
+//
+// Rewritten<Nt, T>
+//
+
+/// Result of [`Rewriter::rewrite()`](struct.Rewriter.html#method.rewrite).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rewritten<Nt, T> {
+    /// The rewritten sequence of symbols.
+    pub symbols:     Vec<Symbol<Nt, T>>,
+    /// Number of performed iterations.
+    pub iterations:  usize,
+    /// `true` if no rule applies to `symbols`, i.e. rewriting stopped before reaching the maximum number of iterations
+    /// or would stop at the next one.
+    pub is_complete: bool
+}
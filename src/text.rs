@@ -0,0 +1,455 @@
+//
+// Constants
+//
+
+const DEFAULT_GLUE_MARKER:   &str = "~";
+const DEFAULT_PLURAL_MARKER: &str = "~s";
+
+/// Words, which have irregular plural forms in English.
+const ENGLISH_IRREGULAR_PLURALS: &[(&str, &str)] = &[
+    ("man",    "men"),
+    ("woman",  "women"),
+    ("child",  "children"),
+    ("person", "people"),
+    ("mouse",  "mice"),
+    ("louse",  "lice"),
+    ("goose",  "geese"),
+    ("foot",   "feet"),
+    ("tooth",  "teeth"),
+    ("ox",     "oxen"),
+    ("die",    "dice"),
+    ("knife",  "knives"),
+    ("wife",   "wives"),
+    ("life",   "lives"),
+    ("leaf",   "leaves"),
+    ("loaf",   "loaves"),
+    ("wolf",   "wolves"),
+    ("half",   "halves"),
+    ("calf",   "calves"),
+    ("elf",    "elves"),
+    ("shelf",  "shelves"),
+    ("thief",  "thieves"),
+    ("self",   "selves"),
+    ("hero",   "heroes"),
+    ("potato", "potatoes"),
+    ("tomato", "tomatoes"),
+    ("echo",   "echoes"),
+    ("veto",   "vetoes")
+];
+
+/// Words, whose plural forms are the same as singular ones in English.
+const ENGLISH_UNINFLECTED: &[&str] = &[
+    "sheep",
+    "fish",
+    "deer",
+    "moose",
+    "series",
+    "species",
+    "aircraft",
+    "offspring"
+];
+
+/// Beginnings of English words, which start with a vowel letter but not with a vowel sound.
+const ENGLISH_CONSONANT_SOUND_PREFIXES: &[&str] = &[
+    "uni", "use", "usu", "uti", "ura", "ure", "uri", "uro", "ubiq", "eu", "ewe", "one", "once"
+];
+
+/// Beginnings of English words, which start with a consonant letter or a vowel letter pronounced as a consonant,
+/// but nevertheless with a vowel sound.
+const ENGLISH_VOWEL_SOUND_PREFIXES: &[&str] = &[
+    "unid", "unim", "unin", "hour", "honest", "honor", "honour", "heir"
+];
+
+/// Letters, whose English names start with a vowel sound, used for acronyms like "FBI".
+const ENGLISH_VOWEL_SOUND_LETTERS: &str = "aefhilmnorsx";
+
+/// Contractions, which are written without a space before them in English.
+const ENGLISH_CONTRACTIONS: &[&str] = &["'s", "'re", "'ve", "'ll", "'d", "'m", "n't"];
+
+//
+// Interface traits
+//
+
+/// Describes language-specific rules used by [`TextFormatter`](struct.TextFormatter.html).
+///
+/// Default implementations of all methods are language-neutral: they handle common punctuation,
+/// while leaving articles and plural forms unchanged.
+pub trait Language {
+    /// Returns `true` if `token` must not be separated by a space from the preceding one,
+    /// e.g. if it is a comma or a closing parenthesis.
+    fn attaches_to_previous(&self, token: &str) -> bool {
+        is_closing_punctuation(token)
+    }
+
+    /// Returns `true` if `token` must not be separated by a space from the following one,
+    /// e.g. if it is an opening parenthesis.
+    fn attaches_to_next(&self, token: &str) -> bool {
+        token.ends_with(['(', '[', '{'])
+    }
+
+    /// Returns `true` if the word following `token` starts a new sentence.
+    fn ends_sentence(&self, token: &str) -> bool {
+        token.ends_with(['.', '!', '?'])
+    }
+
+    /// If `word` is an article, which depends on the following word, returns its form agreeing with `next_word`.
+    fn agree_article(&self, _word: &str, _next_word: &str) -> Option<String> {
+        None
+    }
+
+    /// Returns the plural form of `word`.
+    fn pluralize(&self, word: &str) -> String {
+        word.to_string()
+    }
+}
+
+//
+// Interface types
+//
+
+//
+// English: Language + Debug + Clone + Copy + Default
+//
+
+/// English [`Language`](trait.Language.html) rules.
+///
+/// Indefinite articles "a" and "an" are chosen according to whether the next word starts with a vowel sound,
+/// using a heuristic which handles common exceptions like "a unicorn" and "an hour", numbers like "an 8"
+/// and acronyms like "an FBI agent". Plural forms are produced by regular English suffixes,
+/// except for a small set of common irregular and uninflected nouns.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct English;
+
+impl Language for English {
+    fn attaches_to_previous(&self, token: &str) -> bool {
+        is_closing_punctuation(token) || ENGLISH_CONTRACTIONS.contains(&token.to_lowercase().as_str())
+    }
+
+    fn agree_article(&self, word: &str, next_word: &str) -> Option<String> {
+        let lowercase_word = word.to_lowercase();
+
+        if lowercase_word != "a" && lowercase_word != "an" {
+            return None;
+        }
+
+        let article = if starts_with_english_vowel_sound(next_word) { "an" } else { "a" };
+
+        Some(match_case(article, word))
+    }
+
+    fn pluralize(&self, word: &str) -> String {
+        let lowercase_word = word.to_lowercase();
+
+        let plural = if ENGLISH_UNINFLECTED.contains(&lowercase_word.as_str()) {
+            lowercase_word
+        } else if let Some(&(_, plural)) = ENGLISH_IRREGULAR_PLURALS.iter().find(|(singular, _)| *singular == lowercase_word) {
+            plural.to_string()
+        } else if ["s", "x", "z", "ch", "sh"].iter().any(|suffix| lowercase_word.ends_with(suffix)) {
+            format!("{}es", lowercase_word)
+        } else if lowercase_word.ends_with('y') && !lowercase_word[..lowercase_word.len() - 1].ends_with(is_vowel) {
+            format!("{}ies", &lowercase_word[..lowercase_word.len() - 1])
+        } else {
+            format!("{}s", lowercase_word)
+        };
+
+        match_case(&plural, word)
+    }
+}
+
+//
+// TextFormatter<L>
+//
+
+/// Joins terminal values of a fully expanded sequence, e.g. one returned by
+/// [`Expander::expand()`](struct.Expander.html#method.expand), into text.
+///
+/// Terminal values are split into words by whitespace, which are then joined with single spaces,
+/// except before closing punctuation and after opening punctuation
+/// (see [`Language`](trait.Language.html)). Additionally `TextFormatter` capitalizes the first word of every sentence
+/// and makes articles agree with the words following them. Terminal values consisting only of a marker
+/// are not included in the text, but change how the neighbouring words are formatted:
+///
+/// * the glue marker (`"~"` by default) joins the previous and the next word without a space,
+/// * the plural marker (`"~s"` by default) replaces the previous word with its plural form.
+///
+/// ```
+/// use branchy::TextFormatter;
+///
+/// let formatter = TextFormatter::new();
+///
+/// assert_eq!(
+///     formatter.format(vec!["bob", "comes from a", "old", "village", ",", "where", "wolf", "~s", "live", "."]),
+///     "Bob comes from an old village, where wolves live."
+/// );
+/// assert_eq!(formatter.format(vec!["super", "~", "market"]), "Supermarket");
+/// ```
+#[derive(Debug, Clone)]
+pub struct TextFormatter<L = English>
+    where L: Language
+{
+    language:             L,
+    glue_marker:          String,
+    plural_marker:        String,
+    capitalize_sentences: bool,
+    agree_articles:       bool
+}
+
+impl TextFormatter<English> {
+    /// Constructs a new `TextFormatter` for [`English`](struct.English.html) text with default markers,
+    /// capitalizing sentences and making articles agree.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_language(English)
+    }
+}
+
+impl Default for TextFormatter<English> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L> TextFormatter<L>
+    where L: Language
+{
+    /// Constructs a new `TextFormatter` for the given [`Language`](trait.Language.html) with default markers,
+    /// capitalizing sentences and making articles agree.
+    #[must_use]
+    pub fn with_language(language: L) -> Self {
+        Self{
+            language,
+            glue_marker:          DEFAULT_GLUE_MARKER.to_string(),
+            plural_marker:        DEFAULT_PLURAL_MARKER.to_string(),
+            capitalize_sentences: true,
+            agree_articles:       true
+        }
+    }
+
+    /// Sets the terminal value, which joins the previous and the next word without a space.
+    pub fn with_glue_marker<S>(self, glue_marker: S) -> Self
+        where S: Into<String>
+    {
+        Self{
+            glue_marker: glue_marker.into(),
+            ..self
+        }
+    }
+
+    /// Sets the terminal value, which replaces the previous word with its plural form.
+    pub fn with_plural_marker<S>(self, plural_marker: S) -> Self
+        where S: Into<String>
+    {
+        Self{
+            plural_marker: plural_marker.into(),
+            ..self
+        }
+    }
+
+    /// Sets whether the first word of every sentence is capitalized.
+    pub fn with_sentence_capitalization(self, capitalize_sentences: bool) -> Self {
+        Self{
+            capitalize_sentences,
+            ..self
+        }
+    }
+
+    /// Sets whether articles are made to agree with the words following them.
+    pub fn with_article_agreement(self, agree_articles: bool) -> Self {
+        Self{
+            agree_articles,
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn language(&self) -> &L {
+        &self.language
+    }
+
+    /// Formats a sequence of terminal values as text.
+    pub fn format<I>(&self, terminal_values: I) -> String
+        where I:       IntoIterator,
+              I::Item: AsRef<str>
+    {
+        let mut words = self.split_words(terminal_values);
+
+        if self.agree_articles {
+            for word_idx in 1..words.len() {
+                if let Some(article) = self.language.agree_article(&words[word_idx - 1].text, &words[word_idx].text) {
+                    words[word_idx - 1].text = article;
+                }
+            }
+        }
+
+        if self.capitalize_sentences {
+            let mut is_sentence_start = true;
+
+            for word in &mut words {
+                if is_sentence_start {
+                    is_sentence_start = !capitalize(&mut word.text);
+                }
+
+                if self.language.ends_sentence(&word.text) {
+                    is_sentence_start = true;
+                }
+            }
+        }
+
+        let mut text = String::new();
+
+        for word in &words {
+            if !text.is_empty() && !word.is_attached {
+                text.push(' ');
+            }
+
+            text.push_str(&word.text);
+        }
+
+        text
+    }
+
+    //
+    // Service
+    //
+
+    fn split_words<I>(&self, terminal_values: I) -> Vec<Word>
+        where I:       IntoIterator,
+              I::Item: AsRef<str>
+    {
+        let mut words: Vec<Word> = Vec::new();
+
+        let mut is_glued = false;
+
+        for terminal_value in terminal_values {
+            let terminal_value = terminal_value.as_ref();
+
+            if terminal_value == self.glue_marker {
+                is_glued = true;
+
+                continue;
+            }
+
+            if terminal_value == self.plural_marker {
+                if let Some(last_word) = words.last_mut() {
+                    last_word.text = self.language.pluralize(&last_word.text);
+                }
+
+                continue;
+            }
+
+            for (word_idx, word) in terminal_value.split_whitespace().enumerate() {
+                let is_attached = (word_idx == 0 && is_glued)
+                    || self.language.attaches_to_previous(word)
+                    || words.last().is_some_and(|last_word| self.language.attaches_to_next(&last_word.text));
+
+                words.push(Word{
+                    text: word.to_string(),
+                    is_attached
+                });
+
+                is_glued = false;
+            }
+        }
+
+        words
+    }
+}
+
+//
+// Service types
+//
+
+struct Word {
+    text:        String,
+    is_attached: bool
+}
+
+//
+// Friend
+//
+
+/// Capitalizes the first alphanumeric character of `word`, returning `false` if there is none.
+pub(crate) fn capitalize(word: &mut String) -> bool {
+    match word.char_indices().find(|(_, c)| c.is_alphanumeric()) {
+        Some((char_idx, c)) => {
+            let uppercase: String = c.to_uppercase().collect();
+
+            word.replace_range(char_idx..char_idx + c.len_utf8(), &uppercase);
+
+            true
+        },
+        None => false
+    }
+}
+
+//
+// Service
+//
+
+fn is_closing_punctuation(token: &str) -> bool {
+    token.starts_with(['.', ',', ';', ':', '!', '?', ')', ']', '}', '%', '…'])
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'e' | 'i' | 'o' | 'u')
+}
+
+/// Returns lowercase `word` converted to the same case as `example`:
+/// uppercase, if `example` is an uppercase acronym, or capitalized, if it starts with an uppercase letter.
+fn match_case(word: &str, example: &str) -> String {
+    let example_letters: Vec<char> = example.chars()
+        .filter(|c| c.is_alphabetic())
+        .collect();
+
+    if example_letters.len() > 1 && example_letters.iter().all(|c| c.is_uppercase()) {
+        word.to_uppercase()
+    } else if example_letters.first().is_some_and(|c| c.is_uppercase()) {
+        let mut capitalized = word.to_string();
+
+        capitalize(&mut capitalized);
+
+        capitalized
+    } else {
+        word.to_string()
+    }
+}
+
+fn starts_with_english_vowel_sound(word: &str) -> bool {
+    let word = word.trim_start_matches(|c: char| !c.is_alphanumeric());
+
+    let first_char = match word.chars().next() {
+        Some(first_char) => first_char,
+        None             => return false
+    };
+
+    if first_char.is_ascii_digit() {
+        // "an 8", "an 11", "an 18", "an 11,000", but "a 1", "a 110" and "a 180".
+        let digits: String = word.chars()
+            .take_while(|c| c.is_ascii_digit() || *c == ',')
+            .filter(char::is_ascii_digit)
+            .collect();
+
+        return first_char == '8'
+            || ((digits.starts_with("11") || digits.starts_with("18")) && digits.len() % 3 == 2);
+    }
+
+    let letters: String = word.chars()
+        .take_while(|c| c.is_alphabetic())
+        .collect();
+
+    let is_spelled_acronym = letters.chars().count() > 1
+        && letters.chars().all(char::is_uppercase)
+        && (letters.chars().count() <= 3 || !letters.to_lowercase().chars().any(is_vowel));
+
+    let lowercase_word = word.to_lowercase();
+
+    if is_spelled_acronym {
+        lowercase_word.starts_with(|c| ENGLISH_VOWEL_SOUND_LETTERS.contains(c))
+    } else if ENGLISH_VOWEL_SOUND_PREFIXES.iter().any(|prefix| lowercase_word.starts_with(prefix)) {
+        true
+    } else if ENGLISH_CONSONANT_SOUND_PREFIXES.iter().any(|prefix| lowercase_word.starts_with(prefix)) {
+        false
+    } else {
+        lowercase_word.starts_with(is_vowel)
+    }
+}
@@ -0,0 +1,740 @@
+#[cfg(test)]
+mod tests;
+
+use crate::grammar::{
+    NonterminalValue,
+    TerminalValue,
+    Symbol,
+    Rule,
+    Modifier,
+    indexed::IndexedGrammar
+};
+
+//
+// Interface
+//
+
+/// Returns values of non-terminal symbols, from which an empty sequence can be derived, in the order of their
+/// first appearance as rule patterns.
+pub fn nullable_nonterms<Nt, T>(rules: &[Rule<Nt, T>]) -> Vec<Nt>
+    where Nt: NonterminalValue
+{
+    let mut nullable_nonterms: Vec<Nt> = Vec::new();
+
+    let mut changed = true;
+
+    while changed {
+        changed = false;
+
+        for rule in rules {
+            if nullable_nonterms.contains(&rule.pattern) {
+                continue;
+            }
+
+            let is_nullable = rule.replacement.iter().all(|symbol| match symbol {
+                Symbol::Nonterminal(value) => nullable_nonterms.contains(value),
+                Symbol::Terminal(_)        => false
+            });
+
+            if is_nullable {
+                nullable_nonterms.push(rule.pattern.clone());
+
+                changed = true;
+            }
+        }
+    }
+
+    patterns(rules).into_iter()
+        .filter(|pattern| nullable_nonterms.contains(pattern))
+        .collect()
+}
+
+/// Removes rules with empty replacements, adding variants of the remaining rules with
+/// [nullable](fn.nullable_nonterms.html) non-terminal symbols omitted.
+///
+/// The resulting rules derive the same sequences as the original ones, except for the empty sequence,
+/// which can no longer be derived. The number of added variants is exponential in the number of nullable
+/// symbols in a single replacement. Every variant keeps the weight of its original rule, along with the modifiers
+/// attached to the symbols, which are not omitted.
+///
+/// Weights are therefore not redistributed between the variants and do not preserve the probabilities
+/// of derivations under [`WeightedRandomRuleSelector`](struct.WeightedRandomRuleSelector.html): a rule with
+/// a nullable symbol gains weight compared to the other rules for the same non-terminal symbol, in proportion
+/// to the number of its variants, regardless of how likely the omitted symbols were to derive the empty sequence.
+/// Adjust the weights of the resulting rules, e.g. by [training them](struct.WeightTrainer.html) on a sample,
+/// if the distribution of the generated sequences matters.
+pub fn remove_empty_rules<Nt, T>(rules: Vec<Rule<Nt, T>>) -> Vec<Rule<Nt, T>>
+    where Nt: NonterminalValue,
+          T:  TerminalValue
+{
+    let nullable_nonterms = nullable_nonterms(&rules);
+
+    let mut result = Vec::new();
+
+    for rule in rules {
+        let variants = replacement_variants(&rule.replacement, &nullable_nonterms).into_iter()
+            .filter(|kept_positions| match kept_positions.as_slice() {
+                []         => false,
+                [position] => !matches!(&rule.replacement[*position], Symbol::Nonterminal(value) if *value == rule.pattern),
+                _          => true
+            });
+
+        for variant in variants {
+            result.push(Rule{
+                pattern:     rule.pattern.clone(),
+                replacement: variant.iter()
+                    .map(|position| rule.replacement[*position].clone())
+                    .collect(),
+                weight:      rule.weight,
+                modifiers:   remap_modifiers(&rule.modifiers, |symbol_idx| {
+                    variant.iter().position(|position| *position == symbol_idx)
+                })
+            });
+        }
+    }
+
+    result
+}
+
+/// Removes unit rules (ones replacing a non-terminal symbol with a single other non-terminal symbol),
+/// giving every non-terminal symbol copies of the other rules of all non-terminal symbols it could be replaced with.
+///
+/// The resulting rules derive the same sequences as the original ones. They are grouped by pattern,
+/// in the order of first appearance of patterns in the original rules. Every copy keeps the weight and modifiers
+/// of the copied rule, while modifiers attached to the symbols of the removed unit rules are dropped.
+pub fn remove_unit_rules<Nt, T>(rules: Vec<Rule<Nt, T>>) -> Vec<Rule<Nt, T>>
+    where Nt: NonterminalValue,
+          T:  TerminalValue
+{
+    let mut result = Vec::new();
+
+    for pattern in patterns(&rules) {
+        for unit_nonterm in unit_closure(&rules, &pattern) {
+            for rule in rules.iter().filter(|rule| rule.pattern == unit_nonterm && !is_unit_rule(rule)) {
+                result.push(Rule{
+                    pattern: pattern.clone(),
+                    ..rule.clone()
+                });
+            }
+        }
+    }
+
+    result
+}
+
+/// Converts rules to Chomsky Normal Form, in which every rule replaces a non-terminal symbol
+/// either with a single terminal symbol or with exactly two non-terminal symbols.
+///
+/// Longer replacements are split into chains of rules with two symbols each, before empty and unit rules are removed
+/// (see [`remove_empty_rules()`](fn.remove_empty_rules.html) and [`remove_unit_rules()`](fn.remove_unit_rules.html)),
+/// so the number of resulting rules stays proportional to the total length of the replacements. The empty sequence
+/// can no longer be derived, while all other sequences derived by the original rules still can. Values
+/// of the additional non-terminal symbols are produced by `new_nonterm`, which must return a distinct value
+/// on every call. The first rule of every chain keeps the weight of the original rule, while the added rules
+/// have the default weight of `1.0`. Modifiers move along with their symbols, as described
+/// for [`remove_empty_rules()`](fn.remove_empty_rules.html) and [`remove_unit_rules()`](fn.remove_unit_rules.html),
+/// and modifiers of a terminal symbol are attached to the non-terminal symbol replacing it.
+///
+/// ```
+/// use branchy::{
+///     Symbol,
+///     Rule,
+///     to_chomsky_normal_form
+/// };
+///
+/// let rules = vec![
+///     Rule::new("list".to_string(), vec![Symbol::Terminal("item")]),
+///     Rule::new(
+///         "list".to_string(),
+///         vec![Symbol::Terminal("item"), Symbol::Terminal(","), Symbol::Nonterminal("list".to_string())]
+///     )
+/// ];
+///
+/// let mut nonterm_count = 0;
+///
+/// let cnf_rules = to_chomsky_normal_form(rules, || {
+///     nonterm_count += 1;
+///
+///     format!("N{}", nonterm_count)
+/// });
+///
+/// assert!(cnf_rules.iter().all(|rule| match rule.replacement.as_slice() {
+///     [Symbol::Terminal(_)]                             => true,
+///     [Symbol::Nonterminal(_), Symbol::Nonterminal(_)] => true,
+///     _                                                 => false
+/// }));
+/// ```
+pub fn to_chomsky_normal_form<Nt, T, F>(rules: Vec<Rule<Nt, T>>, mut new_nonterm: F) -> Vec<Rule<Nt, T>>
+    where Nt: NonterminalValue,
+          T:  TerminalValue + PartialEq,
+          F:  FnMut() -> Nt
+{
+    let rules = binarize_rules(rules, &mut new_nonterm);
+
+    dedup_rules(remove_unit_rules(remove_empty_rules(rules)))
+}
+
+/// Converts rules to Greibach Normal Form, in which every rule replaces a non-terminal symbol
+/// with a terminal symbol, followed by any number of non-terminal symbols.
+///
+/// The rules are converted to [Chomsky Normal Form](fn.to_chomsky_normal_form.html) first, so the empty sequence
+/// can no longer be derived, while all other sequences derived by the original rules still can. Rules, which cannot
+/// be used to derive any fully expanded sequence, are removed. Values of the additional non-terminal symbols
+/// are produced by `new_nonterm`, which must return a distinct value on every call.
+///
+/// Weights are carried over as described for [`to_chomsky_normal_form()`](fn.to_chomsky_normal_form.html)
+/// and [`remove_left_recursion()`](fn.remove_left_recursion.html).
+///
+/// Note that the number of resulting rules may be considerably larger than that of the original ones.
+pub fn to_greibach_normal_form<Nt, T, F>(rules: Vec<Rule<Nt, T>>, mut new_nonterm: F) -> Vec<Rule<Nt, T>>
+    where Nt: NonterminalValue,
+          T:  TerminalValue + PartialEq,
+          F:  FnMut() -> Nt
+{
+    let rules = remove_unproductive_rules(to_chomsky_normal_form(rules, &mut new_nonterm));
+
+    let mut grammar = OrderedRules::new(rules);
+
+    let original_nonterm_count = grammar.nonterms.len();
+
+    grammar.remove_left_recursion(&mut new_nonterm);
+
+    // After removal of left recursion every replacement of an original non-terminal starts either with a terminal
+    // or with a non-terminal, which is later in the order, so substituting backwards makes all of them start
+    // with terminals. Replacements of each added non-terminal start with original or previously added non-terminals.
+    for nonterm_idx in (0..original_nonterm_count).rev() {
+        for leading_nonterm_idx in (nonterm_idx + 1)..original_nonterm_count {
+            grammar.substitute_leading_nonterm(nonterm_idx, leading_nonterm_idx);
+        }
+    }
+
+    for nonterm_idx in original_nonterm_count..grammar.nonterms.len() {
+        for leading_nonterm_idx in 0..nonterm_idx {
+            grammar.substitute_leading_nonterm(nonterm_idx, leading_nonterm_idx);
+        }
+    }
+
+    dedup_rules(grammar.into_rules())
+}
+
+/// Removes both direct (`A -> A x`) and indirect (`A -> B x`, `B -> A y`) left recursion,
+/// i.e. the possibility of deriving a sequence starting with a non-terminal symbol from that same symbol.
+///
+/// Empty rules are removed first (see [`remove_empty_rules()`](fn.remove_empty_rules.html)),
+/// so the empty sequence can no longer be derived, while all other sequences derived by the original rules still can.
+/// If unit rules form cycles (`A -> B`, `B -> A`), unit rules are removed as well
+/// (see [`remove_unit_rules()`](fn.remove_unit_rules.html)). Values of the additional non-terminal symbols
+/// are produced by `new_nonterm`, which must return a distinct value on every call.
+///
+/// The weight of every rule is split between the rules replacing it, so that rewriting left recursion
+/// doesn't change the probability of any derivation, although removing empty rules does. Modifiers move along with their symbols, except for those
+/// attached to the leading non-terminal symbols, which are substituted or removed.
+pub fn remove_left_recursion<Nt, T, F>(rules: Vec<Rule<Nt, T>>, mut new_nonterm: F) -> Vec<Rule<Nt, T>>
+    where Nt: NonterminalValue,
+          T:  TerminalValue,
+          F:  FnMut() -> Nt
+{
+    let mut rules = remove_empty_rules(rules);
+
+    if has_unit_cycle(&rules) {
+        rules = remove_unit_rules(rules);
+    }
+
+    let mut grammar = OrderedRules::new(rules);
+
+    grammar.remove_left_recursion(&mut new_nonterm);
+
+    grammar.into_rules()
+}
+
+/// Converts values of all non-terminal symbols in `rules` using `map_nonterm` and values of all terminal symbols
+/// using `map_term` (see [`Rule::map()`](struct.Rule.html#method.map)), e.g. in order to author a grammar
+/// with string values and expand it with interned identifiers or enums.
+///
+/// ```
+/// use branchy::{
+///     Symbol,
+///     Rule,
+///     map_grammar
+/// };
+///
+/// let rules = vec![
+///     Rule::new("greeting", vec![Symbol::Terminal("hello"), Symbol::Nonterminal("name")]),
+///     Rule::new("name", vec![Symbol::Terminal("world")])
+/// ];
+///
+/// let mut nonterms: Vec<&str> = Vec::new();
+///
+/// let interned_rules = map_grammar(
+///     rules,
+///     |value| match nonterms.iter().position(|nonterm| *nonterm == value) {
+///         Some(idx) => idx,
+///         None      => {
+///             nonterms.push(value);
+///
+///             nonterms.len() - 1
+///         }
+///     },
+///     String::from
+/// );
+///
+/// assert_eq!(interned_rules[1], Rule::new(1, vec![Symbol::Terminal("world".to_string())]));
+/// ```
+pub fn map_grammar<Nt, T, Nt2, T2, FN, FT>(rules: Vec<Rule<Nt, T>>, mut map_nonterm: FN, mut map_term: FT) -> Vec<Rule<Nt2, T2>>
+    where FN: FnMut(Nt) -> Nt2,
+          FT: FnMut(T) -> T2
+{
+    rules.into_iter()
+        .map(|rule| rule.map(&mut map_nonterm, &mut map_term))
+        .collect()
+}
+
+/// Fallible version of [`map_grammar()`](fn.map_grammar.html), stopping at the first error produced by either function,
+/// e.g. when a value has no counterpart in the target representation.
+///
+/// # Errors
+/// Returns the first error produced by `map_nonterm` or `map_term`.
+pub fn try_map_grammar<Nt, T, Nt2, T2, E, FN, FT>(
+    rules:           Vec<Rule<Nt, T>>,
+    mut map_nonterm: FN,
+    mut map_term:    FT
+) -> Result<Vec<Rule<Nt2, T2>>, E>
+    where FN: FnMut(Nt) -> Result<Nt2, E>,
+          FT: FnMut(T) -> Result<T2, E>
+{
+    rules.into_iter()
+        .map(|rule| rule.try_map(&mut map_nonterm, &mut map_term))
+        .collect()
+}
+
+//
+// Service types
+//
+
+//
+// OrderedRules<Nt, T>
+//
+
+/// Rules of every non-terminal symbol, in the order of first appearance of patterns,
+/// followed by non-terminal symbols added during transformations.
+struct OrderedRules<Nt, T> {
+    nonterms: Vec<Nt>,
+    rules:    Vec<Vec<Rule<Nt, T>>>
+}
+
+impl<Nt, T> OrderedRules<Nt, T>
+    where Nt: NonterminalValue,
+          T:  TerminalValue
+{
+    fn new(rules: Vec<Rule<Nt, T>>) -> Self {
+        let nonterms = patterns(&rules);
+
+        let mut nonterm_rules: Vec<Vec<_>> = (0..nonterms.len()).map(|_| Vec::new()).collect();
+
+        for rule in rules {
+            let nonterm_idx = nonterms.iter()
+                .position(|nonterm| *nonterm == rule.pattern)
+                .expect("every pattern must be among non-terminals");
+
+            nonterm_rules[nonterm_idx].push(rule);
+        }
+
+        Self{
+            nonterms,
+            rules: nonterm_rules
+        }
+    }
+
+    fn into_rules(self) -> Vec<Rule<Nt, T>> {
+        self.rules.into_iter()
+            .flatten()
+            .collect()
+    }
+
+    fn leading_nonterm_idx(&self, replacement: &[Symbol<Nt, T>]) -> Option<usize> {
+        match replacement.first() {
+            Some(Symbol::Nonterminal(value)) => self.nonterms.iter().position(|nonterm| nonterm == value),
+            _                                => None
+        }
+    }
+
+    /// Removes left recursion using the well-known algorithm, which orders non-terminals
+    /// and ensures that replacements of each of them only start with non-terminals, which are later in the order.
+    /// Requires that there are no empty rules and no cycles of unit rules.
+    fn remove_left_recursion<F>(&mut self, new_nonterm: &mut F)
+        where F: FnMut() -> Nt
+    {
+        let original_nonterm_count = self.nonterms.len();
+
+        for nonterm_idx in 0..original_nonterm_count {
+            for leading_nonterm_idx in 0..nonterm_idx {
+                self.substitute_leading_nonterm(nonterm_idx, leading_nonterm_idx);
+            }
+
+            self.remove_direct_left_recursion(nonterm_idx, new_nonterm);
+        }
+    }
+
+    /// Replaces every rule of `nonterm_idx`, whose replacement starts with `leading_nonterm_idx`,
+    /// with copies, in which that non-terminal is replaced with each of its own replacements.
+    /// The weight of the replaced rule is split between the copies in proportion to the weights of those replacements.
+    fn substitute_leading_nonterm(&mut self, nonterm_idx: usize, leading_nonterm_idx: usize) {
+        if leading_nonterm_idx == nonterm_idx {
+            return;
+        }
+
+        let rules = std::mem::take(&mut self.rules[nonterm_idx]);
+
+        let mut substituted_rules = Vec::new();
+
+        for rule in rules {
+            if self.leading_nonterm_idx(&rule.replacement) == Some(leading_nonterm_idx) {
+                let leading_rules = &self.rules[leading_nonterm_idx];
+
+                let leading_weight = total_weight(leading_rules);
+
+                for leading_rule in leading_rules {
+                    let leading_len = leading_rule.replacement.len();
+
+                    let mut modifiers = leading_rule.modifiers.clone();
+
+                    modifiers.extend(remap_modifiers(&rule.modifiers, |symbol_idx| {
+                        symbol_idx.checked_sub(1).map(|symbol_idx| symbol_idx + leading_len)
+                    }));
+
+                    substituted_rules.push(Rule{
+                        pattern:     rule.pattern.clone(),
+                        replacement: leading_rule.replacement.iter()
+                            .chain(&rule.replacement[1..])
+                            .cloned()
+                            .collect(),
+                        weight:      split_weight(rule.weight, leading_rule.weight, leading_weight),
+                        modifiers
+                    });
+                }
+            } else {
+                substituted_rules.push(rule);
+            }
+        }
+
+        self.rules[nonterm_idx] = substituted_rules;
+    }
+
+    /// Turns `A -> A a | b` into `A -> b | b A'`, `A' -> a | a A'`.
+    ///
+    /// Weights of both kinds of the original rules are split between the rules with and without `A'` in proportion
+    /// to the total weights of the non-recursive and recursive rules, which keeps the probabilities of derivations.
+    fn remove_direct_left_recursion<F>(&mut self, nonterm_idx: usize, new_nonterm: &mut F)
+        where F: FnMut() -> Nt
+    {
+        let rules = std::mem::take(&mut self.rules[nonterm_idx]);
+
+        let (recursive_rules, other_rules): (Vec<_>, Vec<_>) = rules.into_iter()
+            .partition(|rule| self.leading_nonterm_idx(&rule.replacement) == Some(nonterm_idx));
+
+        let recursive_rules: Vec<_> = recursive_rules.into_iter()
+            .filter(|rule| rule.replacement.len() > 1)
+            .collect();
+
+        if recursive_rules.is_empty() {
+            self.rules[nonterm_idx] = other_rules;
+
+            return;
+        }
+
+        let tail_nonterm = new_nonterm();
+
+        let other_weight     = total_weight(&other_rules);
+        let recursive_weight = total_weight(&recursive_rules);
+
+        let tail_rules = recursive_rules.into_iter()
+            .map(|mut rule| Rule{
+                pattern:     tail_nonterm.clone(),
+                replacement: rule.replacement.split_off(1),
+                weight:      rule.weight,
+                modifiers:   remap_modifiers(&rule.modifiers, |symbol_idx| symbol_idx.checked_sub(1))
+            })
+            .collect();
+
+        self.rules[nonterm_idx] = with_optional_suffix(other_rules, &tail_nonterm, other_weight, recursive_weight);
+
+        self.nonterms.push(tail_nonterm.clone());
+        self.rules.push(with_optional_suffix(tail_rules, &tail_nonterm, other_weight, recursive_weight));
+    }
+}
+
+//
+// Service
+//
+
+/// Returns distinct rule patterns in the order of first appearance.
+fn patterns<Nt, T>(rules: &[Rule<Nt, T>]) -> Vec<Nt>
+    where Nt: NonterminalValue
+{
+    let mut patterns: Vec<Nt> = Vec::new();
+
+    for rule in rules {
+        if !patterns.contains(&rule.pattern) {
+            patterns.push(rule.pattern.clone());
+        }
+    }
+
+    patterns
+}
+
+fn is_unit_rule<Nt, T>(rule: &Rule<Nt, T>) -> bool {
+    matches!(rule.replacement.as_slice(), [Symbol::Nonterminal(_)])
+}
+
+/// Returns `nonterm_value` along with values of all non-terminal symbols, which it can be replaced with
+/// using only unit rules.
+fn unit_closure<Nt, T>(rules: &[Rule<Nt, T>], nonterm_value: &Nt) -> Vec<Nt>
+    where Nt: NonterminalValue
+{
+    let mut closure = vec![nonterm_value.clone()];
+
+    let mut closure_idx = 0;
+
+    while closure_idx < closure.len() {
+        let nonterm_value = closure[closure_idx].clone();
+
+        for rule in rules.iter().filter(|rule| rule.pattern == nonterm_value) {
+            if let [Symbol::Nonterminal(value)] = rule.replacement.as_slice() {
+                if !closure.contains(value) {
+                    closure.push(value.clone());
+                }
+            }
+        }
+
+        closure_idx += 1;
+    }
+
+    closure
+}
+
+fn has_unit_cycle<Nt, T>(rules: &[Rule<Nt, T>]) -> bool
+    where Nt: NonterminalValue
+{
+    rules.iter().any(|rule| match rule.replacement.as_slice() {
+        [Symbol::Nonterminal(value)] if *value != rule.pattern => unit_closure(rules, value).contains(&rule.pattern),
+        _                                                      => false
+    })
+}
+
+/// Returns positions of the kept symbols of every distinct variant of `replacement` with any of the nullable
+/// non-terminal symbols omitted, starting with `replacement` itself.
+///
+/// Every variant is produced once, from the leftmost positions of its symbols: a nullable symbol is only kept
+/// if no equal symbol has been omitted since the previous kept one.
+fn replacement_variants<Nt, T>(replacement: &[Symbol<Nt, T>], nullable_nonterms: &[Nt]) -> Vec<Vec<usize>>
+    where Nt: NonterminalValue
+{
+    let mut variants = Vec::new();
+
+    let mut pending_variants = vec![(0, Vec::new())];
+
+    while let Some((position, mut kept_positions)) = pending_variants.pop() {
+        let nullable_value = match replacement.get(position) {
+            Some(Symbol::Nonterminal(value)) if nullable_nonterms.contains(value) => Some(value),
+            Some(_)                                                               => None,
+            None                                                                  => {
+                variants.push(kept_positions);
+
+                continue;
+            }
+        };
+
+        let omitted_start = kept_positions.last().map_or(0, |kept_position| kept_position + 1);
+
+        let can_keep = nullable_value.is_none_or(|nullable_value| {
+            !replacement[omitted_start..position].iter()
+                .any(|symbol| matches!(symbol, Symbol::Nonterminal(value) if value == nullable_value))
+        });
+
+        if nullable_value.is_some() {
+            pending_variants.push((position + 1, kept_positions.clone()));
+        }
+
+        if can_keep {
+            kept_positions.push(position);
+
+            pending_variants.push((position + 1, kept_positions));
+        }
+    }
+
+    variants
+}
+
+/// Replaces terminal symbols in replacements of more than one symbol with non-terminal symbols, each of which
+/// is replaced with that terminal symbol only, and splits replacements of more than two symbols into chains
+/// of rules with two symbols each.
+fn binarize_rules<Nt, T, F>(rules: Vec<Rule<Nt, T>>, new_nonterm: &mut F) -> Vec<Rule<Nt, T>>
+    where Nt: NonterminalValue,
+          T:  TerminalValue + PartialEq,
+          F:  FnMut() -> Nt
+{
+    let mut terminal_nonterms: Vec<(T, Nt)> = Vec::new();
+
+    let mut result = Vec::new();
+
+    for rule in rules {
+        if rule.replacement.len() <= 1 {
+            result.push(rule);
+
+            continue;
+        }
+
+        let first_link_idx = result.len();
+
+        let mut pattern = rule.pattern;
+
+        let mut nonterms: Vec<_> = rule.replacement.into_iter()
+            .map(|symbol| match symbol {
+                Symbol::Nonterminal(value) => value,
+                Symbol::Terminal(value)    => terminal_nonterm(&mut terminal_nonterms, value, new_nonterm)
+            })
+            .collect();
+
+        while nonterms.len() > 2 {
+            let first_nonterm = nonterms.remove(0);
+            let rest_nonterm  = new_nonterm();
+
+            result.push(Rule::new(
+                pattern,
+                vec![Symbol::Nonterminal(first_nonterm), Symbol::Nonterminal(rest_nonterm.clone())]
+            ));
+
+            pattern = rest_nonterm;
+        }
+
+        result.push(Rule::new(pattern, nonterms.into_iter().map(Symbol::Nonterminal).collect()));
+
+        result[first_link_idx].weight = rule.weight;
+
+        // Every symbol but the last two starts its own link, while the last two make up the last link.
+        let last_link_idx = result.len() - 1;
+
+        for modifier in rule.modifiers {
+            let (link_idx, symbol_idx) = if first_link_idx + modifier.symbol_idx < last_link_idx {
+                (first_link_idx + modifier.symbol_idx, 0)
+            } else {
+                (last_link_idx, first_link_idx + modifier.symbol_idx - last_link_idx)
+            };
+
+            result[link_idx].modifiers.push(Modifier{
+                symbol_idx,
+                ..modifier
+            });
+        }
+    }
+
+    for (terminal_value, nonterm_value) in terminal_nonterms {
+        result.push(Rule::new(nonterm_value, vec![Symbol::Terminal(terminal_value)]));
+    }
+
+    result
+}
+
+fn terminal_nonterm<Nt, T, F>(terminal_nonterms: &mut Vec<(T, Nt)>, terminal_value: T, new_nonterm: &mut F) -> Nt
+    where Nt: NonterminalValue,
+          T:  PartialEq,
+          F:  FnMut() -> Nt
+{
+    if let Some((_, nonterm_value)) = terminal_nonterms.iter().find(|(value, _)| *value == terminal_value) {
+        nonterm_value.clone()
+    } else {
+        let nonterm_value = new_nonterm();
+
+        terminal_nonterms.push((terminal_value, nonterm_value.clone()));
+
+        nonterm_value
+    }
+}
+
+fn remove_unproductive_rules<Nt, T>(rules: Vec<Rule<Nt, T>>) -> Vec<Rule<Nt, T>>
+    where Nt: NonterminalValue,
+          T:  TerminalValue
+{
+    let productive_rules = {
+        let grammar = IndexedGrammar::new(&rules);
+
+        grammar.productive_rules(&grammar.minimal_derivations())
+    };
+
+    rules.into_iter()
+        .zip(productive_rules)
+        .filter(|(_, is_productive)| *is_productive)
+        .map(|(rule, _)| rule)
+        .collect()
+}
+
+/// Returns every rule both with and without `suffix_nonterm` appended to its replacement, splitting its weight
+/// between the two in the proportion of `end_weight` to `suffix_weight`.
+fn with_optional_suffix<Nt, T>(
+    rules:          Vec<Rule<Nt, T>>,
+    suffix_nonterm: &Nt,
+    end_weight:     f64,
+    suffix_weight:  f64
+) -> Vec<Rule<Nt, T>>
+    where Nt: NonterminalValue,
+          T:  TerminalValue
+{
+    rules.into_iter()
+        .flat_map(|rule| {
+            let mut suffixed_rule = rule.clone();
+
+            suffixed_rule.replacement.push(Symbol::Nonterminal(suffix_nonterm.clone()));
+            suffixed_rule.weight = split_weight(rule.weight, suffix_weight, end_weight + suffix_weight);
+
+            let weight = split_weight(rule.weight, end_weight, end_weight + suffix_weight);
+
+            vec![rule.with_weight(weight), suffixed_rule]
+        })
+        .collect()
+}
+
+/// Returns copies of `modifiers` attached to the symbol indices returned by `map_symbol_idx`,
+/// dropping the modifiers, for which it returns `None`.
+fn remap_modifiers<F>(modifiers: &[Modifier], mut map_symbol_idx: F) -> Vec<Modifier>
+    where F: FnMut(usize) -> Option<usize>
+{
+    modifiers.iter()
+        .filter_map(|modifier| {
+            map_symbol_idx(modifier.symbol_idx).map(|symbol_idx| Modifier{
+                symbol_idx,
+                name: modifier.name.clone()
+            })
+        })
+        .collect()
+}
+
+fn total_weight<Nt, T>(rules: &[Rule<Nt, T>]) -> f64 {
+    rules.iter()
+        .map(|rule| rule.weight)
+        .sum()
+}
+
+/// Returns the part of `weight` proportional to `part_weight` out of `total_weight`,
+/// or the whole `weight` if `total_weight` is not positive.
+fn split_weight(weight: f64, part_weight: f64, total_weight: f64) -> f64 {
+    if total_weight > 0.0 {
+        weight * part_weight / total_weight
+    } else {
+        weight
+    }
+}
+
+fn dedup_rules<Nt, T>(rules: Vec<Rule<Nt, T>>) -> Vec<Rule<Nt, T>>
+    where Nt: NonterminalValue,
+          T:  TerminalValue + PartialEq
+{
+    let mut result: Vec<Rule<Nt, T>> = Vec::with_capacity(rules.len());
+
+    for rule in rules {
+        if !result.contains(&rule) {
+            result.push(rule);
+        }
+    }
+
+    result
+}
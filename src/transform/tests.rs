@@ -0,0 +1,574 @@
+use super::*;
+
+use std::collections::{
+    BTreeSet,
+    HashSet,
+    VecDeque
+};
+
+//
+// Tests
+//
+
+#[test]
+fn nullable_nonterms_transitive() {
+    let rules = grammar(&[
+        ("S", &["A", "B", "'x'"]),
+        ("A", &["B", "B"]),
+        ("B", &[]),
+        ("B", &["'b'"]),
+        ("C", &["A", "'c'"])
+    ]);
+
+    assert_eq!(nullable_nonterms(&rules), vec!["A".to_string(), "B".to_string()]);
+}
+
+#[test]
+fn remove_empty_rules_preserves_nonempty_sequences() {
+    let rules = grammar(&[
+        ("S", &["A", "'b'", "A"]),
+        ("S", &[]),
+        ("A", &["'a'"]),
+        ("A", &[])
+    ]);
+
+    let transformed_rules = remove_empty_rules(rules.clone());
+
+    assert!(transformed_rules.iter().all(|rule| !rule.replacement.is_empty()));
+    assert_eq!(transformed_rules.len(), 5);
+
+    assert_same_nonempty_sequences(&rules, &transformed_rules, "S", 4);
+    assert_eq!(
+        sequences(&transformed_rules, "S", 4),
+        strings(&[&["b"], &["a", "b"], &["b", "a"], &["a", "b", "a"]])
+    );
+}
+
+#[test]
+fn remove_empty_rules_no_duplicate_variants() {
+    let rules = grammar(&[
+        ("S", &["A", "A"]),
+        ("A", &["'a'"]),
+        ("A", &[])
+    ]);
+
+    let transformed_rules = remove_empty_rules(rules);
+
+    assert_eq!(
+        transformed_rules,
+        grammar(&[
+            ("S", &["A", "A"]),
+            ("S", &["A"]),
+            ("A", &["'a'"])
+        ])
+    );
+}
+
+#[test]
+fn remove_empty_rules_many_equal_nullable_symbols() {
+    let rules = vec![
+        Rule::new("S".to_string(), vec![Symbol::Nonterminal("A".to_string()); 100]),
+        Rule::new("A".to_string(), vec![Symbol::Terminal("a")]),
+        Rule::new("A".to_string(), vec![])
+    ];
+
+    let transformed_rules = remove_empty_rules(rules);
+
+    let mut replacement_lengths: Vec<_> = transformed_rules.iter()
+        .filter(|rule| rule.pattern == "S")
+        .map(|rule| rule.replacement.len())
+        .collect();
+
+    replacement_lengths.sort_unstable();
+
+    assert_eq!(replacement_lengths, (1..=100).collect::<Vec<_>>());
+}
+
+#[test]
+fn remove_unit_rules_preserves_sequences() {
+    let rules = expression_grammar();
+
+    let transformed_rules = remove_unit_rules(rules.clone());
+
+    assert!(transformed_rules.iter().all(|rule| !is_unit_rule(rule)));
+
+    assert_same_nonempty_sequences(&rules, &transformed_rules, "E", 7);
+}
+
+#[test]
+fn remove_unit_rules_cycle() {
+    let rules = grammar(&[
+        ("A", &["B"]),
+        ("A", &["'a'"]),
+        ("B", &["A"]),
+        ("B", &["'b'"])
+    ]);
+
+    let transformed_rules = remove_unit_rules(rules);
+
+    assert_eq!(
+        transformed_rules,
+        grammar(&[
+            ("A", &["'a'"]),
+            ("A", &["'b'"]),
+            ("B", &["'b'"]),
+            ("B", &["'a'"])
+        ])
+    );
+}
+
+#[test]
+fn to_chomsky_normal_form_structure_and_sequences() {
+    let rules = grammar(&[
+        ("S", &["'('", "L", "')'"]),
+        ("S", &["'x'"]),
+        ("L", &["S", "R"]),
+        ("L", &[]),
+        ("R", &["'.'", "S", "R"]),
+        ("R", &[]),
+        ("T", &["S"])
+    ]);
+
+    let transformed_rules = to_chomsky_normal_form(rules.clone(), nonterm_factory());
+
+    for rule in &transformed_rules {
+        assert!(
+            matches!(
+                rule.replacement.as_slice(),
+                [Symbol::Terminal(_)] | [Symbol::Nonterminal(_), Symbol::Nonterminal(_)]
+            ),
+            "{:?} is not in Chomsky Normal Form",
+            rule
+        );
+    }
+
+    assert_same_nonempty_sequences(&rules, &transformed_rules, "S", 7);
+    assert_same_nonempty_sequences(&rules, &transformed_rules, "T", 7);
+}
+
+#[test]
+fn to_chomsky_normal_form_many_nullable_symbols() {
+    let nullable_nonterms: Vec<_> = (0..70).map(|nonterm_idx| format!("A{}", nonterm_idx)).collect();
+
+    let mut rules = vec![
+        Rule::new("S".to_string(), nullable_nonterms.iter().cloned().map(Symbol::Nonterminal).collect())
+    ];
+
+    for nonterm in &nullable_nonterms {
+        rules.push(Rule::new(nonterm.clone(), vec![Symbol::Terminal("a")]));
+        rules.push(Rule::new(nonterm.clone(), vec![]));
+    }
+
+    let transformed_rules = to_chomsky_normal_form(rules, nonterm_factory());
+
+    // Removal of unit rules copies rules of every chain link into all preceding ones, but the count stays polynomial.
+    assert!(transformed_rules.len() <= nullable_nonterms.len().pow(2));
+    assert!(transformed_rules.iter().any(|rule| rule.pattern == "S"));
+    assert!(transformed_rules.iter().all(|rule| {
+        matches!(
+            rule.replacement.as_slice(),
+            [Symbol::Terminal(_)] | [Symbol::Nonterminal(_), Symbol::Nonterminal(_)]
+        )
+    }));
+}
+
+#[test]
+fn to_greibach_normal_form_structure_and_sequences() {
+    let rules = expression_grammar();
+
+    let transformed_rules = to_greibach_normal_form(rules.clone(), nonterm_factory());
+
+    for rule in &transformed_rules {
+        assert!(
+            matches!(rule.replacement.first(), Some(Symbol::Terminal(_)))
+                && rule.replacement[1..].iter().all(Symbol::is_nonterminal),
+            "{:?} is not in Greibach Normal Form",
+            rule
+        );
+    }
+
+    assert_same_nonempty_sequences(&rules, &transformed_rules, "E", 7);
+}
+
+#[test]
+fn to_greibach_normal_form_removes_unproductive_rules() {
+    let rules = grammar(&[
+        ("S", &["'a'", "S"]),
+        ("S", &["'b'"]),
+        ("S", &["U", "'c'"]),
+        ("U", &["U", "'u'"])
+    ]);
+
+    let transformed_rules = to_greibach_normal_form(rules.clone(), nonterm_factory());
+
+    assert!(transformed_rules.iter().all(|rule| rule.pattern != "U"));
+
+    assert_same_nonempty_sequences(&rules, &transformed_rules, "S", 6);
+}
+
+#[test]
+fn remove_left_recursion_direct() {
+    let rules = expression_grammar();
+
+    let transformed_rules = remove_left_recursion(rules.clone(), nonterm_factory());
+
+    assert_no_left_recursion(&transformed_rules);
+    assert_same_nonempty_sequences(&rules, &transformed_rules, "E", 7);
+}
+
+#[test]
+fn remove_left_recursion_indirect() {
+    let rules = grammar(&[
+        ("S", &["A", "'a'"]),
+        ("S", &["'b'"]),
+        ("A", &["A", "'c'"]),
+        ("A", &["S", "'d'"]),
+        ("A", &[])
+    ]);
+
+    let transformed_rules = remove_left_recursion(rules.clone(), nonterm_factory());
+
+    assert_no_left_recursion(&transformed_rules);
+    assert_same_nonempty_sequences(&rules, &transformed_rules, "S", 7);
+}
+
+#[test]
+fn remove_left_recursion_unit_cycle() {
+    let rules = grammar(&[
+        ("A", &["B"]),
+        ("A", &["A", "'a'"]),
+        ("B", &["A"]),
+        ("B", &["'b'"])
+    ]);
+
+    let transformed_rules = remove_left_recursion(rules.clone(), nonterm_factory());
+
+    assert_no_left_recursion(&transformed_rules);
+    assert_same_nonempty_sequences(&rules, &transformed_rules, "A", 6);
+    assert_same_nonempty_sequences(&rules, &transformed_rules, "B", 6);
+}
+
+#[test]
+fn remove_left_recursion_keeps_non_recursive_rules() {
+    let rules = grammar(&[
+        ("S", &["A", "'x'"]),
+        ("A", &["'a'"])
+    ]);
+
+    assert_eq!(remove_left_recursion(rules.clone(), nonterm_factory()), rules);
+}
+
+#[test]
+fn transformations_preserve_weights() {
+    let rules = weighted(grammar(&[
+        ("S", &["A", "'b'"]),
+        ("S", &["T"]),
+        ("A", &["'a'"]),
+        ("A", &[]),
+        ("T", &["'c'", "'d'", "'e'"])
+    ]), &[2.0, 5.0, 3.0, 1.0, 4.0]);
+
+    assert_eq!(
+        weights(&remove_empty_rules(rules.clone())),
+        vec![("S", 2.0), ("S", 2.0), ("S", 5.0), ("A", 3.0), ("T", 4.0)]
+    );
+    assert_eq!(
+        weights(&remove_unit_rules(rules.clone())),
+        vec![("S", 2.0), ("S", 4.0), ("A", 3.0), ("A", 1.0), ("T", 4.0)]
+    );
+
+    let cnf_rules = to_chomsky_normal_form(rules, nonterm_factory());
+
+    let chain_rule = cnf_rules.iter()
+        .find(|rule| rule.pattern == "T")
+        .unwrap();
+
+    assert_eq!(chain_rule.weight, 4.0);
+
+    let rest_nonterm = match &chain_rule.replacement[1] {
+        Symbol::Nonterminal(value) => value,
+        Symbol::Terminal(_)        => panic!("{:?} must end with a non-terminal symbol", chain_rule)
+    };
+
+    assert!(cnf_rules.iter().filter(|rule| rule.pattern == *rest_nonterm).all(|rule| rule.weight == 1.0));
+}
+
+#[test]
+fn transformations_preserve_modifiers() {
+    let mut rules = grammar(&[
+        ("S", &["A", "'b'", "A"]),
+        ("S", &["T"]),
+        ("A", &["'a'"]),
+        ("A", &[]),
+        ("T", &["'c'", "A", "'d'", "'e'"])
+    ]);
+
+    rules[0] = rules[0].clone().with_modifier(0, "first").with_modifier(2, "last");
+    rules[4] = rules[4].clone().with_modifier(0, "c").with_modifier(2, "d").with_modifier(3, "e");
+
+    assert_eq!(
+        modifiers(&remove_empty_rules(rules.clone())),
+        vec![
+            ("S", vec![(0, "first"), (2, "last")]),
+            ("S", vec![(0, "first")]),
+            ("S", vec![(1, "last")]),
+            ("S", vec![]),
+            ("S", vec![]),
+            ("A", vec![]),
+            ("T", vec![(0, "c"), (2, "d"), (3, "e")]),
+            ("T", vec![(0, "c"), (1, "d"), (2, "e")])
+        ]
+    );
+    assert_eq!(
+        modifiers(&remove_unit_rules(rules.clone()))[1],
+        ("S", vec![(0, "c"), (2, "d"), (3, "e")])
+    );
+
+    let cnf_rules = to_chomsky_normal_form(rules, nonterm_factory());
+
+    // T -> C X, X -> A Y, Y -> D E, with the modifiers of the terminal symbols moved to C, D and E.
+    let cnf_modifiers = modifiers(&cnf_rules);
+
+    assert!(cnf_modifiers.contains(&("T", vec![(0, "c")])));
+    assert!(cnf_modifiers.iter().any(|(_, modifiers)| *modifiers == [(0, "d"), (1, "e")]));
+}
+
+#[test]
+fn remove_left_recursion_preserves_modifiers() {
+    let rules = vec![
+        Rule::new(
+            "E".to_string(),
+            vec![Symbol::Nonterminal("E".to_string()), Symbol::Terminal("+"), Symbol::Terminal("x")]
+        )
+        .with_modifier(0, "lost")
+        .with_modifier(2, "x"),
+        Rule::new("E".to_string(), vec![Symbol::Terminal("x")]).with_modifier(0, "x")
+    ];
+
+    assert_eq!(
+        modifiers(&remove_left_recursion(rules, nonterm_factory())),
+        vec![
+            ("E", vec![(0, "x")]),
+            ("E", vec![(0, "x")]),
+            ("N1", vec![(1, "x")]),
+            ("N1", vec![(1, "x")])
+        ]
+    );
+}
+
+#[test]
+fn remove_left_recursion_preserves_derivation_probabilities() {
+    let rules = weighted(grammar(&[
+        ("E", &["E", "'+'", "'x'"]),
+        ("E", &["'x'"])
+    ]), &[1.0, 3.0]);
+
+    let transformed_rules = remove_left_recursion(rules, nonterm_factory());
+
+    // Both "x" and "x + x" keep their original probabilities of 3/4 and 3/16.
+    assert_eq!(
+        weights(&transformed_rules),
+        vec![("E", 2.25), ("E", 0.75), ("N1", 0.75), ("N1", 0.25)]
+    );
+}
+
+#[test]
+fn map_grammar_round_trip() {
+    let rules = expression_grammar().into_iter()
+        .map(|rule| rule.with_weight(2.0).with_modifier(0, "upper"))
+        .collect::<Vec<_>>();
+
+    let mapped_rules = map_grammar(rules.clone(), |value| value.chars().next().unwrap(), |value| value.to_string());
+
+    assert_eq!(mapped_rules.len(), rules.len());
+    assert_eq!(mapped_rules[0].pattern, 'E');
+    assert_eq!(mapped_rules[0].replacement[1], Symbol::Terminal("+".to_string()));
+    assert!(mapped_rules.iter().all(|rule| rule.weight == 2.0 && rule.symbol_modifiers(0).eq(["upper"])));
+
+    let terminals = ["+", "*", "(", ")", "x"];
+
+    let restored_rules = map_grammar(
+        mapped_rules,
+        |value| value.to_string(),
+        |value| *terminals.iter().find(|terminal| **terminal == value).unwrap()
+    );
+
+    assert_eq!(restored_rules, rules);
+}
+
+#[test]
+fn try_map_grammar_first_error() {
+    let mut mapped_nonterms = Vec::new();
+
+    let result: Result<Vec<Rule<usize, char>>, String> = try_map_grammar(
+        expression_grammar(),
+        |value| {
+            mapped_nonterms.push(value.clone());
+
+            if value == "T" { Err(value) } else { Ok(value.len()) }
+        },
+        |value| value.chars().next().ok_or_else(String::new)
+    );
+
+    assert_eq!(result, Err("T".to_string()));
+    assert_eq!(mapped_nonterms, ["E", "E", "T"]);
+
+    assert!(try_map_grammar(expression_grammar(), |value| Ok::<_, ()>(value.len()), |value| Ok(value.len())).is_ok());
+}
+
+//
+// Service
+//
+
+type TestRule = Rule<String, &'static str>;
+
+/// Builds rules from `(pattern, replacement)` pairs, in which quoted symbols are terminals.
+fn grammar(rules: &[(&str, &[&'static str])]) -> Vec<TestRule> {
+    rules.iter()
+        .map(|(pattern, replacement)| {
+            Rule::new(
+                pattern.to_string(),
+                replacement.iter()
+                    .map(|symbol| {
+                        if symbol.len() > 1 && symbol.starts_with('\'') && symbol.ends_with('\'') {
+                            Symbol::Terminal(&symbol[1..symbol.len() - 1])
+                        } else {
+                            Symbol::Nonterminal(symbol.to_string())
+                        }
+                    })
+                    .collect()
+            )
+        })
+        .collect()
+}
+
+fn weighted(rules: Vec<TestRule>, weights: &[f64]) -> Vec<TestRule> {
+    rules.into_iter()
+        .zip(weights)
+        .map(|(rule, weight)| rule.with_weight(*weight))
+        .collect()
+}
+
+fn weights(rules: &[TestRule]) -> Vec<(&str, f64)> {
+    rules.iter()
+        .map(|rule| (rule.pattern.as_str(), rule.weight))
+        .collect()
+}
+
+fn modifiers(rules: &[TestRule]) -> Vec<(&str, Vec<(usize, &str)>)> {
+    rules.iter()
+        .map(|rule| {
+            (
+                rule.pattern.as_str(),
+                rule.modifiers.iter()
+                    .map(|modifier| (modifier.symbol_idx, modifier.name.as_str()))
+                    .collect()
+            )
+        })
+        .collect()
+}
+
+fn expression_grammar() -> Vec<TestRule> {
+    grammar(&[
+        ("E", &["E", "'+'", "T"]),
+        ("E", &["T"]),
+        ("T", &["T", "'*'", "F"]),
+        ("T", &["F"]),
+        ("F", &["'('", "E", "')'"]),
+        ("F", &["'x'"])
+    ])
+}
+
+fn nonterm_factory() -> impl FnMut() -> String {
+    let mut nonterm_count = 0;
+
+    move || {
+        nonterm_count += 1;
+
+        format!("N{}", nonterm_count)
+    }
+}
+
+fn strings(sequences: &[&[&'static str]]) -> BTreeSet<Vec<&'static str>> {
+    sequences.iter()
+        .map(|sequence| sequence.to_vec())
+        .collect()
+}
+
+/// Enumerates all fully expanded sequences of at most `max_len` terminals derived from `start`
+/// by exploring leftmost derivations, whose intermediate sequences are not much longer than that.
+fn sequences(rules: &[TestRule], start: &str, max_len: usize) -> BTreeSet<Vec<&'static str>> {
+    const MAX_EXTRA_NONTERMS: usize = 4;
+
+    let mut result = BTreeSet::new();
+
+    let mut visited = HashSet::new();
+    let mut queue: VecDeque<Vec<Symbol<String, &'static str>>> = VecDeque::new();
+
+    queue.push_back(vec![Symbol::Nonterminal(start.to_string())]);
+
+    while let Some(state) = queue.pop_front() {
+        let key: Vec<_> = state.iter()
+            .map(|symbol| match symbol {
+                Symbol::Nonterminal(value) => (true, value.clone()),
+                Symbol::Terminal(value)    => (false, value.to_string())
+            })
+            .collect();
+
+        if !visited.insert(key) {
+            continue;
+        }
+
+        let terminal_count = state.iter().filter(|symbol| symbol.is_terminal()).count();
+
+        if terminal_count > max_len || state.len() > max_len + MAX_EXTRA_NONTERMS {
+            continue;
+        }
+
+        if let Some(nonterm_idx) = state.iter().position(Symbol::is_nonterminal) {
+            let nonterm_value = state[nonterm_idx].clone().unwrap_nonterm();
+
+            for rule in rules.iter().filter(|rule| rule.pattern == nonterm_value) {
+                let mut next_state = state.clone();
+
+                next_state.splice(nonterm_idx..=nonterm_idx, rule.replacement.iter().cloned());
+
+                queue.push_back(next_state);
+            }
+        } else {
+            result.insert(state.into_iter().map(Symbol::unwrap_term).collect());
+        }
+    }
+
+    result
+}
+
+fn assert_same_nonempty_sequences(original_rules: &[TestRule], transformed_rules: &[TestRule], start: &str, max_len: usize) {
+    let mut expected_sequences = sequences(original_rules, start, max_len);
+
+    expected_sequences.remove(&Vec::new());
+
+    assert!(!expected_sequences.is_empty());
+    assert_eq!(sequences(transformed_rules, start, max_len), expected_sequences);
+}
+
+fn assert_no_left_recursion(rules: &[TestRule]) {
+    for rule in rules {
+        let mut left_reachable = Vec::new();
+        let mut queue          = vec![rule.replacement.first().cloned()];
+
+        while let Some(maybe_symbol) = queue.pop() {
+            if let Some(Symbol::Nonterminal(value)) = maybe_symbol {
+                assert_ne!(value, rule.pattern, "{} is left-recursive", rule.pattern);
+
+                if !left_reachable.contains(&value) {
+                    for other_rule in rules.iter().filter(|other_rule| other_rule.pattern == value) {
+                        queue.push(other_rule.replacement.first().cloned());
+                    }
+
+                    left_reachable.push(value);
+                }
+            }
+        }
+    }
+}
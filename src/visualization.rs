@@ -0,0 +1,329 @@
+use std::fmt::{
+    self,
+    Display,
+    Write
+};
+
+use crate::grammar::{
+    NonterminalValue,
+    Symbol,
+    Rule,
+    DEFAULT_RULE_WEIGHT,
+    indexed::{
+        IndexedGrammar,
+        strongly_connected_components
+    }
+};
+use crate::derivation::{
+    Derivation,
+    DerivationTree
+};
+
+//
+// Interface types
+//
+
+//
+// enum GraphFormat: Debug + Clone + Copy + PartialEq + Eq
+//
+
+/// Text format of graphs produced by [`grammar_graph()`](fn.grammar_graph.html)
+/// and [`derivation_graph()`](fn.derivation_graph.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// [Graphviz](https://graphviz.org) DOT language, to be rendered e.g. by `dot -Tsvg`.
+    Dot,
+    /// [Mermaid](https://mermaid.js.org) flowchart syntax, rendered by many documentation tools.
+    Mermaid
+}
+
+//
+// Interface
+//
+
+/// Describes the dependencies between non-terminal symbols of a grammar as a graph in the given `format`.
+///
+/// Every non-terminal symbol is a node, and there is an edge from the `pattern` of every rule
+/// to every non-terminal symbol in its `replacement`. Edges are labeled with the number of such rules
+/// and, if any of them have non-default [`weight`](struct.Rule.html#structfield.weight)s, with their total weight.
+///
+/// Edges which are part of recursion cycles are drawn bold. Non-terminal symbols without any rules
+/// are drawn dashed, and ones from which no fully expanded sequence can be derived are drawn red.
+///
+/// ```
+/// use branchy::{
+///     Symbol,
+///     Rule,
+///     GraphFormat,
+///     grammar_graph
+/// };
+///
+/// let rules = vec![
+///     Rule::new("list", vec![Symbol::Nonterminal("item")]),
+///     Rule::new("list", vec![Symbol::Nonterminal("item"), Symbol::Terminal(","), Symbol::Nonterminal("list")]),
+///     Rule::new("item", vec![Symbol::Terminal("x")])
+/// ];
+///
+/// let dot = grammar_graph(&rules, GraphFormat::Dot);
+///
+/// assert!(dot.starts_with("digraph grammar {"));
+/// assert!(dot.contains("n0 -> n0 [label=\"1\", style=bold];"));
+/// ```
+pub fn grammar_graph<Nt, T>(rules: &[Rule<Nt, T>], format: GraphFormat) -> String
+    where Nt: NonterminalValue + Display
+{
+    let mut graph = String::new();
+
+    write_grammar_graph(&mut graph, rules, format)
+        .expect("writing to a String must not fail");
+
+    graph
+}
+
+/// Describes the trees of a [`Derivation`](struct.Derivation.html) as a graph in the given `format`.
+///
+/// Non-terminal symbols are drawn as rounded nodes and terminal symbols as rectangular boxes,
+/// with children ordered as in the replacements of the applied rules.
+pub fn derivation_graph<Nt, T>(derivation: &Derivation<Nt, T>, format: GraphFormat) -> String
+    where Nt: Display,
+          T:  Display
+{
+    let mut graph = String::new();
+
+    write_derivation_graph(&mut graph, derivation, format)
+        .expect("writing to a String must not fail");
+
+    graph
+}
+
+//
+// Service
+//
+
+fn write_grammar_graph<Nt, T>(out: &mut String, rules: &[Rule<Nt, T>], format: GraphFormat) -> fmt::Result
+    where Nt: NonterminalValue + Display
+{
+    let grammar             = IndexedGrammar::new(rules);
+    let minimal_derivations = grammar.minimal_derivations();
+    let edges               = grammar_edges(&grammar);
+    let nonterm_components  = nonterm_components(grammar.nonterm_count(), &edges);
+
+    let is_weighted = rules.iter().any(|rule| (rule.weight - DEFAULT_RULE_WEIGHT).abs() > f64::EPSILON);
+
+    match format {
+        GraphFormat::Dot     => writeln!(out, "digraph grammar {{")?,
+        GraphFormat::Mermaid => writeln!(out, "flowchart TD")?
+    }
+
+    for (nonterm_idx, nonterm) in grammar.nonterms.iter().enumerate() {
+        let label = nonterm.to_string();
+
+        let is_undefined    = grammar.rules_by_nonterm[nonterm_idx].is_empty();
+        let is_unproductive = minimal_derivations.costs[nonterm_idx].is_none();
+
+        match format {
+            GraphFormat::Dot => {
+                let mut attributes = vec![format!("label=\"{}\"", escape_dot(&label))];
+
+                if is_undefined {
+                    attributes.push("style=dashed".to_string());
+                }
+
+                if is_unproductive {
+                    attributes.push("color=red".to_string());
+                }
+
+                writeln!(out, "    n{} [{}];", nonterm_idx, attributes.join(", "))?;
+            },
+            GraphFormat::Mermaid => {
+                writeln!(out, "    n{}([\"{}\"])", nonterm_idx, escape_mermaid(&label))?;
+
+                if is_undefined {
+                    writeln!(out, "    class n{} undefined", nonterm_idx)?;
+                }
+
+                if is_unproductive {
+                    writeln!(out, "    class n{} unproductive", nonterm_idx)?;
+                }
+            }
+        }
+    }
+
+    for edge in &edges {
+        let mut label = edge.rule_count.to_string();
+
+        if is_weighted {
+            write!(label, ", w={}", edge.total_weight)?;
+        }
+
+        // An edge is on a cycle if and only if both of its ends are in the same strongly connected component,
+        // which includes edges from a non-terminal symbol to itself.
+        let is_recursive = nonterm_components[edge.source] == nonterm_components[edge.target];
+
+        match format {
+            GraphFormat::Dot => {
+                let style = if is_recursive { ", style=bold" } else { "" };
+
+                writeln!(out, "    n{} -> n{} [label=\"{}\"{}];", edge.source, edge.target, label, style)?;
+            },
+            GraphFormat::Mermaid => {
+                let arrow = if is_recursive { "==" } else { "--" };
+
+                writeln!(out, "    n{} {} \"{}\" {}> n{}", edge.source, arrow, label, arrow, edge.target)?;
+            }
+        }
+    }
+
+    match format {
+        GraphFormat::Dot     => writeln!(out, "}}"),
+        GraphFormat::Mermaid => {
+            writeln!(out, "    classDef undefined stroke-dasharray:5 5")?;
+            writeln!(out, "    classDef unproductive stroke:#f00")
+        }
+    }
+}
+
+fn write_derivation_graph<Nt, T>(out: &mut String, derivation: &Derivation<Nt, T>, format: GraphFormat) -> fmt::Result
+    where Nt: Display,
+          T:  Display
+{
+    match format {
+        GraphFormat::Dot     => writeln!(out, "digraph derivation {{\n    ordering=out;")?,
+        GraphFormat::Mermaid => writeln!(out, "flowchart TD")?
+    }
+
+    let mut node_count = 0;
+
+    for tree in &derivation.trees {
+        write_derivation_tree(out, tree, format, &mut node_count)?;
+    }
+
+    match format {
+        GraphFormat::Dot     => writeln!(out, "}}"),
+        GraphFormat::Mermaid => Ok(())
+    }
+}
+
+/// Writes the nodes and edges of `tree`, numbering its nodes in pre-order starting with `node_count`,
+/// and returns the number of its root node.
+fn write_derivation_tree<Nt, T>(
+    out:        &mut String,
+    tree:       &DerivationTree<Nt, T>,
+    format:     GraphFormat,
+    node_count: &mut usize
+) -> Result<usize, fmt::Error>
+    where Nt: Display,
+          T:  Display
+{
+    let node_idx = *node_count;
+
+    *node_count += 1;
+
+    match (tree.symbol(), format) {
+        (Symbol::Nonterminal(value), GraphFormat::Dot) => {
+            writeln!(out, "    n{} [label=\"{}\"];", node_idx, escape_dot(&value.to_string()))?;
+        },
+        (Symbol::Terminal(value), GraphFormat::Dot) => {
+            writeln!(out, "    n{} [label=\"{}\", shape=box];", node_idx, escape_dot(&value.to_string()))?;
+        },
+        (Symbol::Nonterminal(value), GraphFormat::Mermaid) => {
+            writeln!(out, "    n{}([\"{}\"])", node_idx, escape_mermaid(&value.to_string()))?;
+        },
+        (Symbol::Terminal(value), GraphFormat::Mermaid) => {
+            writeln!(out, "    n{}[\"{}\"]", node_idx, escape_mermaid(&value.to_string()))?;
+        }
+    }
+
+    for child in tree.children() {
+        let child_idx = write_derivation_tree(out, child, format, node_count)?;
+
+        match format {
+            GraphFormat::Dot     => writeln!(out, "    n{} -> n{};", node_idx, child_idx)?,
+            GraphFormat::Mermaid => writeln!(out, "    n{} --> n{}", node_idx, child_idx)?
+        }
+    }
+
+    Ok(node_idx)
+}
+
+/// Lists edges between non-terminal symbols, ordered by source and then by first occurrence.
+fn grammar_edges<Nt, T>(grammar: &IndexedGrammar<Nt, T>) -> Vec<GrammarEdge> {
+    let mut edges: Vec<GrammarEdge> = Vec::new();
+
+    for nonterm_rules in &grammar.rules_by_nonterm {
+        let first_edge_idx = edges.len();
+
+        for &rule_idx in nonterm_rules {
+            let source = grammar.rule_patterns[rule_idx];
+
+            let mut targets: Vec<usize> = grammar.rule_symbols[rule_idx].iter()
+                .flatten()
+                .copied()
+                .collect();
+
+            targets.sort_unstable();
+            targets.dedup();
+
+            for target in targets {
+                let weight = grammar.rules[rule_idx].weight;
+
+                if let Some(edge) = edges[first_edge_idx..].iter_mut().find(|edge| edge.target == target) {
+                    edge.rule_count   += 1;
+                    edge.total_weight += weight;
+                } else {
+                    edges.push(GrammarEdge{
+                        source,
+                        target,
+                        rule_count:   1,
+                        total_weight: weight
+                    });
+                }
+            }
+        }
+    }
+
+    edges
+}
+
+/// Returns the index of the strongly connected component of every non-terminal symbol.
+fn nonterm_components(nonterm_count: usize, edges: &[GrammarEdge]) -> Vec<usize> {
+    let mut successors: Vec<Vec<(usize, usize)>> = vec![Vec::new(); nonterm_count];
+
+    for edge in edges {
+        successors[edge.source].push((edge.target, edge.rule_count));
+    }
+
+    let mut nonterm_components = vec![0; nonterm_count];
+
+    for (component_idx, component) in strongly_connected_components(&successors).into_iter().enumerate() {
+        for nonterm_idx in component {
+            nonterm_components[nonterm_idx] = component_idx;
+        }
+    }
+
+    nonterm_components
+}
+
+fn escape_dot(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn escape_mermaid(label: &str) -> String {
+    label
+        .replace('"', "#quot;")
+        .replace('\n', "<br>")
+}
+
+//
+// Service types
+//
+
+struct GrammarEdge {
+    source:       usize,
+    target:       usize,
+    rule_count:   usize,
+    total_weight: f64
+}
@@ -0,0 +1,144 @@
+use branchy::{
+    Symbol,
+    Rule,
+    ExpanderBuilder,
+    ExpansionLogger,
+    ExpansionLoggerExt,
+    NullExpansionLogger,
+    StatsExpansionLogger
+};
+
+#[test]
+fn combined_loggers()
+{
+    let mut stats = StatsExpansionLogger::new();
+
+    {
+        let boxed_logger: Box<dyn ExpansionLogger<&str, &str>> = Box::new(&mut stats);
+
+        let mut expander = ExpanderBuilder::new()
+            .with_new_rule("greeting", vec![Symbol::Terminal("Hello"), Symbol::Nonterminal("name")])
+            .with_new_rule("name", vec![Symbol::Terminal("world")])
+            .with_logger(
+                NullExpansionLogger
+                    .and(NullExpansionLogger.only_failures())
+                    .and(boxed_logger)
+            )
+            .build();
+
+        expander.expand(vec![Symbol::Nonterminal("greeting")]).unwrap();
+        expander.expand(vec![Symbol::Nonterminal("farewell")]).unwrap_err();
+    }
+
+    assert_eq!(stats.expansion_count(), 2);
+    assert_eq!(stats.nonterm_expansion_count(&"name"), 1);
+    assert_eq!(stats.nonterm_failure_count(&"farewell"), 1);
+}
+
+#[test]
+fn tee_logger_forwards_everything_to_both()
+{
+    let mut logger = RecordingLogger::default().and(RecordingLogger::default());
+
+    notify_all(&mut logger, "name");
+
+    let (first, second) = logger.get_ref();
+
+    assert_eq!(first.events, ALL_EVENTS);
+    assert_eq!(second.events, ALL_EVENTS);
+}
+
+#[test]
+fn nonterm_filter_logger_drops_notifications_about_other_nonterms()
+{
+    let mut logger = RecordingLogger::default().filter_nonterm(|nonterm_value: &&str| *nonterm_value == "name");
+
+    notify_all(&mut logger, "name");
+
+    assert_eq!(logger.get_ref().events, ALL_EVENTS);
+
+    let mut logger = RecordingLogger::default().filter_nonterm(|nonterm_value: &&str| *nonterm_value == "name");
+
+    notify_all(&mut logger, "title");
+
+    assert_eq!(logger.get_ref().events, ["started", "fully expanded", "max iterations reached"]);
+}
+
+#[test]
+fn failures_only_logger_drops_notifications_about_successes()
+{
+    let mut logger = RecordingLogger::default().only_failures();
+
+    notify_all(&mut logger, "name");
+
+    assert_eq!(logger.get_ref().events, ["failed name", "recovered name", "max iterations reached"]);
+}
+
+//
+// Service
+//
+
+const ALL_EVENTS: [&str; 7] = [
+    "started",
+    "expanded name",
+    "applied name",
+    "failed name",
+    "recovered name",
+    "fully expanded",
+    "max iterations reached"
+];
+
+/// Sends every notification to `logger`, once each, about `nonterm_value` where applicable.
+fn notify_all<L>(logger: &mut L, nonterm_value: &'static str)
+    where L: ExpansionLogger<&'static str, &'static str>
+{
+    let rule = Rule::new(nonterm_value, vec![Symbol::Terminal("Alice")]);
+
+    logger.on_expansion_started(&[Symbol::Nonterminal(nonterm_value)]);
+    logger.on_nonterm_expanded(&nonterm_value, &rule);
+    logger.on_rule_applied(&nonterm_value, 0);
+    logger.on_nonterm_expansion_failed(&nonterm_value);
+    logger.on_nonterm_expansion_recovered(&nonterm_value, &rule.replacement);
+    logger.on_input_fully_expanded(&["Alice"]);
+    logger.on_max_iterations_reached(&[Symbol::Nonterminal(nonterm_value)], 1);
+}
+
+//
+// RecordingLogger: ExpansionLogger<&str, &str> + Default
+//
+
+/// Records a short description of every notification it receives.
+#[derive(Default)]
+struct RecordingLogger {
+    events: Vec<String>
+}
+
+impl<'a> ExpansionLogger<&'a str, &'a str> for RecordingLogger {
+    fn on_expansion_started(&mut self, _input: &[Symbol<&'a str, &'a str>]) {
+        self.events.push("started".to_string());
+    }
+
+    fn on_nonterm_expanded(&mut self, expanded_nonterm_value: &&'a str, _rule: &Rule<&'a str, &'a str>) {
+        self.events.push(format!("expanded {}", expanded_nonterm_value));
+    }
+
+    fn on_rule_applied(&mut self, expanded_nonterm_value: &&'a str, _rule_idx: usize) {
+        self.events.push(format!("applied {}", expanded_nonterm_value));
+    }
+
+    fn on_nonterm_expansion_failed(&mut self, expanded_nonterm_value: &&'a str) {
+        self.events.push(format!("failed {}", expanded_nonterm_value));
+    }
+
+    fn on_nonterm_expansion_recovered(&mut self, recovered_nonterm_value: &&'a str, _replacement: &[Symbol<&'a str, &'a str>]) {
+        self.events.push(format!("recovered {}", recovered_nonterm_value));
+    }
+
+    fn on_input_fully_expanded(&mut self, _expansion_result: &[&'a str]) {
+        self.events.push("fully expanded".to_string());
+    }
+
+    fn on_max_iterations_reached(&mut self, _current_state: &[Symbol<&'a str, &'a str>], _iterations: usize) {
+        self.events.push("max iterations reached".to_string());
+    }
+}
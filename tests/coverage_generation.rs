@@ -0,0 +1,102 @@
+use std::collections::HashSet;
+
+use branchy::{
+    Symbol,
+    Rule,
+    DerivationTree,
+    CoverageGenerator,
+    CoverageCriterion
+};
+
+#[test]
+fn coverage_generation_rules()
+{
+    let rules = expression_rules();
+
+    let coverage_suite = CoverageGenerator::new(&rules).generate(&[Symbol::Nonterminal("expr")]);
+
+    let covered_rule_idxs: HashSet<_> = coverage_suite.derivations.iter()
+        .flat_map(|derivation| derivation.rule_idxs())
+        .collect();
+
+    assert_eq!(covered_rule_idxs, (0..7).collect());
+    assert_eq!(coverage_suite.uncovered_rule_idxs, vec![7, 8]);
+    assert!(coverage_suite.derivations.len() <= 3);
+
+    for (derivation, output) in coverage_suite.derivations.iter().zip(coverage_suite.outputs()) {
+        assert_eq!(derivation.input(), vec![Symbol::Nonterminal("expr")]);
+        assert!(!output.is_empty());
+        assert!(output.iter().all(|terminal| ["x", "1", "+", "*", "(", ")"].contains(terminal)));
+    }
+}
+
+#[test]
+fn coverage_generation_rule_pairs()
+{
+    let rules = expression_rules();
+
+    let coverage_suite = CoverageGenerator::new(&rules)
+        .with_criterion(CoverageCriterion::RulePairs)
+        .generate(&[Symbol::Nonterminal("expr"), Symbol::Terminal(";")]);
+
+    let mut covered_rule_pairs = HashSet::new();
+
+    for derivation in &coverage_suite.derivations {
+        assert_eq!(derivation.terminals().last(), Some(&";"));
+
+        for tree in &derivation.trees {
+            collect_rule_pairs(tree, &mut covered_rule_pairs);
+        }
+    }
+
+    for (parent_idx, parent_rule) in rules.iter().enumerate().take(7) {
+        for (child_idx, child_rule) in rules.iter().enumerate().take(7) {
+            if parent_rule.replacement.contains(&Symbol::Nonterminal(child_rule.pattern)) {
+                assert!(covered_rule_pairs.contains(&(parent_idx, child_idx)));
+            }
+        }
+    }
+
+    assert_eq!(coverage_suite.uncovered_rule_idxs, vec![7, 8]);
+}
+
+#[test]
+fn coverage_generation_unproductive_input()
+{
+    let rules = expression_rules();
+
+    let coverage_suite = CoverageGenerator::new(&rules).generate(&[Symbol::Nonterminal("loop")]);
+
+    assert!(coverage_suite.derivations.is_empty());
+    assert_eq!(coverage_suite.uncovered_rule_idxs.len(), rules.len());
+}
+
+//
+// Service
+//
+
+fn expression_rules() -> Vec<Rule<&'static str, &'static str>> {
+    vec![
+        Rule::new("expr", vec![Symbol::Nonterminal("term")]),
+        Rule::new("expr", vec![Symbol::Nonterminal("expr"), Symbol::Terminal("+"), Symbol::Nonterminal("term")]),
+        Rule::new("term", vec![Symbol::Nonterminal("factor")]),
+        Rule::new("term", vec![Symbol::Nonterminal("term"), Symbol::Terminal("*"), Symbol::Nonterminal("factor")]),
+        Rule::new("factor", vec![Symbol::Terminal("x")]),
+        Rule::new("factor", vec![Symbol::Terminal("1")]),
+        Rule::new("factor", vec![Symbol::Terminal("("), Symbol::Nonterminal("expr"), Symbol::Terminal(")")]),
+        Rule::new("factor", vec![Symbol::Nonterminal("loop")]),
+        Rule::new("loop", vec![Symbol::Nonterminal("loop")])
+    ]
+}
+
+fn collect_rule_pairs(tree: &DerivationTree<&str, &str>, rule_pairs: &mut HashSet<(usize, usize)>) {
+    if let Some(rule_idx) = tree.rule_idx() {
+        for child in tree.children() {
+            if let Some(child_rule_idx) = child.rule_idx() {
+                rule_pairs.insert((rule_idx, child_rule_idx));
+            }
+
+            collect_rule_pairs(child, rule_pairs);
+        }
+    }
+}
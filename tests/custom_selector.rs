@@ -2,7 +2,8 @@ use branchy::{
     Symbol,
     Rule,
     ExpanderBuilder,
-    RuleSelector
+    RuleSelector,
+    ExpansionContext
 };
 
 #[test]
@@ -11,7 +12,11 @@ fn custom_selector()
     struct AlwaysFirstRuleSelector;
  
     impl<Nt, T> RuleSelector<Nt, T> for AlwaysFirstRuleSelector {
-        fn select_matching_rule<'a>(&self, matching_rules: &[&'a Rule<Nt, T>]) -> Option<&'a Rule<Nt, T>> {
+        fn select_matching_rule<'a>(
+            &mut self,
+            matching_rules: &[&'a Rule<Nt, T>],
+            _context:       &ExpansionContext<Nt, T>
+        ) -> Option<&'a Rule<Nt, T>> {
             if matching_rules.is_empty() {
                 None
             } else {
@@ -48,4 +53,28 @@ fn custom_selector()
         expansion_result,
         vec!["There is a", "huge", "forest", "to the", "north", "of the town."]
     );
-}
\ No newline at end of file
+}
+#[test]
+fn custom_selector_selecting_cloned_rule()
+{
+    struct ClonedRuleSelector;
+
+    impl RuleSelector<&'static str, &'static str> for ClonedRuleSelector {
+        fn select_matching_rule<'a>(
+            &mut self,
+            matching_rules: &[&'a Rule<&'static str, &'static str>],
+            _context:       &ExpansionContext<&'static str, &'static str>
+        ) -> Option<&'a Rule<&'static str, &'static str>> {
+            matching_rules.first()
+                .map(|rule| &*Box::leak(Box::new((*rule).clone())))
+        }
+    }
+
+    let mut expander = ExpanderBuilder::new()
+        .with_new_rule("greeting", vec![Symbol::Terminal("Hello"), Symbol::Nonterminal("name")])
+        .with_new_rule("name", vec![Symbol::Terminal("Alice")])
+        .with_rule_selector(ClonedRuleSelector)
+        .build();
+
+    assert_eq!(expander.expand(vec![Symbol::Nonterminal("greeting")]).unwrap(), ["Hello", "Alice"]);
+}
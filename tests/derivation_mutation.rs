@@ -0,0 +1,198 @@
+use branchy::{
+    Symbol,
+    Rule,
+    Derivation,
+    DerivationTree,
+    Expander,
+    ExpanderBuilder,
+    RuleSelector,
+    ExpansionLogger,
+    DerivationMutator
+};
+use rand::{
+    SeedableRng,
+    rngs::StdRng
+};
+
+#[test]
+fn derivation_mutation_expand_derivation()
+{
+    let rules = expression_rules();
+
+    let mut expander = ExpanderBuilder::from(rules.clone()).build();
+
+    let input      = vec![Symbol::Nonterminal("expr"), Symbol::Terminal(";")];
+    let derivation = expand_derivation(&mut expander, input.clone());
+
+    assert_eq!(derivation.input(), input);
+    assert_eq!(derivation.terminals().last(), Some(&";"));
+    assert_valid_derivation(&derivation, &rules);
+
+    let rebuilt_derivation = Derivation::from_rule_idxs(&rules, &input, &derivation.rule_idxs());
+
+    assert_eq!(rebuilt_derivation, Some(derivation.clone()));
+
+    let mut truncated_rule_idxs = derivation.rule_idxs();
+    truncated_rule_idxs.pop();
+
+    assert_eq!(Derivation::from_rule_idxs(&rules, &input, &truncated_rule_idxs), None);
+}
+
+#[test]
+fn derivation_mutation_regenerate_subtree()
+{
+    let rules   = expression_rules();
+    let mutator = DerivationMutator::new(&rules);
+
+    let mut rng = StdRng::seed_from_u64(1);
+
+    let mut derivation = expand_derivation(
+        &mut ExpanderBuilder::from(rules.clone()).build(),
+        vec![Symbol::Nonterminal("expr")]
+    );
+
+    let mut outputs_changed = false;
+
+    for _ in 0..100 {
+        let mutant = mutator.regenerate_subtree(&derivation, &mut rng).unwrap();
+
+        assert_valid_derivation(&mutant, &rules);
+
+        outputs_changed |= mutant.terminals() != derivation.terminals();
+
+        derivation = mutant;
+    }
+
+    assert!(outputs_changed);
+}
+
+#[test]
+fn derivation_mutation_splice_subtree()
+{
+    let rules   = expression_rules();
+    let mutator = DerivationMutator::new(&rules);
+
+    let mut rng = StdRng::seed_from_u64(2);
+
+    // x
+    let derivation = Derivation::from_rule_idxs(&rules, &[Symbol::Nonterminal("expr")], &[0, 2, 4]).unwrap();
+    // (1)
+    let donor = Derivation::from_rule_idxs(&rules, &[Symbol::Nonterminal("factor")], &[6, 0, 2, 5]).unwrap();
+
+    let mut mutant_outputs = Vec::new();
+
+    for _ in 0..100 {
+        let mutant = mutator.splice_subtree(&derivation, &donor, &mut rng).unwrap();
+
+        assert_valid_derivation(&mutant, &rules);
+
+        mutant_outputs.push(mutant.terminals());
+    }
+
+    assert!(mutant_outputs.contains(&vec!["1"]));
+    assert!(mutant_outputs.contains(&vec!["(", "1", ")"]));
+
+    let unrelated_donor = Derivation::new(vec![DerivationTree::Terminal(";")]);
+
+    assert_eq!(mutator.splice_subtree(&derivation, &unrelated_donor, &mut rng), None);
+}
+
+#[test]
+fn derivation_mutation_duplicate_recursion()
+{
+    let rules   = expression_rules();
+    let mutator = DerivationMutator::new(&rules);
+
+    let mut rng = StdRng::seed_from_u64(3);
+
+    // x + 1
+    let derivation = Derivation::from_rule_idxs(&rules, &[Symbol::Nonterminal("expr")], &[1, 0, 2, 4, 2, 5]).unwrap();
+
+    let mutant = mutator.duplicate_recursion(&derivation, &mut rng).unwrap();
+
+    assert_valid_derivation(&mutant, &rules);
+    assert_eq!(mutant.terminals(), vec!["x", "+", "1", "+", "1"]);
+
+    // x
+    let non_recursive_derivation = Derivation::from_rule_idxs(&rules, &[Symbol::Nonterminal("expr")], &[0, 2, 4]).unwrap();
+
+    assert_eq!(mutator.duplicate_recursion(&non_recursive_derivation, &mut rng), None);
+}
+
+#[test]
+fn derivation_mutation_generate()
+{
+    let rules   = expression_rules();
+    let mutator = DerivationMutator::new(&rules).with_max_node_count(32);
+
+    let mut rng = StdRng::seed_from_u64(5);
+
+    let input = vec![Symbol::Nonterminal("expr"), Symbol::Terminal(";")];
+
+    for _ in 0..100 {
+        let derivation = mutator.generate(&input, &mut rng).unwrap();
+
+        assert_eq!(derivation.input(), input);
+        assert_valid_derivation(&derivation, &rules);
+    }
+
+    assert_eq!(mutator.generate(&[Symbol::Nonterminal("unknown")], &mut rng), None);
+}
+
+#[test]
+fn derivation_mutation_mutate()
+{
+    let rules   = expression_rules();
+    let mutator = DerivationMutator::new(&rules).with_max_node_count(16);
+
+    let mut rng = StdRng::seed_from_u64(4);
+
+    let mut expander = ExpanderBuilder::from(rules.clone()).build();
+
+    let mut corpus: Vec<_> = (0..4)
+        .map(|_| expand_derivation(&mut expander, vec![Symbol::Nonterminal("expr")]))
+        .collect();
+
+    for idx in 0..200 {
+        let mutant = mutator.mutate(&corpus[idx % corpus.len()], &corpus, &mut rng).unwrap();
+
+        assert_valid_derivation(&mutant, &rules);
+
+        if mutant.node_count() < 200 {
+            corpus.push(mutant);
+        }
+    }
+}
+
+fn expression_rules() -> Vec<Rule<&'static str, &'static str>>
+{
+    vec![
+        Rule::new("expr", vec![Symbol::Nonterminal("term")]),
+        Rule::new("expr", vec![Symbol::Nonterminal("expr"), Symbol::Terminal("+"), Symbol::Nonterminal("term")]),
+        Rule::new("term", vec![Symbol::Nonterminal("factor")]),
+        Rule::new("term", vec![Symbol::Nonterminal("term"), Symbol::Terminal("*"), Symbol::Nonterminal("factor")]),
+        Rule::new("factor", vec![Symbol::Terminal("x")]),
+        Rule::new("factor", vec![Symbol::Terminal("1")]),
+        Rule::new("factor", vec![Symbol::Terminal("("), Symbol::Nonterminal("expr"), Symbol::Terminal(")")])
+    ]
+}
+
+/// Retries expansion, since random expansion of a recursive grammar may reach the maximum number of iterations.
+fn expand_derivation<RS, EL>(
+    expander: &mut Expander<&'static str, &'static str, RS, EL>,
+    input:    Vec<Symbol<&'static str, &'static str>>
+) -> Derivation<&'static str, &'static str>
+    where RS: RuleSelector<&'static str, &'static str>,
+          EL: ExpansionLogger<&'static str, &'static str>
+{
+    (0..100)
+        .find_map(|_| expander.expand_derivation(input.clone()).ok())
+        .expect("at least one expansion should succeed")
+}
+
+fn assert_valid_derivation(derivation: &Derivation<&'static str, &'static str>, rules: &[Rule<&'static str, &'static str>])
+{
+    let rebuilt_derivation = Derivation::from_rule_idxs(rules, &derivation.input(), &derivation.rule_idxs());
+
+    assert_eq!(rebuilt_derivation.as_ref(), Some(derivation));
+}
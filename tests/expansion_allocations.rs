@@ -0,0 +1,123 @@
+use std::{
+    alloc::{
+        GlobalAlloc,
+        Layout,
+        System
+    },
+    cell::Cell
+};
+
+use branchy::{
+    Symbol,
+    ExpanderBuilder,
+    WeightedRandomRuleSelector
+};
+
+//
+// Tests
+//
+
+#[test]
+fn expand_into_does_not_allocate_once_buffers_have_grown()
+{
+    let mut expander = ExpanderBuilder::new()
+        .with_new_rule("sentence", vec![Symbol::Nonterminal("name"), Symbol::Terminal("likes"), Symbol::Nonterminal("food")])
+        .with_new_rule("name", vec![Symbol::Terminal("Alice")])
+        .with_new_rule("name", vec![Symbol::Terminal("Bob")])
+        .with_new_rule("food", vec![Symbol::Terminal("fresh"), Symbol::Nonterminal("fruit")])
+        .with_new_rule("food", vec![Symbol::Terminal("ripe"), Symbol::Nonterminal("fruit")])
+        .with_new_rule("fruit", vec![Symbol::Terminal("apples")])
+        .with_new_rule("fruit", vec![Symbol::Terminal("pears")])
+        .build();
+
+    let input = [Symbol::Nonterminal("sentence"), Symbol::Terminal(".")];
+
+    let mut output = Vec::new();
+
+    let allocation_count = count_allocations(|| {
+        for _ in 0..100 {
+            output.clear();
+
+            expander.expand_into(&input, &mut output).unwrap();
+        }
+    });
+
+    assert!(allocation_count > 0);
+    assert_eq!(output.len(), 5);
+
+    let allocation_count = count_allocations(|| {
+        for _ in 0..100 {
+            output.clear();
+
+            expander.expand_into(&input, &mut output).unwrap();
+        }
+    });
+
+    assert_eq!(allocation_count, 0);
+
+    // Adding rules extends the rule index, after which expansion does not allocate either.
+    expander.add_new_rule("fruit", vec![Symbol::Terminal("plums")]);
+
+    let mut expander = expander.with_rule_selector(WeightedRandomRuleSelector::new());
+
+    expander.expand_into(&input, &mut output).unwrap();
+
+    let allocation_count = count_allocations(|| {
+        for _ in 0..100 {
+            output.clear();
+
+            expander.expand_into(&input, &mut output).unwrap();
+        }
+    });
+
+    assert_eq!(allocation_count, 0);
+    assert!(["apples", "pears", "plums"].contains(&output[3]));
+}
+
+//
+// Service
+//
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+thread_local! {
+    static ALLOCATION_COUNT: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Returns the number of allocations made by the current thread while running `f`.
+fn count_allocations<F>(f: F) -> usize
+    where F: FnOnce()
+{
+    let start_count = ALLOCATION_COUNT.with(Cell::get);
+
+    f();
+
+    ALLOCATION_COUNT.with(Cell::get) - start_count
+}
+
+//
+// CountingAllocator: GlobalAlloc
+//
+
+/// Counts allocations and reallocations of every thread separately, so that tests running in parallel
+/// do not affect one another's counts.
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATION_COUNT.try_with(|count| count.set(count.get() + 1));
+
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATION_COUNT.try_with(|count| count.set(count.get() + 1));
+
+        System.realloc(ptr, layout, new_size)
+    }
+}
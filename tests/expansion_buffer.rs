@@ -0,0 +1,80 @@
+use std::rc::Rc;
+
+use branchy::{
+    Symbol,
+    Rule,
+    ExpanderBuilder,
+    ErrorKind,
+    ModifierRegistry
+};
+
+#[test]
+fn expand_into_appends_to_output()
+{
+    let mut expander = ExpanderBuilder::new()
+        .with_rule(
+            Rule::new("greeting", vec![Symbol::Terminal("hello"), Symbol::Nonterminal("name")])
+                .with_modifier(1, "upper")
+        )
+        .with_new_rule("name", vec![Symbol::Terminal("bob")])
+        .with_modifier_registry(
+            ModifierRegistry::new()
+                .with_modifier("upper", |values: Vec<&str>| values.into_iter().map(|_| "BOB").collect())
+        )
+        .build();
+
+    let input = [Symbol::Nonterminal("greeting"), Symbol::Terminal("!")];
+
+    let mut output = vec!["start"];
+
+    expander.expand_into(&input, &mut output).unwrap();
+    expander.expand_into(&input, &mut output).unwrap();
+
+    assert_eq!(output, ["start", "hello", "BOB", "!", "hello", "BOB", "!"]);
+    assert_eq!(expander.expand(input.to_vec()).unwrap(), ["hello", "BOB", "!"]);
+}
+
+#[test]
+fn expand_into_leaves_output_unchanged_on_failure()
+{
+    let mut expander = ExpanderBuilder::new()
+        .with_new_rule("sentence", vec![Symbol::Terminal("a"), Symbol::Nonterminal("noun")])
+        .build();
+
+    let mut output = vec!["start"];
+
+    let error = expander.expand_into(&[Symbol::Nonterminal("sentence")], &mut output).unwrap_err();
+
+    assert_eq!(output, ["start"]);
+    assert!(matches!(error.kind, ErrorKind::NontermExpansionFailed("noun")));
+    assert_eq!(error.state, [Symbol::Terminal("a"), Symbol::Nonterminal("noun")]);
+    assert_eq!(error.ancestors, ["sentence"]);
+}
+
+#[test]
+fn expand_into_shares_rc_terminals()
+{
+    let hello: Rc<str> = Rc::from("hello");
+
+    let mut expander = ExpanderBuilder::new()
+        .with_new_rule("greeting", vec![Symbol::Terminal(Rc::clone(&hello)), Symbol::Nonterminal("greeting")])
+        .with_new_rule("greeting", vec![Symbol::Terminal(Rc::clone(&hello))])
+        .build();
+
+    let input = [Symbol::Nonterminal("greeting")];
+
+    let mut output = Vec::new();
+
+    for _ in 0..100 {
+        output.clear();
+
+        expander.expand_into(&input, &mut output).unwrap();
+
+        assert!(!output.is_empty());
+        assert!(output.iter().all(|value| Rc::ptr_eq(value, &hello)));
+    }
+
+    drop(output);
+
+    assert_eq!(Rc::strong_count(&hello), 3);
+}
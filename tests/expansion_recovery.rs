@@ -0,0 +1,125 @@
+use branchy::{
+    Symbol,
+    ExpanderBuilder,
+    RecoveryPolicy,
+    ExpansionWarning,
+    StatsExpansionLogger,
+    ErrorKind
+};
+
+#[test]
+fn recovery_policy_fail()
+{
+    let mut expander = ExpanderBuilder::new()
+        .with_new_rule("greeting", vec![Symbol::Terminal("hello"), Symbol::Nonterminal("name")])
+        .build();
+
+    let error = expander.expand(vec![Symbol::Nonterminal("greeting")]).unwrap_err();
+
+    assert!(matches!(error.kind, ErrorKind::NontermExpansionFailed("name")));
+}
+
+#[test]
+fn recovery_policy_placeholder()
+{
+    let mut expander = ExpanderBuilder::new()
+        .with_new_rule("greeting", vec![Symbol::Terminal("hello"), Symbol::Nonterminal("name"), Symbol::Terminal("!")])
+        .with_recovery_policy(RecoveryPolicy::Placeholder("???"))
+        .build();
+
+    let recovered = expander.expand_with_warnings(vec![Symbol::Nonterminal("greeting")]).unwrap();
+
+    assert_eq!(recovered.output, ["hello", "???", "!"]);
+    assert_eq!(
+        recovered.warnings,
+        vec![ExpansionWarning{
+            nonterm_value: "name",
+            position:      1,
+            ancestors:     vec!["greeting"]
+        }]
+    );
+    assert!(!recovered.is_clean());
+
+    assert_eq!(expander.expand(vec![Symbol::Nonterminal("name")]).unwrap(), ["???"]);
+}
+
+#[test]
+fn recovery_policy_drop()
+{
+    let mut expander = ExpanderBuilder::new()
+        .with_new_rule("greeting", vec![Symbol::Terminal("hello"), Symbol::Nonterminal("name"), Symbol::Terminal("!")])
+        .with_new_rule("name", vec![Symbol::Terminal("Alice")])
+        .with_recovery_policy(RecoveryPolicy::Drop)
+        .build();
+
+    let recovered = expander.expand_with_warnings(vec![Symbol::Nonterminal("greeting")]).unwrap();
+
+    assert_eq!(recovered.output, ["hello", "Alice", "!"]);
+    assert!(recovered.is_clean());
+
+    let recovered = expander.expand_with_warnings(
+        vec![Symbol::Nonterminal("title"), Symbol::Nonterminal("greeting"), Symbol::Nonterminal("title")]
+    ).unwrap();
+
+    assert_eq!(recovered.output, ["hello", "Alice", "!"]);
+    assert_eq!(
+        recovered.warnings.iter().map(|warning| (warning.nonterm_value, warning.position)).collect::<Vec<_>>(),
+        vec![("title", 0), ("title", 3)]
+    );
+}
+
+#[test]
+fn recovery_policy_fallback()
+{
+    let mut expander = ExpanderBuilder::new()
+        .with_new_rule("greeting", vec![Symbol::Terminal("hello"), Symbol::Nonterminal("name_fr")])
+        .with_new_rule("name", vec![Symbol::Terminal("Alice")])
+        .with_recovery_policy(RecoveryPolicy::fallback(|nonterm_value: &&str| {
+            match nonterm_value.strip_suffix("_fr") {
+                Some("name") => vec![Symbol::Nonterminal("name")],
+                _            => vec![Symbol::Terminal("?")]
+            }
+        }))
+        .with_logger(StatsExpansionLogger::new())
+        .build();
+
+    let recovered = expander.expand_with_warnings(vec![Symbol::Nonterminal("greeting")]).unwrap();
+
+    assert_eq!(recovered.output, ["hello", "Alice"]);
+    assert_eq!(
+        recovered.warnings,
+        vec![ExpansionWarning{
+            nonterm_value: "name_fr",
+            position:      1,
+            ancestors:     vec!["greeting"]
+        }]
+    );
+
+    assert_eq!(expander.expand(vec![Symbol::Nonterminal("farewell")]).unwrap(), ["?"]);
+
+    assert_eq!(expander.logger().nonterm_recovery_count(&"name_fr"), 1);
+    assert_eq!(expander.logger().nonterm_recovery_count(&"farewell"), 1);
+    assert_eq!(expander.logger().nonterm_failure_count(&"name_fr"), 0);
+    assert_eq!(expander.logger().nonterm_expansion_failure_count(), 0);
+    assert_eq!(expander.logger().success_count(), 2);
+
+    // Derivations cannot describe recovered symbols, so recovery is not used for them.
+    assert!(expander.expand_derivation(vec![Symbol::Nonterminal("greeting")]).is_err());
+
+    let previous_policy = expander.set_recovery_policy(RecoveryPolicy::Fail);
+
+    assert!(matches!(previous_policy, RecoveryPolicy::Fallback(_)));
+    assert!(expander.expand(vec![Symbol::Nonterminal("greeting")]).is_err());
+}
+
+#[test]
+fn recovery_policy_is_send_and_sync()
+{
+    fn assert_send_sync<S: Send + Sync>(_: &S) {}
+
+    let recovery_policy: RecoveryPolicy<String, String> = RecoveryPolicy::fallback(|nonterm_value: &String| {
+        vec![Symbol::Terminal(nonterm_value.to_uppercase())]
+    });
+
+    assert_send_sync(&recovery_policy);
+}
@@ -0,0 +1,138 @@
+use branchy::{
+    Symbol,
+    Rule,
+    ExpanderBuilder,
+    GrammarInducer
+};
+
+fn nonterm_factory() -> impl FnMut() -> String {
+    let mut nonterm_count = 0;
+
+    move || {
+        nonterm_count += 1;
+
+        format!("N{}", nonterm_count)
+    }
+}
+
+#[test]
+fn grammar_inference_compression()
+{
+    let examples = vec![
+        vec!['a', 'b', 'c', 'a', 'b', 'c'],
+        vec!['x', 'y']
+    ];
+
+    let rules = GrammarInducer::new()
+        .with_generalization(false)
+        .induce("S".to_string(), &examples, nonterm_factory());
+
+    assert_eq!(
+        rules,
+        vec![
+            Rule::new("S".to_string(), vec![Symbol::Nonterminal("N1".to_string()), Symbol::Nonterminal("N1".to_string())]),
+            Rule::new("S".to_string(), vec![Symbol::Terminal('x'), Symbol::Terminal('y')]),
+            Rule::new("N1".to_string(), vec![Symbol::Terminal('a'), Symbol::Terminal('b'), Symbol::Terminal('c')])
+        ]
+    );
+
+    let mut expander = ExpanderBuilder::from(rules).build();
+
+    for _ in 0..10 {
+        let output = expander.expand(vec![Symbol::Nonterminal("S".to_string())]).unwrap();
+
+        assert!(examples.contains(&output));
+    }
+}
+
+#[test]
+fn grammar_inference_generalization()
+{
+    let examples = vec![
+        vec!["the", "cat", "sleeps"],
+        vec!["the", "dog", "sleeps"],
+        vec!["the", "cat", "eats"]
+    ];
+
+    let rules = GrammarInducer::new().induce("S".to_string(), &examples, nonterm_factory());
+
+    assert_eq!(
+        rules,
+        vec![
+            Rule::new(
+                "S".to_string(),
+                vec![Symbol::Terminal("the"), Symbol::Nonterminal("N1".to_string()), Symbol::Nonterminal("N2".to_string())]
+            ).with_weight(3.0),
+            Rule::new("N1".to_string(), vec![Symbol::Terminal("cat")]).with_weight(2.0),
+            Rule::new("N1".to_string(), vec![Symbol::Terminal("dog")]),
+            Rule::new("N2".to_string(), vec![Symbol::Terminal("sleeps")]).with_weight(2.0),
+            Rule::new("N2".to_string(), vec![Symbol::Terminal("eats")])
+        ]
+    );
+}
+
+#[test]
+fn grammar_inference_generalization_and_compression()
+{
+    let examples = vec![
+        vec!["I", "like", "red", "apples", "and", "red", "apples"],
+        vec!["I", "like", "green", "apples"],
+        vec!["you", "like", "green", "pears"],
+        vec!["you", "like", "red", "pears"]
+    ];
+
+    let rules = GrammarInducer::new().induce("S", &examples, {
+        let mut nonterms = vec!["Color", "Subject", "Fruit", "Object", "Clause"].into_iter();
+
+        move || nonterms.next().unwrap()
+    });
+
+    let mut expander = ExpanderBuilder::from(rules.clone()).build();
+
+    let mut outputs = Vec::new();
+
+    for _ in 0..200 {
+        let output = expander.expand(vec![Symbol::Nonterminal("S")]).unwrap();
+
+        if !outputs.contains(&output) {
+            outputs.push(output);
+        }
+    }
+
+    // Every subject, color and fruit can be combined with one another.
+    assert!(outputs.contains(&vec!["you", "like", "green", "apples"]));
+    assert!(outputs.contains(&vec!["I", "like", "red", "pears"]));
+    assert!(outputs.iter().all(|output| output.len() == 4 || output.len() == 7));
+
+    assert_eq!(
+        &rules[..2],
+        [
+            Rule::new("S", vec![Symbol::Nonterminal("Clause"), Symbol::Terminal("and"), Symbol::Nonterminal("Object")]),
+            Rule::new("S", vec![Symbol::Nonterminal("Clause")]).with_weight(3.0)
+        ]
+    );
+    assert!(rules.contains(&Rule::new("Object", vec![Symbol::Nonterminal("Color"), Symbol::Nonterminal("Fruit")])));
+    assert!(rules.contains(
+        &Rule::new("Clause", vec![Symbol::Nonterminal("Subject"), Symbol::Terminal("like"), Symbol::Nonterminal("Object")])
+    ));
+}
+
+#[test]
+fn grammar_inference_duplicate_and_empty_examples()
+{
+    let examples: Vec<Vec<u8>> = vec![
+        vec![1, 2, 3],
+        vec![],
+        vec![1, 2, 3]
+    ];
+
+    let rules = GrammarInducer::new().induce(0, &examples, || unreachable!());
+
+    assert_eq!(
+        rules,
+        vec![
+            Rule::new(0, vec![Symbol::Terminal(1), Symbol::Terminal(2), Symbol::Terminal(3)]).with_weight(2.0),
+            Rule::new(0, vec![])
+        ]
+    );
+}
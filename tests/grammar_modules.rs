@@ -0,0 +1,196 @@
+use branchy::{
+    Symbol,
+    Rule,
+    GrammarModule,
+    GrammarLinker,
+    QualifiedNonterm,
+    OverrideMode,
+    LinkError,
+    ExpanderBuilder
+};
+
+#[test]
+fn grammar_modules_namespaces()
+{
+    let rules = GrammarLinker::new()
+        .with_module(names_module())
+        .with_module(places_module())
+        .with_module(
+            GrammarModule::new("story")
+                .with_import("names", "name")
+                .with_import_as("places", "name", "place")
+                .with_new_rule("sentence", vec![Symbol::Nonterminal("name"), Symbol::Terminal("visits"), Symbol::Nonterminal("place")])
+        )
+        .link()
+        .unwrap();
+
+    assert_eq!(
+        rules.iter().filter(|rule| rule.pattern == QualifiedNonterm::new("names", "name")).count(),
+        2
+    );
+    assert_eq!(
+        rules.iter().filter(|rule| rule.pattern == QualifiedNonterm::new("places", "name")).count(),
+        2
+    );
+    assert_eq!(
+        rules.last(),
+        Some(&Rule::new(
+            QualifiedNonterm::new("story", "sentence"),
+            vec![
+                Symbol::Nonterminal(QualifiedNonterm::new("names", "name")),
+                Symbol::Terminal("visits"),
+                Symbol::Nonterminal(QualifiedNonterm::new("places", "name"))
+            ]
+        ))
+    );
+
+    let mut expander = ExpanderBuilder::from(rules).build();
+
+    for _ in 0..20 {
+        let sentence = expander.expand(vec![Symbol::Nonterminal(QualifiedNonterm::new("story", "sentence"))]).unwrap();
+
+        assert!(["Alice", "Bob"].contains(&sentence[0]));
+        assert_eq!(sentence[1], "visits");
+        assert!(["Paris", "the old mill"].contains(&sentence[2]));
+    }
+}
+
+#[test]
+fn grammar_modules_overrides()
+{
+    let rules = GrammarLinker::new()
+        .with_module(names_module())
+        .with_module(places_module())
+        .with_module(
+            GrammarModule::new("fantasy")
+                .with_import("names", "name")
+                .with_import_as("places", "name", "place")
+                .with_override(OverrideMode::Replace, Rule::new("name", vec![Symbol::Nonterminal("elven_name")]))
+                .with_override(OverrideMode::Extend, Rule::new("place", vec![Symbol::Terminal("Rivendell")]))
+                .with_new_rule("elven_name", vec![Symbol::Terminal("Elrond")])
+        )
+        .link()
+        .unwrap();
+
+    let name_rules: Vec<_> = rules.iter()
+        .filter(|rule| rule.pattern == QualifiedNonterm::new("names", "name"))
+        .collect();
+
+    assert_eq!(
+        name_rules,
+        vec![&Rule::new(
+            QualifiedNonterm::new("names", "name"),
+            vec![Symbol::Nonterminal(QualifiedNonterm::new("fantasy", "elven_name"))]
+        )]
+    );
+
+    let place_rules: Vec<_> = rules.iter()
+        .filter(|rule| rule.pattern == QualifiedNonterm::new("places", "name"))
+        .map(|rule| rule.replacement.clone())
+        .collect();
+
+    assert_eq!(
+        place_rules,
+        vec![
+            vec![Symbol::Terminal("Paris")],
+            vec![Symbol::Nonterminal(QualifiedNonterm::new("places", "village"))],
+            vec![Symbol::Terminal("Rivendell")]
+        ]
+    );
+}
+
+#[test]
+fn grammar_modules_link_errors()
+{
+    let link = |modules: Vec<GrammarModule<&'static str, &'static str>>| {
+        modules.into_iter()
+            .fold(GrammarLinker::new(), GrammarLinker::with_module)
+            .link()
+            .unwrap_err()
+    };
+
+    assert_eq!(
+        link(vec![names_module(), names_module()]),
+        LinkError::DuplicateModule("names".to_string())
+    );
+
+    assert_eq!(
+        link(vec![GrammarModule::new("story").with_import("people", "name")]),
+        LinkError::UnknownModule{module: "story".to_string(), imported_module: "people".to_string()}
+    );
+
+    assert_eq!(
+        link(vec![places_module(), GrammarModule::new("story").with_import("places", "village")]),
+        LinkError::NotExported{module: "story".to_string(), imported_module: "places".to_string(), name: "village"}
+    );
+
+    assert_eq!(
+        link(vec![
+            names_module(),
+            GrammarModule::new("story")
+                .with_import("names", "name")
+                .with_new_rule("name", vec![Symbol::Terminal("Carol")])
+        ]),
+        LinkError::ConflictingImport{module: "story".to_string(), local_name: "name"}
+    );
+
+    assert_eq!(
+        link(vec![GrammarModule::new("story").with_new_rule("sentence", vec![Symbol::Nonterminal("name")])]),
+        LinkError::UndefinedNonterm{module: "story".to_string(), name: "name"}
+    );
+
+    assert_eq!(
+        link(vec![GrammarModule::new("story").with_export("sentence")]),
+        LinkError::UndefinedNonterm{module: "story".to_string(), name: "sentence"}
+    );
+
+    assert_eq!(
+        link(vec![
+            GrammarModule::new("story").with_override(OverrideMode::Extend, Rule::new("name", vec![Symbol::Terminal("Carol")]))
+        ]),
+        LinkError::InvalidOverride{module: "story".to_string(), name: "name"}
+    );
+
+    let replacing_module = |name: &str| {
+        GrammarModule::new(name)
+            .with_import("names", "name")
+            .with_override(OverrideMode::Replace, Rule::new("name", vec![Symbol::Terminal("Carol")]))
+    };
+
+    let error = link(vec![names_module(), replacing_module("first"), replacing_module("second")]);
+
+    assert_eq!(
+        error,
+        LinkError::ConflictingOverrides{
+            target:  QualifiedNonterm::new("names", "name"),
+            modules: ("first".to_string(), "second".to_string())
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "both module \"first\" and module \"second\" replace rules of \"name\" from module \"names\""
+    );
+}
+
+#[test]
+fn grammar_modules_qualified_nonterm_display()
+{
+    assert_eq!(QualifiedNonterm::new("names", "name").to_string(), "names::name");
+}
+
+fn names_module() -> GrammarModule<&'static str, &'static str>
+{
+    GrammarModule::new("names")
+        .with_new_rule("name", vec![Symbol::Terminal("Alice")])
+        .with_new_rule("name", vec![Symbol::Terminal("Bob")])
+        .with_export("name")
+}
+
+fn places_module() -> GrammarModule<&'static str, &'static str>
+{
+    GrammarModule::new("places")
+        .with_new_rule("name", vec![Symbol::Terminal("Paris")])
+        .with_new_rule("name", vec![Symbol::Nonterminal("village")])
+        .with_new_rule("village", vec![Symbol::Terminal("the old mill")])
+        .with_export("name")
+}
@@ -0,0 +1,180 @@
+use branchy::{
+    Symbol,
+    Rule,
+    ParseError,
+    ParseErrorKind,
+    display_grammar,
+    parse_grammar
+};
+
+#[test]
+fn symbol_and_rule_display()
+{
+    assert_eq!(Symbol::<&str, &str>::Nonterminal("name").to_string(), "<name>");
+    assert_eq!(Symbol::<&str, &str>::Terminal("say \"hi\"").to_string(), "\"say \\\"hi\\\"\"");
+    assert_eq!(Symbol::<&str, &str>::Nonterminal("a>b\\c").to_string(), "<a\\>b\\\\c>");
+
+    assert_eq!(
+        Rule::new("x", vec![Symbol::Nonterminal("y"), Symbol::Terminal("z")]).to_string(),
+        "x -> <y> \"z\""
+    );
+    assert_eq!(
+        Rule::<_, &str>::new("x", vec![]).with_weight(2.5).to_string(),
+        "x -> ε [2.5]"
+    );
+    assert_eq!(
+        Rule::<_, &str>::new("big x", vec![Symbol::Nonterminal("y")]).to_string(),
+        "<big x> -> <y>"
+    );
+}
+
+#[test]
+fn grammar_display_parse_round_trip()
+{
+    let rules = vec![
+        Rule::new("sentence", vec![Symbol::Nonterminal("name"), Symbol::Terminal("says"), Symbol::Nonterminal("quote")]),
+        Rule::new("name", vec![Symbol::Terminal("Alice")]).with_weight(3.0),
+        Rule::new("quote", vec![Symbol::Terminal("\"hi\" | # [1]")]),
+        Rule::new("name", vec![Symbol::Terminal("Bob")]),
+        Rule::new("name", vec![]).with_weight(0.25),
+        Rule::new("odd -> name", vec![Symbol::Nonterminal("odd -> name")])
+    ];
+
+    let text = display_grammar(&rules).to_string();
+
+    assert_eq!(
+        text,
+        "sentence -> <name> \"says\" <quote>\n\
+         name -> \"Alice\" [3] | \"Bob\" | ε [0.25]\n\
+         quote -> \"\\\"hi\\\" | # [1]\"\n\
+         <odd -\\> name> -> <odd -\\> name>\n"
+    );
+
+    let parsed_rules = parse_grammar(&text).unwrap();
+
+    let grouped_rules: Vec<_> = [0, 1, 3, 4, 2, 5].iter()
+        .map(|rule_idx| {
+            let rule = &rules[*rule_idx];
+
+            Rule::new(
+                rule.pattern.to_string(),
+                rule.replacement.iter()
+                    .map(|symbol| match symbol {
+                        Symbol::Nonterminal(value) => Symbol::Nonterminal(value.to_string()),
+                        Symbol::Terminal(value)    => Symbol::Terminal(value.to_string())
+                    })
+                    .collect()
+            ).with_weight(rule.weight)
+        })
+        .collect();
+
+    assert_eq!(parsed_rules, grouped_rules);
+}
+
+#[test]
+fn grammar_display_parse_round_trip_of_unusual_values()
+{
+    let rules = vec![
+        Rule::new("".to_string(), vec![Symbol::Nonterminal("".to_string()), Symbol::Terminal("".to_string())]),
+        Rule::new("a->b".to_string(), vec![Symbol::Terminal("line\nbreak\r\tand\u{7f}".to_string())])
+            .with_modifier(0, "new\nline")
+            .with_weight(-1.0),
+        Rule::new("\u{1}".to_string(), vec![Symbol::Terminal("\\u{1}".to_string())]).with_weight(f64::INFINITY)
+    ];
+
+    let text = display_grammar(&rules).to_string();
+
+    assert_eq!(
+        text,
+        "<> -> <> \"\"\n\
+         <a-\\>b> -> \"line\\nbreak\\r\\tand\\u{7f}\".\"new\\nline\" [-1]\n\
+         <\\u{1}> -> \"\\\\u{1}\" [inf]\n"
+    );
+    assert_eq!(parse_grammar(&text).unwrap(), rules);
+
+    let nan_rules = parse_grammar(&display_grammar(&[Rule::<_, String>::new("a", vec![]).with_weight(f64::NAN)]).to_string())
+        .unwrap();
+
+    assert!(nan_rules[0].weight.is_nan());
+}
+
+#[test]
+fn parse_grammar_comments_and_continuations()
+{
+    let rules = parse_grammar(
+        "# Animals\n\
+         \n\
+         animal -> \"cat\"   # the best one\n\
+         \x20   | \"dog\" [0.5] | <bird>\n\
+         <bird> -> \"owl\"\n"
+    ).unwrap();
+
+    assert_eq!(
+        rules,
+        vec![
+            Rule::new("animal".to_string(), vec![Symbol::Terminal("cat".to_string())]),
+            Rule::new("animal".to_string(), vec![Symbol::Terminal("dog".to_string())]).with_weight(0.5),
+            Rule::new("animal".to_string(), vec![Symbol::Nonterminal("bird".to_string())]),
+            Rule::new("bird".to_string(), vec![Symbol::Terminal("owl".to_string())])
+        ]
+    );
+}
+
+#[test]
+fn parse_grammar_errors()
+{
+    let error = |line, column, kind| Err(ParseError{line, column, kind});
+
+    assert_eq!(parse_grammar("a <b>"), error(1, 6, ParseErrorKind::MissingArrow));
+    assert_eq!(parse_grammar("  -> <b>"), error(1, 3, ParseErrorKind::EmptyPattern));
+    assert_eq!(parse_grammar("\n  | <b>"), error(2, 3, ParseErrorKind::ContinuationWithoutRule));
+    assert_eq!(parse_grammar("a -> <b> |"), error(1, 11, ParseErrorKind::EmptyAlternative));
+    assert_eq!(parse_grammar("a -> # nothing"), error(1, 6, ParseErrorKind::EmptyAlternative));
+    assert_eq!(parse_grammar("a -> \"b"), error(1, 6, ParseErrorKind::UnterminatedSymbol));
+    assert_eq!(parse_grammar("a -> <b> [x]"), error(1, 10, ParseErrorKind::InvalidWeight("x".to_string())));
+    assert_eq!(parse_grammar("a -> \"\\u{zz}\""), error(1, 7, ParseErrorKind::InvalidEscape("\\u{zz}".to_string())));
+    assert_eq!(parse_grammar("a -> <\\u>"), error(1, 7, ParseErrorKind::InvalidEscape("\\u>".to_string())));
+    assert_eq!(parse_grammar("a -> <b> [1] <c>"), error(1, 14, ParseErrorKind::UnexpectedCharacter('<')));
+    assert_eq!(parse_grammar("a -> ε <c>"), error(1, 8, ParseErrorKind::UnexpectedCharacter('<')));
+    assert_eq!(parse_grammar("a -> b"), error(1, 6, ParseErrorKind::UnexpectedCharacter('b')));
+
+    assert_eq!(
+        parse_grammar("a -> <b> [x]").unwrap_err().to_string(),
+        "1:10: invalid rule weight \"x\""
+    );
+}
+
+#[test]
+fn modifiers_notation()
+{
+    let rule = Rule::new("x", vec![Symbol::Nonterminal("y"), Symbol::Terminal("z")])
+        .with_modifier(0, "s")
+        .with_modifier(1, "upper")
+        .with_modifier(0, "capitalize");
+
+    assert_eq!(rule.to_string(), "x -> <y>.s.capitalize \"z\".upper");
+
+    let rules = parse_grammar("x -> <y>.s.capitalize \"z\".upper [2] | <y>.a_1").unwrap();
+
+    assert_eq!(rules.len(), 2);
+    assert_eq!(rules[0].to_string(), "x -> <y>.s.capitalize \"z\".upper [2]");
+    assert_eq!(rules[0].symbol_modifiers(0).collect::<Vec<_>>(), ["s", "capitalize"]);
+    assert_eq!(rules[1].symbol_modifiers(0).collect::<Vec<_>>(), ["a_1"]);
+
+    let quoted_rule = Rule::new("x".to_string(), vec![Symbol::Nonterminal("y".to_string())])
+        .with_modifier(0, "title case")
+        .with_modifier(0, "a.b \"c\"")
+        .with_modifier(0, "");
+
+    assert_eq!(quoted_rule.to_string(), r#"x -> <y>."title case"."a.b \"c\""."""#);
+    assert_eq!(parse_grammar(&quoted_rule.to_string()).unwrap(), vec![quoted_rule]);
+
+    assert_eq!(
+        parse_grammar("x -> <y>. \"z\"").unwrap_err(),
+        ParseError{line: 1, column: 9, kind: ParseErrorKind::UnexpectedCharacter('.')}
+    );
+    assert_eq!(
+        parse_grammar("x -> <y> .s").unwrap_err(),
+        ParseError{line: 1, column: 10, kind: ParseErrorKind::UnexpectedCharacter('.')}
+    );
+}
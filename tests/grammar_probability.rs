@@ -0,0 +1,128 @@
+use branchy::{
+    Symbol,
+    Rule,
+    ExpanderBuilder,
+    WeightedRandomRuleSelector,
+    ProbabilisticGrammar,
+    RuleDistribution
+};
+
+const EPSILON: f64 = 1e-9;
+
+#[test]
+fn derivation_probability_follows_selector_distribution()
+{
+    let mut expander = ExpanderBuilder::new()
+        .with_rule(Rule::new("greeting", vec![Symbol::Terminal("hello"), Symbol::Nonterminal("name")]))
+        .with_rule(Rule::new("name", vec![Symbol::Terminal("Alice")]).with_weight(3.0))
+        .with_rule(Rule::new("name", vec![Symbol::Terminal("Bob")]))
+        .build();
+
+    let derivation = expander.expand_derivation(vec![Symbol::Nonterminal("greeting")]).unwrap();
+
+    assert!((expander.probabilistic_grammar().derivation_probability(&derivation) - 0.5).abs() < EPSILON);
+
+    let expander = expander.with_rule_selector(WeightedRandomRuleSelector::new());
+
+    let expected_probability = if derivation.terminals() == ["hello", "Alice"] { 0.75 } else { 0.25 };
+
+    assert!((expander.probabilistic_grammar().derivation_probability(&derivation) - expected_probability).abs() < EPSILON);
+}
+
+#[test]
+fn sentence_probability_sums_over_derivations()
+{
+    let rules = vec![
+        Rule::new("S", vec![Symbol::Nonterminal("S"), Symbol::Nonterminal("S")]).with_weight(0.2),
+        Rule::new("S", vec![Symbol::Terminal("a")]).with_weight(0.8)
+    ];
+
+    let grammar = ProbabilisticGrammar::new(&rules, RuleDistribution::Weighted);
+
+    let input = [Symbol::Nonterminal("S")];
+
+    assert!((grammar.sentence_probability(&input, &["a"]) - 0.8).abs() < EPSILON);
+    assert!((grammar.sentence_probability(&input, &["a", "a"]) - 0.2 * 0.8 * 0.8).abs() < EPSILON);
+    assert!((grammar.sentence_probability(&input, &["a", "a", "a"]) - 2.0 * 0.2 * 0.2 * 0.8 * 0.8 * 0.8).abs() < EPSILON);
+    assert_eq!(grammar.sentence_probability(&input, &[]), 0.0);
+    assert_eq!(grammar.sentence_probability(&input, &["b"]), 0.0);
+}
+
+#[test]
+fn sentence_probability_with_empty_and_unit_rules()
+{
+    let rules = vec![
+        Rule::new("As", vec![Symbol::Terminal("a"), Symbol::Nonterminal("As")]),
+        Rule::new("As", vec![]),
+        Rule::new("B", vec![Symbol::Nonterminal("B")]),
+        Rule::new("B", vec![Symbol::Terminal("b")])
+    ];
+
+    let grammar = ProbabilisticGrammar::new(&rules, RuleDistribution::Uniform);
+
+    let input = [Symbol::Nonterminal("As"), Symbol::Terminal("b"), Symbol::Nonterminal("B")];
+
+    assert!((grammar.sentence_probability(&input, &["b", "b"]) - 0.5).abs() < EPSILON);
+    assert!((grammar.sentence_probability(&input, &["a", "a", "b", "b"]) - 0.125).abs() < EPSILON);
+    assert_eq!(grammar.sentence_probability(&input, &["a", "b"]), 0.0);
+}
+
+#[test]
+fn expected_length_and_entropy()
+{
+    let rules = vec![
+        Rule::new("list", vec![Symbol::Nonterminal("item")]),
+        Rule::new("list", vec![Symbol::Nonterminal("item"), Symbol::Terminal(","), Symbol::Nonterminal("list")]),
+        Rule::new("item", vec![Symbol::Terminal("x")]),
+        Rule::new("item", vec![Symbol::Terminal("y")]),
+        Rule::new("item", vec![Symbol::Terminal("z")]),
+        Rule::new("item", vec![Symbol::Terminal("w")])
+    ];
+
+    let grammar = ProbabilisticGrammar::new(&rules, RuleDistribution::Uniform);
+
+    // On average, a list has 2 items and 1 separator.
+    assert!((grammar.expected_length(&"list").unwrap() - 3.0).abs() < EPSILON);
+    assert!((grammar.expected_length(&"item").unwrap() - 1.0).abs() < EPSILON);
+
+    assert!((grammar.rule_entropy(&"list").unwrap() - 1.0).abs() < EPSILON);
+    assert!((grammar.rule_entropy(&"item").unwrap() - 2.0).abs() < EPSILON);
+
+    // 1 bit for choosing whether to continue plus 2 bits per item, 2 times on average.
+    assert!((grammar.derivation_entropy(&"list").unwrap() - 6.0).abs() < EPSILON);
+
+    assert_eq!(grammar.expected_length(&"unknown"), None);
+    assert_eq!(grammar.rule_entropy(&"unknown"), None);
+}
+
+#[test]
+fn divergent_nonterms_detection()
+{
+    let rules = vec![
+        Rule::new("forest", vec![Symbol::Nonterminal("tree"), Symbol::Nonterminal("word")]),
+        Rule::new("tree", vec![Symbol::Terminal("leaf")]),
+        Rule::new("tree", vec![Symbol::Nonterminal("tree"), Symbol::Nonterminal("tree")]),
+        Rule::new("word", vec![Symbol::Terminal("oak")])
+    ];
+
+    // Exactly critical: every tree is expected to be replaced with one tree.
+    let grammar = ProbabilisticGrammar::new(&rules, RuleDistribution::Uniform);
+
+    assert!(grammar.is_divergent(&"tree"));
+    assert!(grammar.is_divergent(&"forest"));
+    assert!(!grammar.is_divergent(&"word"));
+    assert_eq!(grammar.divergent_nonterms(), vec![&"forest", &"tree"]);
+    assert_eq!(grammar.expected_length(&"forest"), Some(f64::INFINITY));
+    assert_eq!(grammar.expected_length(&"word"), Some(1.0));
+
+    let mut weighted_rules = rules.clone();
+
+    weighted_rules[1].weight = 2.0;
+
+    let weighted_grammar = ProbabilisticGrammar::new(&weighted_rules, RuleDistribution::Weighted);
+
+    assert!(weighted_grammar.divergent_nonterms().is_empty());
+    // E = 2/3 + 1/3 * 2E, so E = 2.
+    assert!((weighted_grammar.expected_length(&"tree").unwrap() - 2.0).abs() < EPSILON);
+    assert!((weighted_grammar.expected_length(&"forest").unwrap() - 3.0).abs() < EPSILON);
+}
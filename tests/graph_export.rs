@@ -0,0 +1,132 @@
+use branchy::{
+    Symbol,
+    Rule,
+    Derivation,
+    GraphFormat,
+    grammar_graph,
+    derivation_graph
+};
+
+#[test]
+fn grammar_graph_dot()
+{
+    assert_eq!(
+        grammar_graph(&list_rules(), GraphFormat::Dot),
+        "digraph grammar {\n\
+        \x20   n0 [label=\"list\"];\n\
+        \x20   n1 [label=\"item\"];\n\
+        \x20   n2 [label=\"missing\", style=dashed, color=red];\n\
+        \x20   n0 -> n1 [label=\"2\"];\n\
+        \x20   n0 -> n0 [label=\"1\", style=bold];\n\
+        \x20   n1 -> n2 [label=\"1\"];\n\
+        }\n"
+    );
+}
+
+#[test]
+fn grammar_graph_mermaid_weighted()
+{
+    let mut rules = list_rules();
+
+    rules[1].weight = 0.5;
+
+    assert_eq!(
+        grammar_graph(&rules, GraphFormat::Mermaid),
+        "flowchart TD\n\
+        \x20   n0([\"list\"])\n\
+        \x20   n1([\"item\"])\n\
+        \x20   n2([\"missing\"])\n\
+        \x20   class n2 undefined\n\
+        \x20   class n2 unproductive\n\
+        \x20   n0 -- \"2, w=1.5\" --> n1\n\
+        \x20   n0 == \"1, w=0.5\" ==> n0\n\
+        \x20   n1 -- \"1, w=1\" --> n2\n\
+        \x20   classDef undefined stroke-dasharray:5 5\n\
+        \x20   classDef unproductive stroke:#f00\n"
+    );
+}
+
+#[test]
+fn grammar_graph_indirect_recursion()
+{
+    let rules = vec![
+        Rule::new("expr", vec![Symbol::Nonterminal("term")]),
+        Rule::new("term", vec![Symbol::Terminal("("), Symbol::Nonterminal("expr"), Symbol::Terminal(")")]),
+        Rule::new("term", vec![Symbol::Nonterminal("atom")]),
+        Rule::new("atom", vec![Symbol::Terminal("x")])
+    ];
+
+    assert_eq!(
+        grammar_graph(&rules, GraphFormat::Dot),
+        "digraph grammar {\n\
+        \x20   n0 [label=\"expr\"];\n\
+        \x20   n1 [label=\"term\"];\n\
+        \x20   n2 [label=\"atom\"];\n\
+        \x20   n0 -> n1 [label=\"1\", style=bold];\n\
+        \x20   n1 -> n0 [label=\"1\", style=bold];\n\
+        \x20   n1 -> n2 [label=\"1\"];\n\
+        }\n"
+    );
+}
+
+#[test]
+fn derivation_graph_dot_and_mermaid()
+{
+    let rules = list_rules();
+
+    let derivation = Derivation::from_rule_idxs(
+        &rules,
+        &[Symbol::Nonterminal("list"), Symbol::Terminal("\"end\"")],
+        &[1, 2, 0, 2]
+    ).unwrap();
+
+    assert_eq!(
+        derivation_graph(&derivation, GraphFormat::Dot),
+        "digraph derivation {\n\
+        \x20   ordering=out;\n\
+        \x20   n0 [label=\"list\"];\n\
+        \x20   n1 [label=\"item\"];\n\
+        \x20   n2 [label=\"x\", shape=box];\n\
+        \x20   n1 -> n2;\n\
+        \x20   n0 -> n1;\n\
+        \x20   n3 [label=\",\", shape=box];\n\
+        \x20   n0 -> n3;\n\
+        \x20   n4 [label=\"list\"];\n\
+        \x20   n5 [label=\"item\"];\n\
+        \x20   n6 [label=\"x\", shape=box];\n\
+        \x20   n5 -> n6;\n\
+        \x20   n4 -> n5;\n\
+        \x20   n0 -> n4;\n\
+        \x20   n7 [label=\"\\\"end\\\"\", shape=box];\n\
+        }\n"
+    );
+
+    assert_eq!(
+        derivation_graph(&derivation, GraphFormat::Mermaid),
+        "flowchart TD\n\
+        \x20   n0([\"list\"])\n\
+        \x20   n1([\"item\"])\n\
+        \x20   n2[\"x\"]\n\
+        \x20   n1 --> n2\n\
+        \x20   n0 --> n1\n\
+        \x20   n3[\",\"]\n\
+        \x20   n0 --> n3\n\
+        \x20   n4([\"list\"])\n\
+        \x20   n5([\"item\"])\n\
+        \x20   n6[\"x\"]\n\
+        \x20   n5 --> n6\n\
+        \x20   n4 --> n5\n\
+        \x20   n0 --> n4\n\
+        \x20   n7[\"#quot;end#quot;\"]\n"
+    );
+}
+
+fn list_rules() -> Vec<Rule<&'static str, &'static str>>
+{
+    vec![
+        Rule::new("list", vec![Symbol::Nonterminal("item")]),
+        Rule::new("list", vec![Symbol::Nonterminal("item"), Symbol::Terminal(","), Symbol::Nonterminal("list")]),
+        Rule::new("item", vec![Symbol::Terminal("x")]),
+        Rule::new("item", vec![Symbol::Nonterminal("missing")])
+    ]
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "log")]
+
+use std::sync::Mutex;
+
+use log::{
+    Log,
+    Metadata,
+    Record
+};
+
+use branchy::{
+    Symbol,
+    ExpanderBuilder,
+    LogExpansionLogger
+};
+
+static RECORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+#[test]
+fn log_logger()
+{
+    log::set_logger(&RecordingLogger).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+
+    let mut expander = ExpanderBuilder::new()
+        .with_new_rule("greeting", vec![Symbol::Terminal("Hello"), Symbol::Nonterminal("name")])
+        .with_new_rule("name", vec![Symbol::Terminal("world")])
+        .with_logger(LogExpansionLogger::new())
+        .build();
+
+    expander.expand(vec![Symbol::Nonterminal("greeting")]).unwrap();
+    expander.expand(vec![Symbol::Nonterminal("farewell")]).unwrap_err();
+
+    assert_eq!(
+        *RECORDS.lock().unwrap(),
+        vec![
+            "DEBUG expand#1: started with input [Nonterminal(\"greeting\")]",
+            "TRACE expand#1: expanded \"greeting\" to [Terminal(\"Hello\"), Nonterminal(\"name\")]",
+            "TRACE expand#1: expanded \"name\" to [Terminal(\"world\")]",
+            "DEBUG expand#1: fully expanded to [\"Hello\", \"world\"]",
+            "DEBUG expand#2: started with input [Nonterminal(\"farewell\")]",
+            "WARN expand#2: failed to expand \"farewell\""
+        ]
+    );
+}
+
+//
+// Service types
+//
+
+struct RecordingLogger;
+
+impl Log for RecordingLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target() == "branchy"
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if self.enabled(record.metadata()) {
+            RECORDS.lock().unwrap().push(format!("{} {}", record.level(), record.args()));
+        }
+    }
+
+    fn flush(&self) {
+        // Empty
+    }
+}
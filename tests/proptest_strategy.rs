@@ -0,0 +1,129 @@
+#![cfg(feature = "proptest")]
+
+use branchy::{
+    Symbol,
+    Rule,
+    DerivationTree,
+    ExpanderBuilder,
+    GrammarStrategy
+};
+use proptest::{
+    strategy::{
+        Strategy,
+        ValueTree
+    },
+    test_runner::{
+        TestRunner,
+        TestError
+    }
+};
+
+#[test]
+fn proptest_strategy_generates_valid_derivations()
+{
+    let rules    = expression_rules();
+    let strategy = GrammarStrategy::new(rules.clone(), vec![Symbol::Nonterminal("expr"), Symbol::Terminal(";")]);
+
+    let mut runner = TestRunner::deterministic();
+
+    for _ in 0..100 {
+        let derivation = strategy.new_tree(&mut runner).unwrap().current();
+
+        assert_eq!(derivation.input(), vec![Symbol::Nonterminal("expr"), Symbol::Terminal(";")]);
+
+        for tree in &derivation.trees {
+            assert_valid_tree(tree, &rules);
+        }
+    }
+}
+
+#[test]
+fn proptest_strategy_shrinks_on_derivation_tree()
+{
+    let rules    = expression_rules();
+    let strategy = GrammarStrategy::new(rules.clone(), vec![Symbol::Nonterminal("expr")])
+        .with_max_node_count(256);
+
+    let mut runner = TestRunner::deterministic();
+
+    let result = runner.run(&strategy, |derivation| {
+        if derivation.terminals().contains(&"*") {
+            Err(proptest::test_runner::TestCaseError::fail("contains *"))
+        } else {
+            Ok(())
+        }
+    });
+
+    match result {
+        Err(TestError::Fail(_, derivation)) => {
+            assert_eq!(derivation.terminals(), vec!["x", "*", "x"]);
+
+            for tree in &derivation.trees {
+                assert_valid_tree(tree, &rules);
+            }
+        },
+        result => panic!("expected the property to fail, got {:?}", result)
+    }
+}
+
+#[test]
+fn proptest_strategy_outputs_from_expander()
+{
+    let expander = ExpanderBuilder::from(expression_rules()).build();
+    let strategy = GrammarStrategy::from_expander(&expander, vec![Symbol::Nonterminal("expr")]).outputs();
+
+    let mut runner = TestRunner::deterministic();
+
+    let result = runner.run(&strategy, |output| {
+        assert!(!output.is_empty());
+        assert!(output.iter().all(|terminal| ["x", "1", "+", "*", "(", ")"].contains(terminal)));
+
+        Ok(())
+    });
+
+    assert!(result.is_ok());
+}
+
+#[test]
+fn proptest_strategy_rejects_unexpandable_input()
+{
+    let strategy = GrammarStrategy::new(expression_rules(), vec![Symbol::Nonterminal("loop")]);
+
+    let mut runner = TestRunner::deterministic();
+
+    assert!(strategy.new_tree(&mut runner).is_err());
+}
+
+fn expression_rules() -> Vec<Rule<&'static str, &'static str>>
+{
+    vec![
+        Rule::new("expr", vec![Symbol::Nonterminal("term")]),
+        Rule::new("expr", vec![Symbol::Nonterminal("expr"), Symbol::Terminal("+"), Symbol::Nonterminal("term")]),
+        Rule::new("term", vec![Symbol::Nonterminal("factor")]),
+        Rule::new("term", vec![Symbol::Nonterminal("term"), Symbol::Terminal("*"), Symbol::Nonterminal("factor")]),
+        Rule::new("factor", vec![Symbol::Terminal("x")]),
+        Rule::new("factor", vec![Symbol::Terminal("1")]),
+        Rule::new("factor", vec![Symbol::Terminal("("), Symbol::Nonterminal("expr"), Symbol::Terminal(")")]),
+        Rule::new("loop", vec![Symbol::Nonterminal("loop")])
+    ]
+}
+
+fn assert_valid_tree(tree: &DerivationTree<&'static str, &'static str>, rules: &[Rule<&'static str, &'static str>])
+{
+    if let DerivationTree::Nonterminal{value, rule_idx, children} = tree {
+        let rule = &rules[*rule_idx];
+
+        assert_eq!(rule.pattern, *value);
+        assert_eq!(rule.replacement.len(), children.len());
+
+        for (symbol, child) in rule.replacement.iter().zip(children) {
+            match (symbol, child.symbol()) {
+                (Symbol::Nonterminal(expected), Symbol::Nonterminal(actual)) => assert_eq!(expected, actual),
+                (Symbol::Terminal(expected), Symbol::Terminal(actual))       => assert_eq!(expected, actual),
+                _                                                            => panic!("symbol kind mismatch")
+            }
+
+            assert_valid_tree(child, rules);
+        }
+    }
+}
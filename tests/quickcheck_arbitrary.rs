@@ -0,0 +1,98 @@
+#![cfg(feature = "quickcheck")]
+
+use branchy::{
+    Symbol,
+    Rule,
+    DerivationTree,
+    ArbitraryGrammar,
+    GrammarSample
+};
+use quickcheck::{
+    Arbitrary,
+    Gen,
+    QuickCheck
+};
+
+struct ExpressionGrammar;
+
+impl ArbitraryGrammar for ExpressionGrammar {
+    type Nonterminal = &'static str;
+    type Terminal    = &'static str;
+
+    fn rules() -> Vec<Rule<&'static str, &'static str>> {
+        vec![
+            Rule::new("expr", vec![Symbol::Nonterminal("term")]),
+            Rule::new("expr", vec![Symbol::Nonterminal("expr"), Symbol::Terminal("+"), Symbol::Nonterminal("term")]),
+            Rule::new("term", vec![Symbol::Nonterminal("factor")]),
+            Rule::new("term", vec![Symbol::Nonterminal("term"), Symbol::Terminal("*"), Symbol::Nonterminal("factor")]),
+            Rule::new("factor", vec![Symbol::Terminal("x")]),
+            Rule::new("factor", vec![Symbol::Terminal("1")]),
+            Rule::new("factor", vec![Symbol::Terminal("("), Symbol::Nonterminal("expr"), Symbol::Terminal(")")])
+        ]
+    }
+
+    fn input() -> Vec<Symbol<&'static str, &'static str>> {
+        vec![Symbol::Nonterminal("expr"), Symbol::Terminal(";")]
+    }
+}
+
+#[test]
+fn quickcheck_arbitrary_generates_valid_derivations()
+{
+    fn is_valid(sample: GrammarSample<ExpressionGrammar>) -> bool {
+        sample.derivation.input() == ExpressionGrammar::input()
+            && sample.terminals().last() == Some(&";")
+            && sample.derivation.trees.iter().all(|tree| is_valid_tree(tree, &ExpressionGrammar::rules()))
+    }
+
+    QuickCheck::new().quickcheck(is_valid as fn(GrammarSample<ExpressionGrammar>) -> bool);
+}
+
+#[test]
+fn quickcheck_arbitrary_shrinks_on_derivation_tree()
+{
+    let mut gen = Gen::new(64);
+
+    let sample = (0..1000)
+        .map(|_| GrammarSample::<ExpressionGrammar>::arbitrary(&mut gen))
+        .find(|sample| sample.derivation.node_count() > 10)
+        .expect("some samples should be large enough to shrink");
+
+    let shrunk_samples: Vec<_> = sample.shrink().collect();
+
+    assert!(!shrunk_samples.is_empty());
+
+    for shrunk_sample in &shrunk_samples {
+        assert!(shrunk_sample.derivation.node_count() < sample.derivation.node_count());
+        assert_eq!(shrunk_sample.derivation.input(), ExpressionGrammar::input());
+        assert!(shrunk_sample.derivation.trees.iter().all(|tree| is_valid_tree(tree, &ExpressionGrammar::rules())));
+    }
+
+    let mut minimal_sample = sample;
+
+    while let Some(shrunk_sample) = minimal_sample.shrink().next() {
+        minimal_sample = shrunk_sample;
+    }
+
+    assert_eq!(minimal_sample.derivation.node_count(), 5);
+    assert!(["x", "1"].contains(&minimal_sample.terminals()[0]));
+}
+
+fn is_valid_tree(tree: &DerivationTree<&'static str, &'static str>, rules: &[Rule<&'static str, &'static str>]) -> bool
+{
+    match tree {
+        DerivationTree::Terminal(_)                            => true,
+        DerivationTree::Nonterminal{value, rule_idx, children} => {
+            let rule = &rules[*rule_idx];
+
+            rule.pattern == *value
+                && rule.replacement.len() == children.len()
+                && rule.replacement.iter().zip(children).all(|(symbol, child)| match (symbol, child.symbol()) {
+                    (Symbol::Nonterminal(expected), Symbol::Nonterminal(actual)) => expected == actual,
+                    (Symbol::Terminal(expected), Symbol::Terminal(actual))       => expected == actual,
+                    _                                                            => false
+                })
+                && children.iter().all(|child| is_valid_tree(child, rules))
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use branchy::{
+    Symbol,
+    Rule,
+    ExpanderBuilder,
+    RewriteRule,
+    RewriteMode,
+    Rewriter
+};
+
+#[test]
+fn rewriting_lsystem_generations()
+{
+    // Lindenmayer's original L-system, modelling the growth of algae.
+    let rewriter = Rewriter::new(vec![
+        Rule::new('A', vec![Symbol::Nonterminal('A'), Symbol::Nonterminal('B')]).into(),
+        Rule::new('B', vec![Symbol::Nonterminal('A')]).into()
+    ]).with_max_iterations(5);
+
+    let rewritten = rewriter.rewrite(vec![Symbol::<char, ()>::Nonterminal('A')], &mut rand::thread_rng());
+
+    let generation: String = rewritten.symbols.into_iter().map(Symbol::unwrap_nonterm).collect();
+
+    assert_eq!(generation, "ABAABABAABAAB");
+    assert_eq!(rewritten.iterations, 5);
+    assert!(!rewritten.is_complete);
+}
+
+#[test]
+fn rewriting_default_max_iterations()
+{
+    let rewriter = Rewriter::new(vec![
+        Rule::new('A', vec![Symbol::Nonterminal('A'), Symbol::Nonterminal('B')]).into(),
+        Rule::new('B', vec![Symbol::Nonterminal('A')]).into()
+    ]);
+
+    let rewritten = rewriter.rewrite(vec![Symbol::<char, ()>::Nonterminal('A')], &mut rand::thread_rng());
+
+    // Generations grow like Fibonacci numbers, so only a few of them are performed by default.
+    assert_eq!(rewriter.max_iterations(), 10);
+    assert_eq!(rewritten.iterations, 10);
+    assert_eq!(rewritten.symbols.len(), 144);
+
+    let rewriter = rewriter.with_mode(RewriteMode::Sequential);
+
+    assert_eq!(rewriter.max_iterations(), 1000);
+    assert_eq!(rewriter.with_max_iterations(3).max_iterations(), 3);
+}
+
+#[test]
+fn rewriting_weighted_rules()
+{
+    let rewriter = Rewriter::new(vec![
+        RewriteRule::new(vec![Symbol::Nonterminal("coin")], vec![Symbol::Terminal("heads")]).with_weight(3.0),
+        RewriteRule::new(vec![Symbol::Nonterminal("coin")], vec![Symbol::Terminal("tails")]),
+        RewriteRule::new(vec![Symbol::Nonterminal("coin")], vec![Symbol::Terminal("edge")]).with_weight(0.0)
+    ]).with_max_iterations(1);
+
+    let rewritten = rewriter.rewrite(vec![Symbol::Nonterminal("coin"); 1000], &mut rand::thread_rng());
+
+    let heads_count = rewritten.symbols.iter().filter(|symbol| **symbol == Symbol::Terminal("heads")).count();
+
+    assert!(!rewritten.symbols.contains(&Symbol::Terminal("edge")));
+    assert!(rewritten.is_complete);
+    assert!((650..850).contains(&heads_count));
+}
+
+#[test]
+fn rewriting_multiple_symbols_sequentially()
+{
+    let rewriter = Rewriter::new(vec![
+        RewriteRule::new(vec![Symbol::Nonterminal("A"), Symbol::Nonterminal("B")], vec![Symbol::Terminal("C")])
+    ]).with_mode(RewriteMode::Sequential);
+
+    let mut rng = rand::thread_rng();
+
+    let input = vec![Symbol::Nonterminal("A"), Symbol::Nonterminal("B"), Symbol::Nonterminal("A"), Symbol::Nonterminal("B")];
+
+    assert_eq!(
+        rewriter.step(&input, &mut rng),
+        Some(vec![Symbol::Terminal("C"), Symbol::Nonterminal("A"), Symbol::Nonterminal("B")])
+    );
+
+    let rewritten = rewriter.rewrite(input, &mut rng);
+
+    assert_eq!(rewritten.symbols, [Symbol::Terminal("C"), Symbol::Terminal("C")]);
+    assert_eq!(rewritten.iterations, 2);
+    assert!(rewritten.is_complete);
+
+    assert_eq!(rewriter.step(&rewritten.symbols, &mut rng), None);
+}
+
+#[test]
+fn rewriting_context_takes_precedence()
+{
+    let rewriter = Rewriter::new(vec![
+        RewriteRule::new(vec![Symbol::Nonterminal("A")], vec![Symbol::Terminal("x")]),
+        RewriteRule::new(vec![Symbol::Nonterminal("A")], vec![Symbol::Terminal("y")])
+            .with_right_context(vec![Symbol::Nonterminal("B")]),
+        RewriteRule::new(vec![Symbol::Nonterminal("B")], vec![Symbol::Terminal("z")])
+            .with_weight(0.0)
+    ]).with_max_iterations(1);
+
+    let rewritten = rewriter.rewrite(
+        vec![Symbol::Nonterminal("A"), Symbol::Nonterminal("B"), Symbol::Nonterminal("A")],
+        &mut rand::thread_rng()
+    );
+
+    assert_eq!(rewritten.symbols, [Symbol::Terminal("y"), Symbol::Nonterminal("B"), Symbol::Terminal("x")]);
+    assert!(rewritten.is_complete);
+}
+
+#[test]
+fn rewriting_expander_output()
+{
+    let mut expander = ExpanderBuilder::new()
+        .with_new_rule("word", vec![Symbol::Terminal("i"), Symbol::Nonterminal("nasal"), Symbol::Terminal("p")])
+        .with_new_rule("nasal", vec![Symbol::Terminal("n")])
+        .build();
+
+    // Nasal assimilation: "n" becomes "m" before "p".
+    let rewriter: Rewriter<(), &str> = Rewriter::new(vec![
+        RewriteRule::new(vec![Symbol::Terminal("n")], vec![Symbol::Terminal("m")])
+            .with_right_context(vec![Symbol::Terminal("p")])
+    ]);
+
+    let output = expander.expand(vec![Symbol::Nonterminal("word")]).unwrap();
+
+    let rewritten = rewriter.rewrite(output.into_iter().map(Symbol::Terminal).collect(), &mut rand::thread_rng());
+
+    let word: String = rewritten.symbols.into_iter().map(Symbol::unwrap_term).collect();
+
+    assert_eq!(word, "imp");
+}
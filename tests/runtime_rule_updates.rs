@@ -0,0 +1,158 @@
+use branchy::{
+    Symbol,
+    Rule,
+    ExpanderBuilder,
+    WeightedRandomRuleSelector,
+    ShuffleBagRuleSelector,
+    StatsExpansionLogger
+};
+
+#[test]
+fn weighted_random_selector()
+{
+    let mut expander = ExpanderBuilder::new()
+        .with_rule(Rule::new("coin", vec![Symbol::Terminal("heads")]).with_weight(3.0))
+        .with_rule(Rule::new("coin", vec![Symbol::Terminal("tails")]).with_weight(1.0))
+        .with_rule(Rule::new("coin", vec![Symbol::Terminal("edge")]).with_weight(0.0))
+        .with_rule(Rule::new("never", vec![Symbol::Terminal("never")]).with_weight(0.0))
+        .with_rule_selector(WeightedRandomRuleSelector::new())
+        .build();
+
+    let tosses = expander.expand(vec![Symbol::Nonterminal("coin"); 1000]).unwrap();
+
+    let heads_count = tosses.iter().filter(|toss| **toss == "heads").count();
+
+    assert!(!tosses.contains(&"edge"));
+    assert!((650..850).contains(&heads_count));
+
+    assert!(expander.expand(vec![Symbol::Nonterminal("never")]).is_err());
+}
+
+#[test]
+fn expander_rule_updates()
+{
+    let mut expander = ExpanderBuilder::new()
+        .with_new_rule("animal", vec![Symbol::Terminal("cat")])
+        .with_rule_selector(WeightedRandomRuleSelector::new())
+        .build();
+
+    let expand_animal = |expander: &mut branchy::Expander<_, _, _, _>| {
+        expander.expand(vec![Symbol::Nonterminal("animal")]).unwrap()[0]
+    };
+
+    assert_eq!(expand_animal(&mut expander), "cat");
+
+    expander.add_new_rule("animal", vec![Symbol::Terminal("dog")]);
+    expander.set_rule_weight(0, 0.0);
+
+    assert_eq!(expand_animal(&mut expander), "dog");
+
+    let replaced_rule = expander.replace_rule(1, Rule::new("animal", vec![Symbol::Terminal("owl")]));
+
+    assert_eq!(replaced_rule, Rule::new("animal", vec![Symbol::Terminal("dog")]));
+    assert_eq!(expand_animal(&mut expander), "owl");
+
+    let removed_rule = expander.remove_rule(0);
+
+    assert_eq!(removed_rule, Rule::new("animal", vec![Symbol::Terminal("cat")]).with_weight(0.0));
+    assert_eq!(expander.rules().len(), 1);
+
+    expander.add_rules(vec![
+        Rule::new("animal", vec![Symbol::Terminal("fox")]),
+        Rule::new("plant", vec![Symbol::Terminal("oak")])
+    ]);
+    expander.retain_rules(|rule| rule.pattern != "animal" || rule.replacement[0] == Symbol::Terminal("fox"));
+
+    assert_eq!(expander.rules().len(), 2);
+    assert_eq!(expand_animal(&mut expander), "fox");
+
+    let updated_rule_count = expander.update_rules(|rules| {
+        rules.clear();
+        rules.push(Rule::new("animal", vec![Symbol::Terminal("hamster")]));
+
+        rules.len()
+    });
+
+    assert_eq!(updated_rule_count, 1);
+    assert_eq!(expand_animal(&mut expander), "hamster");
+}
+
+#[test]
+fn expander_added_rules()
+{
+    let mut expander = ExpanderBuilder::new()
+        .with_new_rule("sentence", vec![Symbol::Nonterminal("name"), Symbol::Nonterminal("verb")])
+        .with_new_rule("name", vec![Symbol::Terminal("Alice")])
+        .build();
+
+    assert!(expander.expand(vec![Symbol::Nonterminal("sentence")]).is_err());
+
+    expander.add_rules(vec![
+        Rule::new("verb", vec![Symbol::Terminal("sees"), Symbol::Nonterminal("object")]),
+        Rule::new("object", vec![Symbol::Nonterminal("name")])
+    ]);
+    expander.add_new_rule("name", vec![Symbol::Terminal("Bob")]);
+    expander.set_rule_weight(1, 0.0);
+
+    let mut expander = expander
+        .with_rule_selector(WeightedRandomRuleSelector::new())
+        .with_logger(StatsExpansionLogger::new());
+
+    assert_eq!(expander.expand(vec![Symbol::Nonterminal("sentence")]).unwrap(), ["Bob", "sees", "Bob"]);
+    assert_eq!(
+        expander.logger().rule_selection_counts().collect::<Vec<_>>(),
+        vec![(0, 1), (2, 1), (3, 1), (4, 2)]
+    );
+}
+
+#[test]
+fn expander_rule_updates_reset_selector_state()
+{
+    const ANIMALS: [&str; 3] = ["cat", "dog", "fox"];
+
+    let mut expander = ExpanderBuilder::from(
+        ANIMALS.iter().map(|animal| Rule::new("animal", vec![Symbol::Terminal(*animal)]))
+    )
+        .with_rule_selector(ShuffleBagRuleSelector::new())
+        .build();
+
+    for _ in 0..20 {
+        expander.expand(vec![Symbol::Nonterminal("animal")]).unwrap();
+
+        // Same number of rules, so only the notification can tell the selector that its bag is outdated.
+        let replaced_rule = expander.replace_rule(0, Rule::new("animal", vec![Symbol::Terminal("owl")]));
+
+        let mut bag_contents = expander.expand(vec![Symbol::Nonterminal("animal"); ANIMALS.len()]).unwrap();
+
+        bag_contents.sort_unstable();
+
+        assert_eq!(bag_contents, ["dog", "fox", "owl"]);
+
+        expander.replace_rule(0, replaced_rule);
+    }
+}
+
+#[test]
+fn expander_selector_and_logger_swap()
+{
+    let expander = ExpanderBuilder::new()
+        .with_new_rule("coin", vec![Symbol::Terminal("heads")])
+        .with_new_rule("coin", vec![Symbol::Terminal("tails")])
+        .build();
+
+    let mut expander = expander
+        .with_rule_selector(WeightedRandomRuleSelector::new())
+        .with_logger(StatsExpansionLogger::new());
+
+    expander.set_rule_weight(1, 0.0);
+
+    assert_eq!(expander.expand(vec![Symbol::Nonterminal("coin")]).unwrap(), ["heads"]);
+
+    let previous_logger = expander.set_logger(StatsExpansionLogger::new());
+
+    assert_eq!(previous_logger.expansion_count(), 1);
+    assert_eq!(expander.logger().expansion_count(), 0);
+
+    let _previous_selector = expander.set_rule_selector(WeightedRandomRuleSelector::new());
+    let _selector = expander.rule_selector();
+}
@@ -0,0 +1,63 @@
+use branchy::{
+    Symbol,
+    Rule,
+    ExpanderBuilder,
+    ShuffleBagRuleSelector,
+    NoImmediateRepeatRuleSelector
+};
+
+const ANIMALS: [&str; 4] = ["cat", "dog", "fox", "owl"];
+
+#[test]
+fn shuffle_bag_selector()
+{
+    let rules: Vec<_> = ANIMALS.iter()
+        .map(|animal| Rule::new("animal", vec![Symbol::Terminal(*animal)]))
+        .collect();
+
+    let mut expander = ExpanderBuilder::from(rules)
+        .with_rule_selector(ShuffleBagRuleSelector::new())
+        .build();
+
+    let mut previous_animal = None;
+
+    for _ in 0..16 {
+        let mut bag_contents: Vec<_> = (0..ANIMALS.len())
+            .map(|_| expander.expand(vec![Symbol::Nonterminal("animal")]).unwrap()[0])
+            .collect();
+
+        assert_ne!(previous_animal, bag_contents.first().copied());
+
+        previous_animal = bag_contents.last().copied();
+
+        bag_contents.sort_unstable();
+
+        assert_eq!(bag_contents, ANIMALS);
+    }
+}
+
+#[test]
+fn no_immediate_repeat_selector()
+{
+    let rules: Vec<_> = ANIMALS.iter()
+        .map(|animal| Rule::new("animal", vec![Symbol::Terminal(*animal)]))
+        .collect();
+
+    let mut expander = ExpanderBuilder::from(rules)
+        .with_new_rule("pet", vec![Symbol::Terminal("hamster")])
+        .with_rule_selector(NoImmediateRepeatRuleSelector::new())
+        .build();
+
+    let expansion_result = expander.expand(
+        (0..64)
+            .map(|_| Symbol::Nonterminal("animal"))
+            .chain(std::iter::once(Symbol::Nonterminal("pet")))
+            .chain(std::iter::once(Symbol::Nonterminal("pet")))
+            .collect()
+    ).unwrap();
+
+    assert!(
+        expansion_result[..64].windows(2).all(|pair| pair[0] != pair[1])
+    );
+    assert_eq!(expansion_result[64..], ["hamster", "hamster"]);
+}
@@ -0,0 +1,109 @@
+use branchy::{
+    Symbol,
+    Rule,
+    ExpanderBuilder,
+    RecoveryPolicy,
+    StatsExpansionLogger
+};
+
+#[test]
+fn stats_logger()
+{
+    let rules = vec![
+        Rule::new("greeting", vec![Symbol::Terminal("Hello"), Symbol::Nonterminal("name")]),
+        Rule::new("greeting", vec![Symbol::Terminal("Hi"), Symbol::Nonterminal("name"), Symbol::Nonterminal("name")]),
+        Rule::new("name", vec![Symbol::Terminal("Alice")]),
+        Rule::new("farewell", vec![Symbol::Terminal("Bye")])
+    ];
+
+    let mut expander = ExpanderBuilder::from(rules.clone())
+        .with_logger(StatsExpansionLogger::new())
+        .build();
+
+    for _ in 0..32 {
+        expander.expand(vec![Symbol::Nonterminal("greeting")]).unwrap();
+    }
+
+    expander.expand(vec![Symbol::Nonterminal("name"), Symbol::Nonterminal("title")]).unwrap_err();
+
+    let stats = expander.logger();
+
+    assert_eq!(stats.expansion_count(), 33);
+    assert_eq!(stats.success_count(), 32);
+    assert_eq!(stats.nonterm_expansion_failure_count(), 1);
+    assert_eq!(stats.nonterm_failure_count(&"title"), 1);
+    assert_eq!(stats.max_iterations_reached_count(), 0);
+
+    let hello_count = stats.rule_selection_count(0);
+    let hi_count    = stats.rule_selection_count(1);
+
+    assert_eq!(hello_count + hi_count, 32);
+    assert_eq!(stats.rule_selection_count(3), 0);
+    assert_eq!(
+        stats.rule_selection_counts().map(|(rule_idx, _)| rule_idx).collect::<Vec<_>>(),
+        (0..3).filter(|rule_idx| stats.rule_selection_count(*rule_idx) > 0).collect::<Vec<_>>()
+    );
+    assert_eq!(stats.nonterm_expansion_count(&"greeting"), 32);
+    assert_eq!(stats.nonterm_expansion_count(&"name"), hello_count + 2*hi_count + 1);
+    assert_eq!(
+        stats.output_length_histogram().values().sum::<usize>(),
+        32
+    );
+    assert_eq!(stats.output_length_histogram().get(&2).copied().unwrap_or(0), hello_count);
+
+    let coverage_report = stats.coverage_report(expander.rules());
+
+    assert!(!coverage_report.is_complete());
+    assert_eq!(coverage_report.unused_rules, vec![&rules[3]]);
+
+    expander.logger_mut().reset();
+
+    assert_eq!(expander.logger().expansion_count(), 0);
+    assert_eq!(expander.logger().coverage_report(&rules).coverage(), 0.0);
+}
+
+#[test]
+fn stats_logger_counts_recoveries_separately_from_failures()
+{
+    let mut expander = ExpanderBuilder::new()
+        .with_new_rule("greeting", vec![Symbol::Terminal("Hello"), Symbol::Nonterminal("name"), Symbol::Nonterminal("name")])
+        .with_recovery_policy(RecoveryPolicy::Placeholder("stranger"))
+        .with_logger(StatsExpansionLogger::new())
+        .build();
+
+    for _ in 0..3 {
+        expander.expand(vec![Symbol::Nonterminal("greeting")]).unwrap();
+    }
+
+    expander.set_recovery_policy(RecoveryPolicy::Fail);
+
+    expander.expand(vec![Symbol::Nonterminal("greeting")]).unwrap_err();
+
+    let stats = expander.logger();
+
+    assert_eq!(stats.success_count(), 3);
+    assert_eq!(stats.nonterm_recovery_count(&"name"), 6);
+    assert_eq!(stats.nonterm_recovery_counts().collect::<Vec<_>>(), vec![(&"name", 6)]);
+    assert_eq!(stats.nonterm_failure_count(&"name"), 1);
+    assert_eq!(stats.nonterm_expansion_failure_count(), 1);
+}
+
+#[test]
+fn stats_logger_counts_rules_by_index()
+{
+    // Rules, which are not equal to themselves, are still counted.
+    let rules = vec![
+        Rule::new("name", vec![Symbol::Terminal("Alice")]).with_weight(f64::NAN)
+    ];
+
+    let mut expander = ExpanderBuilder::from(rules)
+        .with_logger(StatsExpansionLogger::new())
+        .build();
+
+    for _ in 0..3 {
+        expander.expand(vec![Symbol::Nonterminal("name")]).unwrap();
+    }
+
+    assert_eq!(expander.logger().rule_selection_counts().collect::<Vec<_>>(), vec![(0, 3)]);
+    assert!(expander.logger().coverage_report(expander.rules()).is_complete());
+}
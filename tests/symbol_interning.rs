@@ -0,0 +1,127 @@
+use std::sync::{
+    Arc,
+    Mutex
+};
+
+use branchy::{
+    Symbol,
+    Rule,
+    ExpanderBuilder,
+    ErrorKind,
+    ModifierRegistry,
+    SymbolTable,
+    TextFormatter,
+    parse_grammar
+};
+
+#[test]
+fn symbol_table_interning()
+{
+    let mut symbol_table = SymbolTable::new();
+
+    assert!(symbol_table.is_empty());
+
+    let hello = symbol_table.intern("hello");
+    let world = symbol_table.intern("world");
+
+    assert_ne!(hello, world);
+    assert_eq!(symbol_table.intern("hello"), hello);
+    assert_eq!(symbol_table.len(), 2);
+    assert_eq!(world.index(), 1);
+
+    assert_eq!(symbol_table.get("world"), Some(world));
+    assert_eq!(symbol_table.get("moon"), None);
+
+    assert_eq!(symbol_table.resolve(hello), "hello");
+    assert_eq!(symbol_table.value(world), Some("world"));
+    assert_eq!(SymbolTable::new().value(world), None);
+}
+
+#[test]
+fn compiled_grammar_expansion()
+{
+    let rules = parse_grammar(r#"
+        sentence -> <subject> "sees" <subject> "."
+        subject  -> "the" "cat" | "the" "dog" [2]
+    "#).unwrap();
+
+    let mut symbol_table = SymbolTable::new();
+
+    let compiled_rules = symbol_table.compile_rules(&rules);
+
+    assert_eq!(compiled_rules.len(), rules.len());
+    assert_eq!(compiled_rules[2].weight, 2.0);
+
+    for (rule, compiled_rule) in rules.iter().zip(&compiled_rules) {
+        assert_eq!(symbol_table.resolve_rule(compiled_rule).to_string(), rule.to_string());
+    }
+
+    let input = symbol_table.compile_symbols(&[Symbol::Nonterminal("sentence"), Symbol::Terminal("!")]);
+
+    let mut expander = ExpanderBuilder::from(compiled_rules).build();
+
+    let output = expander.expand(input).unwrap();
+
+    let words = symbol_table.resolve_all(&output);
+
+    assert_eq!(words.len(), 7);
+    assert!(words[1] == "cat" || words[1] == "dog");
+    assert_eq!(words[2], "sees");
+    assert_eq!(words[5..], [".", "!"]);
+
+    let text = TextFormatter::new().format(&words);
+
+    assert!(text.starts_with("The "));
+    assert!(text.ends_with(".!"));
+}
+
+#[test]
+fn compiled_rule_keeps_modifiers()
+{
+    let rule: Rule<String, String> = Rule::new("greeting".to_string(), vec![Symbol::Nonterminal("name".to_string())])
+        .with_modifier(0, "capitalize");
+
+    let mut symbol_table = SymbolTable::new();
+
+    let compiled_rule = symbol_table.compile_rule(&rule);
+
+    assert_eq!(compiled_rule.symbol_modifiers(0).collect::<Vec<_>>(), ["capitalize"]);
+    assert_eq!(compiled_rule.replacement, [Symbol::Nonterminal(symbol_table.get("name").unwrap())]);
+}
+
+#[test]
+fn compiled_grammar_expansion_with_modifiers()
+{
+    let rules = parse_grammar(r#"
+        sentence -> <name>.capitalize "saw" <animal>.a "and two" <animal>.s
+        name     -> "alice"
+        animal   -> "old fox"
+    "#).unwrap();
+
+    let symbol_table = Arc::new(Mutex::new(SymbolTable::new()));
+
+    let compiled_rules = symbol_table.lock().unwrap().compile_rules(&rules);
+    let input          = symbol_table.lock().unwrap().compile_symbols(&[Symbol::<_, &str>::Nonterminal("sentence")]);
+
+    let mut expander = ExpanderBuilder::from(compiled_rules.clone()).build();
+
+    let error = expander.expand(input.clone()).unwrap_err();
+
+    assert!(matches!(error.kind, ErrorKind::UnknownModifier(_)));
+
+    let mut expander = ExpanderBuilder::from(compiled_rules)
+        .with_modifier_registry(ModifierRegistry::with_builtin_modifiers().compile(&symbol_table))
+        .build();
+
+    for _ in 0..2 {
+        let output = expander.expand(input.clone()).unwrap();
+
+        assert_eq!(
+            symbol_table.lock().unwrap().resolve_all(&output),
+            ["Alice", "saw", "an", "old fox", "and two", "old foxes"]
+        );
+    }
+
+    assert!(symbol_table.lock().unwrap().get("alice").is_some());
+    assert!(symbol_table.lock().unwrap().get("old foxes").is_some());
+}
@@ -0,0 +1,128 @@
+use branchy::{
+    Symbol,
+    Rule,
+    Expander,
+    ExpanderBuilder,
+    ModifierRegistry,
+    RecoveryPolicy,
+    UniformRandomRuleSelector,
+    NullExpansionLogger,
+    StatsExpansionLogger,
+    ErrorKind
+};
+
+fn nonterm(value: &str) -> Symbol<String, String> {
+    Symbol::Nonterminal(value.to_string())
+}
+
+fn term(value: &str) -> Symbol<String, String> {
+    Symbol::Terminal(value.to_string())
+}
+
+#[test]
+fn builtin_modifiers()
+{
+    let mut expander = ExpanderBuilder::new()
+        .with_rule(
+            Rule::new("sentence".to_string(), vec![nonterm("name"), term("saw"), nonterm("animal"), term("and two"), nonterm("animal")])
+                .with_modifier(0, "capitalize")
+                .with_modifier(2, "a")
+                .with_modifier(4, "s")
+        )
+        .with_rule(Rule::new("shout".to_string(), vec![nonterm("phrase")]).with_modifier(0, "upper"))
+        .with_rule(Rule::new("phrase".to_string(), vec![nonterm("animal"), term("run")]).with_modifier(0, "s"))
+        .with_rule(Rule::new("title".to_string(), vec![term("the"), nonterm("animal")]).with_modifier(1, "in_quotes"))
+        .with_rule(Rule::new("title_case".to_string(), vec![nonterm("title")]).with_modifier(0, "capitalize_all"))
+        .with_new_rule("name".to_string(), vec![term("alice")])
+        .with_new_rule("animal".to_string(), vec![term("old fox")])
+        .with_modifier_registry(ModifierRegistry::with_builtin_modifiers())
+        .build();
+
+    assert_eq!(
+        expander.expand(vec![nonterm("sentence")]).unwrap(),
+        ["Alice", "saw", "an", "old fox", "and two", "old foxes"]
+    );
+    assert_eq!(expander.expand(vec![nonterm("shout"), term("now")]).unwrap(), ["OLD FOXES", "RUN", "now"]);
+    assert_eq!(expander.expand(vec![nonterm("title_case")]).unwrap(), ["The", "\"Old Fox\""]);
+}
+
+#[test]
+fn custom_modifiers()
+{
+    let mut expander = ExpanderBuilder::new()
+        .with_rule(
+            Rule::new("list", vec![Symbol::Nonterminal("items"), Symbol::Terminal("end")])
+                .with_modifier(0, "reverse")
+                .with_modifier(0, "twice")
+                .with_modifier(1, "twice")
+        )
+        .with_new_rule("items", vec![Symbol::Terminal("a"), Symbol::Nonterminal("more")])
+        .with_new_rule("more", vec![Symbol::Terminal("b"), Symbol::Terminal("c")])
+        .with_modifier_registry(
+            ModifierRegistry::new()
+                .with_modifier("reverse", |values: Vec<&str>| values.into_iter().rev().collect())
+        )
+        .build();
+
+    let error = expander.expand(vec![Symbol::Nonterminal("list")]).unwrap_err();
+
+    assert!(matches!(&error.kind, ErrorKind::UnknownModifier(name) if name == "twice"));
+    assert_eq!(error.to_string(), "unknown modifier \"twice\"");
+
+    expander.modifier_registry_mut().register("twice", |values: Vec<&str>| [values.clone(), values].concat());
+
+    assert!(expander.modifier_registry().contains("twice"));
+    assert_eq!(
+        expander.expand(vec![Symbol::Terminal("start"), Symbol::Nonterminal("list")]).unwrap(),
+        ["start", "c", "b", "a", "c", "b", "a", "end", "end"]
+    );
+
+    // Derivations describe rules as they are, so modifiers are not applied.
+    let derivation = expander.expand_derivation(vec![Symbol::Nonterminal("list")]).unwrap();
+
+    assert_eq!(derivation.trees.len(), 1);
+}
+
+#[test]
+fn unknown_modifiers_are_logged_as_failures()
+{
+    let mut expander = ExpanderBuilder::new()
+        .with_rule(Rule::new("greeting", vec![Symbol::Nonterminal("name")]).with_modifier(0, "twice"))
+        .with_new_rule("name", vec![Symbol::Terminal("Alice")])
+        .with_modifier_registry(ModifierRegistry::new())
+        .with_logger(StatsExpansionLogger::new())
+        .build();
+
+    assert!(expander.expand(vec![Symbol::Nonterminal("greeting")]).is_err());
+
+    assert_eq!(expander.logger().expansion_count(), 1);
+    assert_eq!(expander.logger().success_count(), 0);
+    assert_eq!(expander.logger().nonterm_failure_count(&"greeting"), 1);
+}
+
+#[test]
+fn modifiers_of_recovered_nonterms()
+{
+    let mut expander = ExpanderBuilder::new()
+        .with_rule(
+            Rule::new("greeting".to_string(), vec![term("hello"), nonterm("name")])
+                .with_modifier(1, "upper")
+        )
+        .with_recovery_policy(RecoveryPolicy::Placeholder("stranger".to_string()))
+        .with_modifier_registry(ModifierRegistry::with_builtin_modifiers())
+        .build();
+
+    assert_eq!(expander.expand(vec![nonterm("greeting")]).unwrap(), ["hello", "STRANGER"]);
+}
+
+#[test]
+fn expander_with_modifiers_is_send_and_sync()
+{
+    fn assert_send_sync<S: Send + Sync>(_: &S) {}
+
+    let expander: Expander<String, String, UniformRandomRuleSelector, NullExpansionLogger> = ExpanderBuilder::new()
+        .with_modifier_registry(ModifierRegistry::with_builtin_modifiers())
+        .build();
+
+    assert_send_sync(&expander);
+}
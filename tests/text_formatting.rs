@@ -0,0 +1,121 @@
+use branchy::{
+    Symbol,
+    Rule,
+    ExpanderBuilder,
+    Language,
+    English,
+    TextFormatter
+};
+
+#[test]
+fn spacing_and_capitalization()
+{
+    let formatter = TextFormatter::new();
+
+    assert_eq!(
+        formatter.format(vec!["hello", ",", "world", "!", "how are", "you", "?", "(", "fine", ")", "thanks", "."]),
+        "Hello, world! How are you? (Fine) thanks."
+    );
+    assert_eq!(formatter.format(vec!["  the", "", "dog", "'s", "bone", "~", "yard", "..."]), "The dog's boneyard...");
+    assert_eq!(formatter.format(Vec::<&str>::new()), "");
+
+    let formatter = TextFormatter::new()
+        .with_sentence_capitalization(false)
+        .with_glue_marker("+");
+
+    assert_eq!(formatter.format(vec!["hello", "+", "world", ".", "bye", "~", "now"]), "helloworld. bye ~ now");
+}
+
+#[test]
+fn english_articles()
+{
+    let formatter = TextFormatter::new();
+
+    let cases = [
+        ("a apple",        "An apple"),
+        ("an pear",        "A pear"),
+        ("an unicorn",     "A unicorn"),
+        ("a hour",         "An hour"),
+        ("a unimportant",  "An unimportant"),
+        ("a FBI agent",    "An FBI agent"),
+        ("an NASA probe",  "A NASA probe"),
+        ("an URL",         "A URL"),
+        ("a 8",            "An 8"),
+        ("a 18,000",       "An 18,000"),
+        ("an 180",         "A 180"),
+        ("a \"elephant\"", "An \"elephant\""),
+        ("AN CAT",         "A CAT")
+    ];
+
+    for (input, expected) in &cases {
+        assert_eq!(formatter.format(vec![*input]), *expected, "formatting {:?}", input);
+    }
+
+    assert_eq!(
+        TextFormatter::new().with_article_agreement(false).format(vec!["a", "apple"]),
+        "A apple"
+    );
+}
+
+#[test]
+fn english_plurals()
+{
+    let formatter = TextFormatter::new().with_sentence_capitalization(false);
+
+    let cases = [
+        ("cat",    "cats"),
+        ("box",    "boxes"),
+        ("church", "churches"),
+        ("city",   "cities"),
+        ("day",    "days"),
+        ("wolf",   "wolves"),
+        ("child",  "children"),
+        ("sheep",  "sheep"),
+        ("Dog",    "Dogs"),
+        ("BUS",    "BUSES")
+    ];
+
+    for (singular, plural) in &cases {
+        assert_eq!(formatter.format(vec![*singular, "~s"]), *plural);
+    }
+
+    assert_eq!(formatter.format(vec!["two big", "mouse", "~s"]), "two big mice");
+    assert_eq!(formatter.format(vec!["~s", "cat"]), "cat");
+}
+
+#[test]
+fn custom_language()
+{
+    struct French;
+
+    impl Language for French {
+        fn attaches_to_previous(&self, token: &str) -> bool {
+            token.starts_with(['.', ','])
+        }
+    }
+
+    let formatter = TextFormatter::with_language(French).with_plural_marker("<pl>");
+
+    assert_eq!(formatter.format(vec!["bonjour", "!", "un", "ami", "<pl>", "."]), "Bonjour ! Un ami.");
+}
+
+#[test]
+fn formatting_expansion_output()
+{
+    let rules = vec![
+        Rule::new(
+            "sentence",
+            vec![Symbol::Nonterminal("name"), Symbol::Terminal("comes from a"), Symbol::Nonterminal("location"), Symbol::Terminal(".")]
+        ),
+        Rule::new("name", vec![Symbol::Terminal("bob")]),
+        Rule::new("location", vec![Symbol::Terminal("old"), Symbol::Terminal("village")])
+    ];
+
+    let mut expander = ExpanderBuilder::from(rules).build();
+
+    let output = expander.expand(vec![Symbol::Nonterminal("sentence")]).unwrap();
+
+    assert_eq!(output.join(" "), "bob comes from a old village .");
+    assert_eq!(TextFormatter::new().format(&output), "Bob comes from an old village.");
+    assert_eq!(English.pluralize("village"), "villages");
+}
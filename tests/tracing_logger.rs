@@ -0,0 +1,116 @@
+#![cfg(feature = "tracing")]
+
+use std::sync::{
+    Arc,
+    Mutex,
+    atomic::{AtomicU64, Ordering}
+};
+
+use tracing::{
+    Event,
+    Metadata,
+    span,
+    field::{Field, Visit}
+};
+
+use branchy::{
+    Symbol,
+    ExpanderBuilder,
+    TracingExpansionLogger
+};
+
+#[test]
+fn tracing_logger()
+{
+    let subscriber = RecordingSubscriber::default();
+    let records    = Arc::clone(&subscriber.records);
+
+    let mut expander = ExpanderBuilder::new()
+        .with_new_rule("greeting", vec![Symbol::Terminal("Hello"), Symbol::Nonterminal("name")])
+        .with_new_rule("name", vec![Symbol::Terminal("world")])
+        .with_logger(TracingExpansionLogger::new())
+        .build();
+
+    tracing::subscriber::with_default(subscriber, || {
+        expander.expand(vec![Symbol::Nonterminal("greeting")]).unwrap();
+        expander.expand(vec![Symbol::Nonterminal("farewell")]).unwrap_err();
+    });
+
+    assert_eq!(
+        *records.lock().unwrap(),
+        vec![
+            "new span expand",
+            "event expansion started",
+            "enter expand",
+            "event nonterminal expanded",
+            "exit expand",
+            "enter expand",
+            "event nonterminal expanded",
+            "exit expand",
+            "enter expand",
+            "event input fully expanded",
+            "exit expand",
+            "new span expand",
+            "event expansion started",
+            "enter expand",
+            "event failed to expand nonterminal",
+            "exit expand"
+        ]
+    );
+}
+
+//
+// Service types
+//
+
+#[derive(Default)]
+struct RecordingSubscriber {
+    records:  Arc<Mutex<Vec<String>>>,
+    next_id:  AtomicU64
+}
+
+impl tracing::Subscriber for RecordingSubscriber {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target() == "branchy"
+    }
+
+    fn new_span(&self, attributes: &span::Attributes<'_>) -> span::Id {
+        self.records.lock().unwrap().push(format!("new span {}", attributes.metadata().name()));
+
+        span::Id::from_u64(self.next_id.fetch_add(1, Ordering::SeqCst) + 1)
+    }
+
+    fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {
+        // Empty
+    }
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {
+        // Empty
+    }
+
+    fn event(&self, event: &Event<'_>) {
+        let mut message_visitor = MessageVisitor(String::new());
+
+        event.record(&mut message_visitor);
+
+        self.records.lock().unwrap().push(format!("event {}", message_visitor.0));
+    }
+
+    fn enter(&self, _span: &span::Id) {
+        self.records.lock().unwrap().push(String::from("enter expand"));
+    }
+
+    fn exit(&self, _span: &span::Id) {
+        self.records.lock().unwrap().push(String::from("exit expand"));
+    }
+}
+
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = format!("{:?}", value);
+        }
+    }
+}
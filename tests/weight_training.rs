@@ -0,0 +1,150 @@
+use branchy::{
+    Symbol,
+    Rule,
+    ExpanderBuilder,
+    WeightedRandomRuleSelector,
+    WeightTrainer,
+    ProbabilisticGrammar,
+    RuleDistribution
+};
+
+const EPSILON: f64 = 1e-6;
+
+#[test]
+fn weight_training_unambiguous_counts()
+{
+    let rules = vec![
+        Rule::new("sentence", vec![Symbol::Nonterminal("subject"), Symbol::Terminal("sleeps")]),
+        Rule::new("subject", vec![Symbol::Terminal("the"), Symbol::Terminal("cat")]),
+        Rule::new("subject", vec![Symbol::Terminal("the"), Symbol::Terminal("dog")]),
+        Rule::new("subject", vec![Symbol::Terminal("a"), Symbol::Nonterminal("adjective"), Symbol::Terminal("owl")]),
+        Rule::new("adjective", vec![Symbol::Terminal("old")]),
+        Rule::new("adjective", vec![Symbol::Terminal("wise")])
+    ];
+
+    let corpus = vec![
+        vec!["the", "cat", "sleeps"],
+        vec!["the", "cat", "sleeps"],
+        vec!["the", "dog", "sleeps"],
+        vec!["a", "wise", "owl", "sleeps"]
+    ];
+
+    let report = WeightTrainer::new(&rules).train(&[Symbol::Nonterminal("sentence")], &corpus);
+
+    let weights: Vec<_> = report.rules.iter().map(|rule| rule.weight).collect();
+
+    assert_eq!(weights, [1.0, 0.5, 0.25, 0.25, 0.0, 1.0]);
+    assert_eq!(report.rule_counts, [4.0, 2.0, 1.0, 1.0, 0.0, 1.0]);
+    assert!(report.converged);
+    assert_eq!(report.iterations, 2);
+    assert!(report.unparsed.is_empty());
+    assert!((report.log_likelihood - (0.5f64.ln() * 2.0 + 0.25f64.ln() * 2.0)).abs() < EPSILON);
+
+    let mut expander = ExpanderBuilder::new()
+        .with_rules(report.rules)
+        .with_rule_selector(WeightedRandomRuleSelector::new())
+        .build();
+
+    for _ in 0..10 {
+        let output = expander.expand(vec![Symbol::Nonterminal("sentence")]).unwrap();
+
+        assert_ne!(output, ["a", "old", "owl", "sleeps"]);
+    }
+}
+
+#[test]
+fn weight_training_ambiguous_expectation_maximization()
+{
+    let rules = vec![
+        Rule::new("S", vec![Symbol::Terminal("x"), Symbol::Nonterminal("T")]).with_weight(3.0),
+        Rule::new("S", vec![Symbol::Terminal("x"), Symbol::Nonterminal("U")]),
+        Rule::new("T", vec![Symbol::Terminal("a")]),
+        Rule::new("T", vec![Symbol::Terminal("b")]),
+        Rule::new("U", vec![Symbol::Terminal("a")]),
+        Rule::new("U", vec![Symbol::Terminal("c")])
+    ];
+
+    let corpus = vec![
+        vec!["x", "a"],
+        vec!["x", "a"],
+        vec!["x", "b"],
+        vec!["x", "c"]
+    ];
+
+    let input = [Symbol::Nonterminal("S")];
+
+    let report = WeightTrainer::new(&rules)
+        .with_initial_distribution(RuleDistribution::Weighted)
+        .train(&input, &corpus);
+
+    assert!(report.converged);
+
+    // "x a" is attributed to "T" and "U" in proportion to its initial probability via each of them.
+    assert!((report.rules[0].weight - 0.625).abs() < EPSILON);
+    assert!((report.rules[2].weight - 0.6).abs() < EPSILON);
+    assert!((report.rules[4].weight - 1.0 / 3.0).abs() < EPSILON);
+
+    let grammar = ProbabilisticGrammar::new(&report.rules, RuleDistribution::Weighted);
+
+    assert!((grammar.sentence_probability(&input, &["x", "a"]) - 0.5).abs() < EPSILON);
+    assert!((grammar.sentence_probability(&input, &["x", "b"]) - 0.25).abs() < EPSILON);
+    assert!((grammar.sentence_probability(&input, &["x", "c"]) - 0.25).abs() < EPSILON);
+}
+
+#[test]
+fn weight_training_empty_and_unit_rules()
+{
+    let rules = vec![
+        Rule::new("As", vec![Symbol::Terminal("a"), Symbol::Nonterminal("As")]),
+        Rule::new("As", vec![]),
+        Rule::new("B", vec![Symbol::Nonterminal("B")]),
+        Rule::new("B", vec![Symbol::Terminal("b")])
+    ];
+
+    let corpus: Vec<Vec<&str>> = vec![
+        vec!["a", "a", "a", "b"],
+        vec!["b"]
+    ];
+
+    let report = WeightTrainer::new(&rules)
+        .with_max_iterations(1)
+        .train(&[Symbol::Nonterminal("As"), Symbol::Nonterminal("B")], &corpus);
+
+    assert!((report.rules[0].weight - 0.6).abs() < EPSILON);
+    assert!((report.rules[1].weight - 0.4).abs() < EPSILON);
+
+    // Any number of unit expansions of "B" produces "b", and their expected number with equal weights is 1.
+    assert!((report.rule_counts[2] - 2.0).abs() < EPSILON);
+    assert!((report.rule_counts[3] - 2.0).abs() < EPSILON);
+    assert!((report.rules[2].weight - 0.5).abs() < EPSILON);
+}
+
+#[test]
+fn weight_training_smoothing_and_unused_rules()
+{
+    let rules = vec![
+        Rule::new("greeting", vec![Symbol::Terminal("hello"), Symbol::Nonterminal("name")]),
+        Rule::new("name", vec![Symbol::Terminal("Alice")]),
+        Rule::new("name", vec![Symbol::Terminal("Bob")]),
+        Rule::new("name", vec![Symbol::Terminal("Carol")]),
+        Rule::new("name", vec![Symbol::Terminal("Mallory")]).with_weight(0.0),
+        Rule::new("farewell", vec![Symbol::Terminal("bye")]).with_weight(3.0),
+        Rule::new("farewell", vec![Symbol::Terminal("farewell")])
+    ];
+
+    let corpus = vec![
+        vec!["hello", "Alice"],
+        vec!["hello", "Alice"],
+        vec!["hello", "Mallory"]
+    ];
+
+    let report = WeightTrainer::new(&rules)
+        .with_initial_distribution(RuleDistribution::Weighted)
+        .with_smoothing(1.0)
+        .train(&[Symbol::Nonterminal("greeting")], &corpus);
+
+    let weights: Vec<_> = report.rules.iter().map(|rule| rule.weight).collect();
+
+    assert_eq!(weights, [1.0, 0.6, 0.2, 0.2, 0.0, 0.75, 0.25]);
+    assert_eq!(report.unparsed, [2]);
+}